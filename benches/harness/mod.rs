@@ -0,0 +1,217 @@
+//! Benchmark harness in the style of Criterion, without the dependency.
+//!
+//! Each benchmark is warmed up, then timed over a number of samples, each running the routine enough times
+//! to be measurable. The median time per iteration is compared to a saved baseline, if there is one.
+//!
+//! Arguments (after "cargo bench --"):
+//! - `<filter>`: Only run benchmarks whose name contains it.
+//! - `--save-baseline <name>`: Save the results of the benchmarks run as baseline `name`.
+//! - `--baseline <name>`: Compare against baseline `name`. Defaults to "base".
+//! - `--threshold <percent>`: Change in median counted as a regression (or improvement). Defaults to 10.
+//!
+//! Baselines are kept in "target/bench/<name>/", one CSV file per benchmark target.
+//! Exits with code 1 if any benchmark regressed.
+
+use std::{
+    collections::HashMap,
+    fs,
+    hint::black_box,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+const WARM_UP: Duration = Duration::from_millis(300);
+const SAMPLES: usize = 20;
+const SAMPLE_TIME: Duration = Duration::from_millis(50);
+
+struct Measurement {
+    name: String,
+    median: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+pub struct Bench {
+    /// Name of the benchmark target, naming its baseline file.
+    target: &'static str,
+    filter: Option<String>,
+    baseline: String,
+    save_baseline: Option<String>,
+    threshold: f64,
+    measurements: Vec<Measurement>,
+}
+
+impl Bench {
+    pub fn from_args(target: &'static str) -> Self {
+        let mut bench = Self {
+            target,
+            filter: None,
+            baseline: "base".to_string(),
+            save_baseline: None,
+            threshold: 10.0,
+            measurements: Vec::new(),
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next().unwrap_or_else(|| {
+                    eprintln!("Missing value for \"{arg}\".");
+                    std::process::exit(2);
+                })
+            };
+
+            match arg.as_str() {
+                "--baseline" => bench.baseline = value(),
+                "--save-baseline" => bench.save_baseline = Some(value()),
+                "--threshold" => {
+                    bench.threshold = value().parse().unwrap_or_else(|_| {
+                        eprintln!("Expected a percentage for \"--threshold\".");
+                        std::process::exit(2);
+                    })
+                }
+                // Passed by "cargo bench".
+                "--bench" => (),
+                _ if arg.starts_with("--") => {
+                    eprintln!("Unknown argument \"{arg}\".");
+                    std::process::exit(2);
+                }
+                _ => bench.filter = Some(arg),
+            }
+        }
+
+        bench
+    }
+
+    /// Time `routine`, unless filtered out. Its result is passed through [black_box], so that it is not optimized away.
+    pub fn run<R>(&mut self, name: &str, mut routine: impl FnMut() -> R) {
+        if self
+            .filter
+            .as_ref()
+            .is_some_and(|filter| !name.contains(filter.as_str()))
+        {
+            return;
+        }
+
+        // Warm up, and estimate the time of an iteration.
+        let start = Instant::now();
+        let mut iterations_warm_up = 0u32;
+        while start.elapsed() < WARM_UP {
+            black_box(routine());
+            iterations_warm_up += 1;
+        }
+        let estimate = start.elapsed() / iterations_warm_up;
+        let iterations =
+            (SAMPLE_TIME.as_nanos() / estimate.as_nanos().max(1)).clamp(1, u32::MAX as u128) as u32;
+
+        let mut samples = (0..SAMPLES)
+            .map(|_| {
+                let start = Instant::now();
+                for _ in 0..iterations {
+                    black_box(routine());
+                }
+                start.elapsed() / iterations
+            })
+            .collect::<Vec<_>>();
+        samples.sort_unstable();
+
+        let measurement = Measurement {
+            name: name.to_string(),
+            median: samples[SAMPLES / 2],
+            min: samples[0],
+            max: samples[SAMPLES - 1],
+        };
+        println!(
+            "{:<40} time: {:>10.2?} [{:.2?} {:.2?}]",
+            measurement.name, measurement.median, measurement.min, measurement.max
+        );
+        self.measurements.push(measurement);
+    }
+
+    fn baseline_path(&self, baseline: &str) -> PathBuf {
+        let target_dir = std::env::var_os("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target"));
+        target_dir
+            .join("bench")
+            .join(baseline)
+            .join(format!("{}.csv", self.target))
+    }
+
+    /// Medians in nanoseconds, by benchmark name.
+    fn load_baseline(&self) -> Option<HashMap<String, u128>> {
+        let csv = fs::read_to_string(self.baseline_path(&self.baseline)).ok()?;
+
+        Some(
+            csv.lines()
+                .skip(1)
+                .filter_map(|line| {
+                    let (name, median) = line.rsplit_once(',')?;
+                    Some((name.to_string(), median.parse().ok()?))
+                })
+                .collect(),
+        )
+    }
+
+    /// Compare with the baseline, and save the results if asked to.
+    pub fn finish(self) {
+        let mut regressions = 0;
+
+        match self.load_baseline() {
+            Some(baseline) => {
+                println!("\nCompared to baseline \"{}\":", self.baseline);
+
+                for measurement in self.measurements.iter() {
+                    let Some(&median_baseline) = baseline.get(&measurement.name) else {
+                        println!("{:<40} new", measurement.name);
+                        continue;
+                    };
+
+                    let change = (measurement.median.as_nanos() as f64
+                        / median_baseline.max(1) as f64
+                        - 1.0)
+                        * 100.0;
+                    let verdict = if change > self.threshold {
+                        regressions += 1;
+                        "REGRESSED"
+                    } else if change < -self.threshold {
+                        "improved"
+                    } else {
+                        "no change"
+                    };
+                    println!(
+                        "{:<40} {:>+7.1}% ({verdict}, was {:.2?})",
+                        measurement.name,
+                        change,
+                        Duration::from_nanos(median_baseline as u64)
+                    );
+                }
+            }
+            None => println!("\nNo baseline \"{}\" to compare to.", self.baseline),
+        }
+
+        if let Some(save_baseline) = self.save_baseline.as_ref() {
+            let path = self.baseline_path(save_baseline);
+            let mut csv = String::from("name,median_ns\n");
+            for measurement in self.measurements.iter() {
+                csv += &format!("{},{}\n", measurement.name, measurement.median.as_nanos());
+            }
+
+            fs::create_dir_all(path.parent().unwrap())
+                .and_then(|_| fs::write(&path, csv))
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to save baseline to {}. {err}", path.display());
+                    std::process::exit(2);
+                });
+            println!("Saved baseline \"{save_baseline}\" to {}.", path.display());
+        }
+
+        if regressions > 0 {
+            println!(
+                "{regressions} benchmark(s) regressed by more than {}%.",
+                self.threshold
+            );
+            std::process::exit(1);
+        }
+    }
+}
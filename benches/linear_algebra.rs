@@ -0,0 +1,39 @@
+mod harness;
+
+use std::hint::black_box;
+
+use linear_algebra::{
+    matrix::Matrix,
+    quaternion::{self, Quaternion},
+    vector::VectorRow,
+};
+
+fn main() {
+    let mut bench = harness::Bench::from_args("linear_algebra");
+
+    let a = Matrix::from([
+        [1.0, 2.0, 3.0, 4.0],
+        [5.0, 6.0, 7.0, 8.0],
+        [9.0, 10.0, 11.0, 12.0],
+        [13.0, 14.0, 15.0, 16.0],
+    ]);
+    let b = a.transpose();
+    bench.run("matrix/mul_4x4", || black_box(&a) * black_box(&b));
+
+    let row = Matrix::from([[1.0, 2.0, 3.0]]);
+    let rotation = Matrix::from([[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]);
+    bench.run("matrix/mul_1x3_3x3", || {
+        black_box(&row) * black_box(&rotation)
+    });
+
+    // A quarter turn around the z-axis, as the camera rotation is applied to every vertex.
+    let angle = std::f64::consts::FRAC_PI_4;
+    let q = Quaternion(angle.cos(), 0.0, 0.0, angle.sin());
+    let q_prim = q.inverse();
+    let point = VectorRow::from([1.0, 2.0, 3.0]);
+    bench.run("quaternion/rotate", || {
+        quaternion::rotate(black_box(&point), black_box(&q), black_box(&q_prim))
+    });
+
+    bench.finish();
+}
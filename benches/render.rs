@@ -0,0 +1,100 @@
+mod harness;
+// Shared with the application, which is not a library. Only part of it is used here.
+#[allow(unused, clippy::all)]
+#[path = "../src/model/mod.rs"]
+mod model;
+
+use std::{cell::RefCell, rc::Rc};
+
+use model::Model;
+use renderer::{
+    bounds::Bounds,
+    lod,
+    overlay::Grid,
+    renderer::{
+        buffer::TerminalBuffer,
+        terminal::{Terminal, TerminalBuilder},
+    },
+    Camera, ProjectionMode, RenderOption, RendererBuilderTrait, RendererTrait,
+};
+
+/// From a small window up to a full screen terminal.
+const RESOLUTIONS: [(u64, u64); 3] = [(64, 64), (256, 128), (742, 393)];
+
+fn main() {
+    let mut bench = harness::Bench::from_args("render");
+
+    for resolution in RESOLUTIONS {
+        let (width, height) = resolution;
+        bench.run(&format!("buffer/new/{width}x{height}"), || {
+            TerminalBuffer::new(&resolution)
+        });
+
+        let mut buffer = TerminalBuffer::new(&resolution);
+        bench.run(&format!("buffer/clear/{width}x{height}"), || buffer.clear());
+    }
+
+    for model in Model::ALL {
+        for resolution in RESOLUTIONS {
+            let (width, height) = resolution;
+            let mut terminal = terminal(&model, resolution);
+
+            // Write whole frames, as when the camera moves.
+            bench.run(&format!("frame/{model}/{width}x{height}"), || {
+                terminal.redraw();
+                terminal.render();
+            });
+        }
+    }
+
+    // The same, with the ground grid and axis gizmo drawn as well.
+    let bounds = Bounds::from_vertices(&Model::Cube.get_vertices()).unwrap();
+    for resolution in RESOLUTIONS {
+        let (width, height) = resolution;
+        let mut terminal = terminal(&Model::Cube, resolution);
+        let mut extras = terminal.extras().clone();
+        extras.gizmo = true;
+        extras.grid = Some(Grid::for_bounds(&bounds));
+        terminal.set_extras(extras);
+
+        bench.run(&format!("frame/guides/{width}x{height}"), || {
+            terminal.redraw();
+            terminal.render();
+        });
+    }
+
+    bench.finish();
+}
+
+/// Headless renderer of `model`, set up as by the application with its default configuration.
+fn terminal(model: &Model, resolution: (u64, u64)) -> Terminal {
+    let mut terminal = TerminalBuilder::default()
+        .with_sink(Box::new(std::io::sink()))
+        .with_camera(Camera {
+            resolution,
+            projection_mode: ProjectionMode::Perspective { fov: 90 },
+            ..Default::default()
+        })
+        .unwrap()
+        .with_option(RenderOption::default())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let mut vertices = model.get_vertices();
+    let mut line_draw_order = model.get_line_draw_order();
+    let mut objects = model.get_objects(&vertices, &line_draw_order);
+    for object in objects.iter_mut() {
+        lod::add_levels(
+            object,
+            &mut vertices,
+            &mut line_draw_order,
+            &lod::DETAILS_DEFAULT,
+        );
+    }
+
+    terminal.set_vertices(Rc::new(RefCell::new(vertices)));
+    terminal.set_vertices_line_draw_order(Rc::new(RefCell::new(line_draw_order)));
+    terminal.set_objects(objects);
+    terminal
+}
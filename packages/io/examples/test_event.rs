@@ -11,8 +11,11 @@ fn main() {
                 match event {
                     Event::Mouse(modifier, mouse_event) => println!("Modifier: {:?} | MouseEvent: {:?}", modifier, mouse_event),
                     Event::Character(character) => println!("Typed character: {character}"),
+                    Event::Paste(text) => println!("Pasted text: {text:?}"),
+                    Event::Focus(focused) => println!("Focused: {focused}"),
                     Event::Misc(event) => match event {
                         io::misc::Event::CurrentSize(current_size) => println!("New terminal size: {}x{}", current_size.0, current_size.1),
+                        io::misc::Event::ModeReport(report) => println!("Mode {} supported: {}", report.mode, report.supported),
                     }
                 }
             }
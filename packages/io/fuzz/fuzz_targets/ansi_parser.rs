@@ -0,0 +1,16 @@
+#![no_main]
+
+use io::ansi_interpretor::Parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // The parser must never panic, and where the input is split must not affect the result.
+    let whole = Parser::new().feed(data);
+
+    let at = data.first().map_or(0, |&byte| byte as usize % (data.len() + 1));
+    let mut parser = Parser::new();
+    let mut split = parser.feed(&data[..at]);
+    split.append(&mut parser.feed(&data[at..]));
+
+    assert!(whole == split);
+});
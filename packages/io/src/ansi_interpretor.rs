@@ -1,303 +1,421 @@
-use crate::{misc, Event};
-use util::{Ansi, CharArray};
-
-mod util {
-    use crate::{mouse, platform::unix::ToU8, Modifier, misc};
-
-    pub struct CharArray<const SIZE: usize, F: Fn(bool) -> Result<char, &'static str>> {
-        array: [char; SIZE],
-        pos: usize,
-        reader: F,
+/// Incremental interpreter of (a subset of) the xterm control sequences.
+/// Bytes are fed in whatever chunks they are read in, and complete [Event]s are emitted
+/// as soon as they can be determined. Sequences split across reads are kept until completed.
+use crate::{misc, mouse, Event, Modifier};
+
+const ESC: u8 = 0x1b;
+
+/// Upper limit for the parameter bytes of a control sequence. Anything longer is discarded.
+const CSI_LEN_MAX: usize = 64;
+
+/// Sent by the terminal after pasted text when bracketed paste is enabled.
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Length of a UTF-8 encoded character given its first byte, or [None] if it can not start one.
+pub(crate) fn utf8_len(lead: u8) -> Option<usize> {
+    match lead {
+        0x00..=0x7f => Some(1),
+        0xc2..=0xdf => Some(2),
+        0xe0..=0xef => Some(3),
+        0xf0..=0xf4 => Some(4),
+        _ => None,
     }
+}
 
-    impl<const SIZE: usize, F: Fn(bool) -> Result<char, &'static str>> CharArray<SIZE, F> {
-        pub fn new(reader: F) -> Self {
-            Self {
-                array: [Default::default(); SIZE],
-                pos: 0,
-                reader,
-            }
-        }
-
-        pub fn read(&mut self, blocking: bool) -> Result<char, &'static str> {
-            if self.pos > SIZE {
-                return Err("Array is full.");
-            }
-
-            if let Ok(c) = (self.reader)(blocking) {
-                self.array[self.pos] = c;
-                self.pos += 1;
-                return Ok(c);
-            }
-
-            Err("Failed to read character.")
-        }
+#[derive(Default)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    /// Control Sequence Introducer ("ESC [") has been read.
+    Csi,
+    /// Control sequence too long or unsupported. Skip until its final byte.
+    CsiIgnore,
+    Utf8 {
+        len: usize,
+    },
+    Paste,
+}
 
-        pub fn last(&self) -> Result<char, &'static str> {
-            if self.pos == 0 {
-                return Err("No character read yet.");
-            }
+#[derive(Default)]
+pub struct Parser {
+    state: State,
+    /// Bytes of the sequence currently being parsed.
+    pending: Vec<u8>,
+}
 
-            Ok(self.array[self.pos - 1])
-        }
+impl Parser {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    impl<const SIZE: usize, Idx, F: Fn(bool) -> Result<char, &'static str>> std::ops::Index<Idx>
-        for CharArray<SIZE, F>
-    where
-        Idx: std::slice::SliceIndex<[char]>,
-    {
-        type Output = Idx::Output;
+    /// Feed raw bytes from the terminal. Returns all events completed by these bytes.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Event> {
+        let mut events = Vec::new();
 
-        fn index(&self, index: Idx) -> &Self::Output {
-            &self.array[index]
+        for &byte in bytes {
+            self.advance(byte, &mut events);
         }
-    }
 
-    pub trait Ansi {
-        fn is_escape(&mut self) -> Result<bool, &'static str>;
-        fn is_sequence(&mut self) -> Result<bool, &'static str>;
-        fn is_mouse_tracking(&mut self) -> Result<(Modifier, mouse::Event), &'static str>;
-        fn is_resize(&mut self) -> Result<misc::CurrentSize, &'static str>;
+        events
     }
 
-    impl<const SIZE: usize, F: Fn(bool) -> Result<char, &'static str>> Ansi for CharArray<SIZE, F> {
-        fn is_escape(&mut self) -> Result<bool, &'static str> {
-            if self.pos != 1 {
-                return Err("Not in correct state to check for escape sequence.");
+    /// Call when no more bytes are ready. An escape with nothing after it is then taken to be the
+    /// escape key, rather than waiting for the rest of a sequence. Returns it, if so.
+    pub fn flush(&mut self) -> Vec<Event> {
+        match self.state {
+            State::Escape => {
+                self.state = State::Ground;
+                vec![Event::Character(ESC as char)]
             }
+            _ => Vec::new(),
+        }
+    }
 
-            if let Ok(c) = self.last() {
-                return Ok(c == '\x1b');
+    fn advance(&mut self, byte: u8, events: &mut Vec<Event>) {
+        match self.state {
+            State::Ground => match byte {
+                ESC => {
+                    self.pending.clear();
+                    self.state = State::Escape;
+                }
+                0x00..=0x7f => events.push(Event::Character(byte as char)),
+                _ => match utf8_len(byte) {
+                    Some(len) => {
+                        self.pending.clear();
+                        self.pending.push(byte);
+                        self.state = State::Utf8 { len };
+                    }
+                    None => events.push(Event::Character(char::REPLACEMENT_CHARACTER)),
+                },
+            },
+            State::Escape => {
+                if byte == b'[' {
+                    self.state = State::Csi;
+                } else {
+                    // A lone escape key press. Interpret the byte on its own.
+                    events.push(Event::Character(ESC as char));
+                    self.state = State::Ground;
+                    self.advance(byte, events);
+                }
             }
+            State::Csi => match byte {
+                0x40..=0x7e => {
+                    self.state = State::Ground;
+                    let params = std::mem::take(&mut self.pending);
+                    self.dispatch(&params, byte, events);
+                }
+                0x20..=0x3f if self.pending.len() < CSI_LEN_MAX => self.pending.push(byte),
+                0x20..=0x3f => self.state = State::CsiIgnore,
+                _ => {
+                    // Malformed. Abandon the sequence and interpret the byte on its own.
+                    self.state = State::Ground;
+                    self.advance(byte, events);
+                }
+            },
+            State::CsiIgnore => match byte {
+                0x20..=0x3f => (),
+                0x40..=0x7e => self.state = State::Ground,
+                _ => {
+                    self.state = State::Ground;
+                    self.advance(byte, events);
+                }
+            },
+            State::Utf8 { len } => {
+                if byte & 0xc0 != 0x80 {
+                    // Truncated character.
+                    events.push(Event::Character(char::REPLACEMENT_CHARACTER));
+                    self.state = State::Ground;
+                    self.advance(byte, events);
+                    return;
+                }
 
-            Err("Failed to parse.")
-        }
+                self.pending.push(byte);
 
-        fn is_sequence(&mut self) -> Result<bool, &'static str> {
-            if self.pos != 2 {
-                return Err("Not in correct state to check for sequence start symbol ('[').");
+                if self.pending.len() == len {
+                    let c = std::str::from_utf8(&self.pending)
+                        .ok()
+                        .and_then(|s| s.chars().next())
+                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    events.push(Event::Character(c));
+                    self.state = State::Ground;
+                }
             }
-
-            if let Ok(c) = self.last() {
-                return Ok(c == '[');
+            State::Paste => {
+                self.pending.push(byte);
+
+                if self.pending.ends_with(PASTE_END) {
+                    let len = self.pending.len() - PASTE_END.len();
+                    events.push(Event::Paste(
+                        String::from_utf8_lossy(&self.pending[..len]).into_owned(),
+                    ));
+                    self.pending.clear();
+                    self.state = State::Ground;
+                }
             }
-
-            Err("Failed to parse.")
         }
+    }
 
-        fn is_mouse_tracking(&mut self) -> Result<(Modifier, mouse::Event), &'static str> {
-            if self.pos != 3 {
-                return Err("Not in correct state to check for mouse tracking sequence.");
+    /// Act on a complete control sequence, i.e. `ESC [ <params> <action>`.
+    fn dispatch(&mut self, params: &[u8], action: u8, events: &mut Vec<Event>) {
+        match (params, action) {
+            (b"", b'I') => events.push(Event::Focus(true)),
+            (b"", b'O') => events.push(Event::Focus(false)),
+            (b"200", b'~') => {
+                self.pending.clear();
+                self.state = State::Paste;
             }
-
-            if let Ok(c) = self.last() {
-                if c != '<' {
-                    return Err("Not a mouse tracking sequence.");
+            ([b'<', params @ ..], b'M' | b'm') => {
+                if let Some((modifier, event)) = Self::mouse_tracking(params, action == b'M') {
+                    events.push(Event::Mouse(modifier, event));
                 }
             }
-
-            // Read data and check formatting
-            // Note: If parsing took place here too, it would be faster
-            //       but I'd rather have the separation.
-            let mut semicolons_left: usize = 2;
-            let mut semicolon_positions: [usize; 2] = [0; 2];
-            let mut m_position: usize = 0;
-            while match self.read(true) {
-                Ok(c) => {
-                    if c == ';' {
-                        if semicolons_left > 0 {
-                            semicolon_positions[2 - semicolons_left] = self.pos - 1;
-                        }
-
-                        if let Some(new) = semicolons_left.checked_sub(1) {
-                            semicolons_left = new;
-                        } else {
-                            return Err("Badly formatted sequence.");
-                        }
-
-                        true
-                    } else {
-                        if c == 'm' || c == 'M' {
-                            m_position = self.pos - 1;
-                            false
-                        } else {
-                            true
-                        }
-                    }
+            (params, b't') => {
+                if let Some(current_size) = Self::resize(params) {
+                    events.push(Event::Misc(misc::Event::CurrentSize(current_size)));
                 }
-                Err(_) => false,
-            } {}
-
-            // Parsing of read data.
-            // Parse (motion + button type).
-            // These are just some examples of what is parsed. Note that 0 is the CHARACTER for 0. Not the integer value 0.
-            // None + 00110000 = Click left (0)
-            // None + 00110001 = Click middle (1)
-            // None + 00110010 = Click right (2)
-            // 00110011 + 00110000 = Move left (32)
-            // 00110011 + 00110001 = Move middle (33)
-            // 00110011 + 00110010 = Move right (34)
-            // 00110110 + 00110100 = Scroll up (64)
-            // 00110110 + 00110101 = Scroll down (65)
-            let mut meb = mouse::EventBuilder::default();
-
-            if self[m_position] == 'M' {
-                meb.motion = Some(mouse::Motion::Down);
-            } else {
-                meb.motion = Some(mouse::Motion::Up);
             }
-
-            let button_type: usize = if let Ok(button_type) = self[3..semicolon_positions[0]].iter().collect::<String>().parse() {
-                button_type
-            } else {
-                return Err("Badly formatted sequence.");
-            };
-
-            // let modifier = Modifier::None;
-            let modifier = match button_type % 32 {
-                bt if bt < 0+3 => Modifier::None,
-                bt if bt < 8+3 => Modifier::Alt,
-                bt if bt < 16+3 => Modifier::Ctrl,
-                _ => Modifier::None,
-            };
-
-            // This is ugly :)
-            if button_type < 64 {
-                // Either MB 1,2, or 3 (probably).
-                meb.button = match button_type % 8 {
-                    0 => Some(mouse::Button::Left),
-                    1 => Some(mouse::Button::Middle),
-                    2 => Some(mouse::Button::Right),
-                    _ => return Err("Not supported."),
-                }
-            } else {
-                // Scroll
-                meb.button = Some(mouse::Button::Scroll);
-                meb.direction = match (button_type - 64) % 8 {
-                    0 => Some(mouse::Direction::Up),
-                    1 => Some(mouse::Direction::Down),
-                    _ => return Err("Not supported."),
+            ([b'?', params @ .., b'$'], b'y') => {
+                if let Some(mode_report) = Self::mode_report(params) {
+                    events.push(Event::Misc(misc::Event::ModeReport(mode_report)));
                 }
             }
+            _ => (), // Unsupported.
+        }
+    }
 
-            // Parse X-coordinate.
-            let x = &self[(semicolon_positions[0] + 1)..semicolon_positions[1]];
-            if let Ok(val) = x.iter().collect::<String>().parse() {
-                meb.x = Some(val);
-            }
-
-            // Parse Y-coordinate.
-            let y = &self[(semicolon_positions[1] + 1)..m_position];
-            if let Ok(val) = y.iter().collect::<String>().parse() {
-                meb.y = Some(val);
-            }
+    /// Parse numeric parameters separated by ';'. Fails if any parameter is missing or too many are given.
+    fn numbers<const N: usize>(params: &[u8]) -> Option<[u64; N]> {
+        let mut numbers = [0; N];
+        let mut fields = std::str::from_utf8(params).ok()?.split(';');
 
+        for number in numbers.iter_mut() {
+            *number = fields.next()?.parse().ok()?;
+        }
 
-            Ok((modifier, meb.build())) // (modifiers not supported yet)
+        if fields.next().is_some() {
+            return None;
         }
 
-        fn is_resize(&mut self) -> Result<misc::CurrentSize, &'static str> {
-            if self.pos != 3 {
-                return Err("Not in correct state to check for sequence start symbol ('[').");
-            }
+        Some(numbers)
+    }
 
-            if let Ok(c) = self.last() {
-                if c != '8' {
-                    return Err("Not a resize sequence.")
-                }
+    /// SGR mouse tracking, `ESC [ < <button type> ; <x> ; <y> <M|m>`, where 'M' is press and 'm' release.
+    fn mouse_tracking(params: &[u8], pressed: bool) -> Option<(Modifier, mouse::Event)> {
+        let [button_type, x, y] = Self::numbers::<3>(params)?;
+        let (x, y) = (u32::try_from(x).ok()?, u32::try_from(y).ok()?);
+        let motion = if pressed {
+            mouse::Motion::Down
+        } else {
+            mouse::Motion::Up
+        };
+
+        // Some examples of button types:
+        // 0 = Click left
+        // 1 = Click middle
+        // 2 = Click right
+        // 32 = Move left
+        // 33 = Move middle
+        // 34 = Move right
+        // 64 = Scroll up
+        // 65 = Scroll down
+        let modifier = match button_type % 32 {
+            bt if bt < 3 => Modifier::None,
+            bt if bt < 8 + 3 => Modifier::Alt,
+            bt if bt < 16 + 3 => Modifier::Ctrl,
+            _ => Modifier::None,
+        };
+
+        let event = if button_type < 64 {
+            match button_type % 8 {
+                0 => mouse::Event::Left(motion, x, y),
+                1 => mouse::Event::Middle(motion, x, y),
+                2 => mouse::Event::Right(motion, x, y),
+                _ => return None,
             }
-
-            let mut semicolons_left: usize = 2;
-            let mut semicolon_positions: [usize; 2] = [0; 2];
-            let mut t_position: usize = 0;
-            while match self.read(true) {
-                Ok(c) => {
-                    if c == ';' {
-                        if semicolons_left > 0 {
-                            semicolon_positions[2 - semicolons_left] = self.pos - 1;
-                        }
-
-                        if let Some(new) = semicolons_left.checked_sub(1) {
-                            semicolons_left = new;
-                        } else {
-                            return Err("Badly formatted sequence.");
-                        }
-
-                        true
-                    } else {
-                        if c == 't' {
-                            t_position = self.pos - 1;
-                            false
-                        } else {
-                            true
-                        }
-                    }
-                }
-                Err(_) => false,
-            } {}
-
-            let mut width = 0;
-            let mut height = 0;
-
-            // Parse height.
-            let x = &self[(semicolon_positions[0] + 1)..semicolon_positions[1]];
-            if let Ok(val) = x.iter().collect::<String>().parse() {
-                height = val;
+        } else {
+            match (button_type - 64) % 8 {
+                0 => mouse::Event::Scroll(mouse::Direction::Up),
+                1 => mouse::Event::Scroll(mouse::Direction::Down),
+                _ => return None,
             }
+        };
 
-            // Parse width.
-            let y = &self[(semicolon_positions[1] + 1)..t_position];
-            if let Ok(val) = y.iter().collect::<String>().parse() {
-                width = val;
-            }
+        Some((modifier, event))
+    }
 
-            Ok(misc::CurrentSize(width, height))
+    /// Reply to a size query, `ESC [ 8 ; <height> ; <width> t`.
+    fn resize(params: &[u8]) -> Option<misc::CurrentSize> {
+        match Self::numbers::<3>(params)? {
+            [8, height, width] => Some(misc::CurrentSize(width, height)),
+            _ => None,
         }
     }
-}
 
-pub fn interpret<F: Fn(bool) -> Result<char, &'static str>>(
-    reader: F,
-) -> Result<Event, &'static str> {
-    let mut chars = CharArray::<64, F>::new(reader);
+    /// Reply to a private mode query (DECRQM), `ESC [ ? <mode> ; <status> $ y`.
+    /// Status is 0 if the mode is not recognized, 1 or 2 if set or reset, and 3 or 4 if permanently so.
+    fn mode_report(params: &[u8]) -> Option<misc::ModeReport> {
+        let [mode, status] = Self::numbers::<2>(params)?;
 
-    if let Err(msg) = chars.read(false) {
-        return Err(msg);
+        Some(misc::ModeReport {
+            mode,
+            supported: matches!(status, 1 | 2),
+        })
     }
+}
 
-    match chars.is_escape() {
-        Ok(is_escape) => {
-            if !is_escape {
-                // Not an escape sequence. Just a character.
-                return Ok(Event::Character(chars.last().unwrap()));
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mouse::{Direction, Motion};
+
+    fn cases() -> Vec<(&'static str, Vec<u8>, Vec<Event>)> {
+        use Event::*;
+
+        vec![
+            ("ascii", b"ab\r".to_vec(), vec![Character('a'), Character('b'), Character('\r')]),
+            (
+                "utf8",
+                "å€😀".as_bytes().to_vec(),
+                vec![Character('å'), Character('€'), Character('😀')],
+            ),
+            ("utf8 stray continuation", vec![0x80, b'a'], vec![Character('\u{fffd}'), Character('a')]),
+            ("utf8 truncated", vec![0xc3, b'a'], vec![Character('\u{fffd}'), Character('a')]),
+            ("lone escape", b"\x1bx".to_vec(), vec![Character('\x1b'), Character('x')]),
+            (
+                "mouse left down",
+                b"\x1b[<0;10;20M".to_vec(),
+                vec![Mouse(Modifier::None, mouse::Event::Left(Motion::Down, 10, 20))],
+            ),
+            (
+                "mouse left up",
+                b"\x1b[<0;10;20m".to_vec(),
+                vec![Mouse(Modifier::None, mouse::Event::Left(Motion::Up, 10, 20))],
+            ),
+            (
+                "mouse middle",
+                b"\x1b[<1;1;2M".to_vec(),
+                vec![Mouse(Modifier::None, mouse::Event::Middle(Motion::Down, 1, 2))],
+            ),
+            (
+                "mouse right drag",
+                b"\x1b[<34;300;400M".to_vec(),
+                vec![Mouse(Modifier::None, mouse::Event::Right(Motion::Down, 300, 400))],
+            ),
+            (
+                "mouse ctrl left drag",
+                b"\x1b[<48;5;6M".to_vec(),
+                vec![Mouse(Modifier::Ctrl, mouse::Event::Left(Motion::Down, 5, 6))],
+            ),
+            (
+                "mouse alt left",
+                b"\x1b[<8;5;6M".to_vec(),
+                vec![Mouse(Modifier::Alt, mouse::Event::Left(Motion::Down, 5, 6))],
+            ),
+            (
+                "scroll",
+                b"\x1b[<64;3;4M\x1b[<65;3;4M".to_vec(),
+                vec![
+                    Mouse(Modifier::None, mouse::Event::Scroll(Direction::Up)),
+                    Mouse(Modifier::None, mouse::Event::Scroll(Direction::Down)),
+                ],
+            ),
+            ("mouse missing coordinate", b"\x1b[<0;10M".to_vec(), vec![]),
+            ("mouse unsupported button", b"\x1b[<3;1;1M".to_vec(), vec![]),
+            (
+                "resize",
+                b"\x1b[8;50;120t".to_vec(),
+                vec![Misc(misc::Event::CurrentSize(misc::CurrentSize(120, 50)))],
+            ),
+            ("resize other window op", b"\x1b[4;50;120t".to_vec(), vec![]),
+            (
+                "mode report",
+                b"\x1b[?2026;2$y\x1b[?2026;0$y".to_vec(),
+                vec![
+                    Misc(misc::Event::ModeReport(misc::ModeReport {
+                        mode: 2026,
+                        supported: true,
+                    })),
+                    Misc(misc::Event::ModeReport(misc::ModeReport {
+                        mode: 2026,
+                        supported: false,
+                    })),
+                ],
+            ),
+            ("focus", b"\x1b[I\x1b[O".to_vec(), vec![Focus(true), Focus(false)]),
+            ("paste", b"\x1b[200~hello\x1b[201~".to_vec(), vec![Paste("hello".into())]),
+            (
+                "paste with escapes",
+                b"\x1b[200~a\x1b[Ib\x1b[201~c".to_vec(),
+                vec![Paste("a\x1b[Ib".into()), Character('c')],
+            ),
+            ("paste utf8", "\x1b[200~åäö\x1b[201~".as_bytes().to_vec(), vec![Paste("åäö".into())]),
+            ("unsupported sequence", b"\x1b[Ax".to_vec(), vec![Character('x')]),
+            ("sequence aborted", b"\x1b[1\x1b[I".to_vec(), vec![Focus(true)]),
+            (
+                "sequence too long",
+                [b"\x1b[".to_vec(), vec![b'1'; 2 * CSI_LEN_MAX], b"mx".to_vec()].concat(),
+                vec![Character('x')],
+            ),
+        ]
+    }
 
-            if let Err(msg) = chars.read(true) {
-                return Err(msg);
-            }
+    #[test]
+    fn whole() {
+        for (name, input, expected) in cases() {
+            let events = Parser::new().feed(&input);
+            assert!(events == expected, "Case: {name}. Actual: {:?}", events);
+        }
+    }
 
-            match chars.is_sequence() {
-                Ok(is_sequence) => {
-                    if is_sequence {
-                        if let Err(msg) = chars.read(true) {
-                            return Err(msg);
-                        }
+    #[test]
+    fn split() {
+        for (name, input, expected) in cases() {
+            for at in 0..=input.len() {
+                let mut parser = Parser::new();
+                let mut events = parser.feed(&input[..at]);
+                events.append(&mut parser.feed(&input[at..]));
+                assert!(events == expected, "Case: {name}, split at {at}. Actual: {:?}", events);
+            }
+        }
+    }
 
-                        if let Ok((modifier, event)) = chars.is_mouse_tracking() {
-                            return Ok(Event::Mouse(modifier, event));
-                        }
+    #[test]
+    fn byte_by_byte() {
+        for (name, input, expected) in cases() {
+            let mut parser = Parser::new();
+            let events = input
+                .iter()
+                .flat_map(|byte| parser.feed(std::slice::from_ref(byte)))
+                .collect::<Vec<_>>();
+            assert!(events == expected, "Case: {name}. Actual: {:?}", events);
+        }
+    }
 
-                        if let Ok(current_size) = chars.is_resize() {
-                            return Ok(Event::Misc(misc::Event::CurrentSize(current_size)));
-                        }
-                    }
+    #[test]
+    fn pending_sequence() {
+        let mut parser = Parser::new();
+        assert!(parser.feed(b"\x1b[<0;1").is_empty());
+        assert!(parser.feed(b"0;2").is_empty());
+        assert!(
+            parser.feed(b"0Mq")
+                == vec![
+                    Event::Mouse(Modifier::None, mouse::Event::Left(Motion::Down, 10, 20)),
+                    Event::Character('q'),
+                ]
+        );
+    }
 
-                    return Err("Unsupported escape format.");
-                }
-                Err(msg) => return Err(msg),
-            }
-        }
-        Err(msg) => return Err(msg),
+    #[test]
+    fn flush() {
+        let mut parser = Parser::new();
+        assert!(parser.feed(b"\x1b").is_empty());
+        assert!(parser.flush() == vec![Event::Character('\x1b')]);
+        assert!(parser.feed(b"q") == vec![Event::Character('q')]);
+        assert!(parser.flush().is_empty());
+
+        // Only a lone escape is flushed, not a sequence in progress.
+        assert!(parser.feed(b"\x1b[<0;1").is_empty());
+        assert!(parser.flush().is_empty());
     }
 }
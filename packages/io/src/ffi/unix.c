@@ -40,6 +40,8 @@ static void* writerFn(void*);
 void disablePartialRawMode() {
   puts("\x1B[?1002l");
   puts("\x1B[?1006l");
+  puts("\x1B[?1004l");
+  puts("\x1B[?2004l");
   puts("\x1B[?25h");
 
   if (tcsetattr(STDIN_FILENO, TCSAFLUSH, &orig_termios) == -1) {
@@ -60,6 +62,8 @@ void enablePartialRawMode() {
   // Also enable mouse tracking via ANSI escape codes (xterm).
   puts("\x1B[?1002h"); // Track button presses + movement while pressed.
   puts("\x1B[?1006h"); // Enable SGR mouse mode to support large terminals (> char/u8 size coordinates).
+  puts("\x1B[?1004h"); // Report focus in/out.
+  puts("\x1B[?2004h"); // Bracketed paste. Pasted text is wrapped in "ESC[200~" and "ESC[201~".
   puts("\x1B[?25l"); // Hide cursor
 }
 
@@ -1,7 +1,7 @@
-mod ansi_interpretor;
+pub mod ansi_interpretor;
 pub mod platform;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Modifier {
     None,
     Ctrl,
@@ -9,77 +9,53 @@ pub enum Modifier {
 }
 
 pub mod mouse {
-    #[derive(Debug, Clone)]
+    #[derive(Debug, Clone, PartialEq)]
     pub enum Motion {
         Down,
         Up,
     }
 
-    #[derive(Debug, Clone)]
+    #[derive(Debug, Clone, PartialEq)]
     pub enum Direction {
         Down,
         Up,
     }
 
-    #[derive(Debug)]
+    #[derive(Debug, PartialEq)]
     pub enum Event {
         Left(Motion, u32, u32),
         Middle(Motion, u32, u32),
         Right(Motion, u32, u32),
         Scroll(Direction),
     }
-
-    pub(crate) enum Button {
-        Left,
-        Middle,
-        Right,
-        Scroll,
-    }
-
-    #[derive(Default)]
-    pub(crate) struct EventBuilder {
-        pub motion: Option<Motion>,
-        pub x: Option<u32>,
-        pub y: Option<u32>,
-        pub direction: Option<Direction>,
-        pub button: Option<Button>,
-    }
-
-    impl EventBuilder {
-        pub fn build(self) -> Event {
-            match self.button.unwrap() {
-                Button::Left => Event::Left(
-                    self.motion.unwrap(),
-                    self.x.unwrap(),
-                    self.y.unwrap(),
-                ),
-                Button::Middle => Event::Middle(
-                    self.motion.unwrap(),
-                    self.x.unwrap(),
-                    self.y.unwrap(),
-                ),
-                Button::Right => Event::Right(
-                    self.motion.unwrap(),
-                    self.x.unwrap(),
-                    self.y.unwrap(),
-                ),
-                Button::Scroll => Event::Scroll(self.direction.unwrap()),
-            }
-        }
-    }
 }
 
 pub mod misc {
+    #[derive(Debug, PartialEq)]
     pub struct CurrentSize(pub u64, pub u64);
 
+    /// Whether the terminal recognizes a private mode, e.g. 2026 (synchronized output).
+    #[derive(Debug, PartialEq)]
+    pub struct ModeReport {
+        pub mode: u64,
+        pub supported: bool,
+    }
+
+    #[derive(Debug, PartialEq)]
     pub enum Event {
         CurrentSize(CurrentSize),
+        ModeReport(ModeReport),
     }
 }
 
+#[derive(Debug, PartialEq)]
 pub enum Event {
     Mouse(Modifier, mouse::Event),
     Character(char),
+    /// Text received via bracketed paste. Not to be interpreted as key presses.
+    Paste(String),
+    /// Terminal window gained (true) or lost (false) focus.
+    Focus(bool),
     Misc(misc::Event),
 }
 
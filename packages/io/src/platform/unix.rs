@@ -6,13 +6,39 @@ include!(concat!(env!("OUT_DIR"), "/io_bindings.rs"));
 use std::sync::atomic::AtomicBool;
 /// Documentation regarding escape codes and control sequences
 /// can be found in the Xterm Control Sequence Documentation.
-use std::{ffi::c_char, sync::atomic::Ordering};
+use std::{cell::RefCell, collections::VecDeque, ffi::c_char, sync::atomic::Ordering};
 
-use crate::{ansi_interpretor, Event, EventHandlerTrait};
+use crate::{
+    ansi_interpretor::{self, Parser},
+    Event, EventHandlerTrait,
+};
 
 static INITIALIZED: AtomicBool = AtomicBool::new(false);
 
-pub struct EventHandler;
+pub struct EventHandler {
+    parser: RefCell<Parser>,
+    /// Events parsed but not yet handed out.
+    events: RefCell<VecDeque<Event>>,
+}
+
+impl EventHandler {
+    fn latest_byte(&self, blocking: bool) -> Result<u8, &'static str> {
+        let mut buf: c_char = 0;
+
+        unsafe {
+            let buf_p = &mut buf as *mut c_char;
+            let result = getChar(buf_p, blocking);
+
+            if result == 1 {
+                return Err("Failed to read.");
+            } else if result == 2 {
+                return Err("Nothing to read.");
+            }
+        }
+
+        Ok(buf as u8)
+    }
+}
 
 impl EventHandlerTrait for EventHandler {
     fn init() -> Result<Self, &'static str> {
@@ -33,28 +59,44 @@ impl EventHandlerTrait for EventHandler {
         }
 
         INITIALIZED.store(true, Ordering::Relaxed);
-        Ok(Self)
+        Ok(Self {
+            parser: RefCell::new(Parser::new()),
+            events: RefCell::new(VecDeque::new()),
+        })
     }
 
     fn latest_event(&self) -> Result<Event, &'static str> {
-        return ansi_interpretor::interpret(|blocking: bool| self.latest_character(blocking));
+        let mut events = self.events.borrow_mut();
+
+        while events.is_empty() {
+            match self.latest_byte(false) {
+                Ok(byte) => events.extend(self.parser.borrow_mut().feed(&[byte])),
+                Err(msg) => {
+                    // Nothing more to read, so an escape on its own was the escape key.
+                    events.extend(self.parser.borrow_mut().flush());
+                    if events.is_empty() {
+                        return Err(msg);
+                    }
+                }
+            }
+        }
+
+        Ok(events.pop_front().unwrap())
     }
 
     fn latest_character(&self, blocking: bool) -> Result<char, &'static str> {
-        let mut buf: c_char = 0;
+        let mut bytes = [0; 4];
+        bytes[0] = self.latest_byte(blocking)?;
+        let len = ansi_interpretor::utf8_len(bytes[0]).ok_or("Invalid UTF-8.")?;
 
-        unsafe {
-            let buf_p = &mut buf as *mut c_char;
-            let result = getChar(buf_p, blocking);
-
-            if result == 1 {
-                return Err("Failed to read.");
-            } else if result == 2 {
-                return Err("Nothing to read.");
-            }
+        for byte in bytes.iter_mut().take(len).skip(1) {
+            *byte = self.latest_byte(true)?;
         }
 
-        Ok(buf.to_char())
+        std::str::from_utf8(&bytes[..len])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .ok_or("Invalid UTF-8.")
     }
 
     fn running(&self) -> bool {
@@ -72,16 +114,6 @@ impl Drop for EventHandler {
     }
 }
 
-pub trait ToChar {
-    fn to_char(&self) -> char;
-}
-
-impl ToChar for c_char {
-    fn to_char(&self) -> char {
-        *self as u8 as char
-    }
-}
-
 pub trait ToU8 {
     fn to_u8(&self) -> u8;
 }
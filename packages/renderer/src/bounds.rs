@@ -0,0 +1,86 @@
+use linear_algebra::vector::VectorRow;
+
+/// Bounding volumes of a set of vertices: an axis-aligned box, and a sphere around its center.
+/// The sphere is cheap to test against planes, the box is tighter for flat or elongated objects.
+#[derive(Clone, Debug)]
+pub struct Bounds {
+    pub min: VectorRow<f64, 3>,
+    pub max: VectorRow<f64, 3>,
+    pub center: VectorRow<f64, 3>,
+    pub radius: f64,
+}
+
+impl Bounds {
+    /// Returns [None] if there are no vertices to bound.
+    pub fn from_vertices(vertices: &[VectorRow<f64, 3>]) -> Option<Self> {
+        let first = vertices.first()?;
+        let mut min = first.clone();
+        let mut max = first.clone();
+
+        for vertex in vertices.iter() {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(vertex[axis]);
+                max[axis] = max[axis].max(vertex[axis]);
+            }
+        }
+
+        let center = VectorRow::from([
+            (min[0] + max[0]) / 2.0,
+            (min[1] + max[1]) / 2.0,
+            (min[2] + max[2]) / 2.0,
+        ]);
+
+        // Tighter than half the diagonal of the box, unless vertices are in its corners.
+        let radius = vertices
+            .iter()
+            .map(|vertex| VectorRow::from(&vertex.0 - &center.0).length())
+            .fold(0.0, f64::max);
+
+        Some(Self {
+            min,
+            max,
+            center,
+            radius,
+        })
+    }
+
+    pub fn corners(&self) -> [VectorRow<f64, 3>; 8] {
+        std::array::from_fn(|index| {
+            let pick = |axis: usize| {
+                if index & (1 << axis) == 0 {
+                    self.min[axis]
+                } else {
+                    self.max[axis]
+                }
+            };
+            VectorRow::from([pick(0), pick(1), pick(2)])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_vertices() {
+        assert!(Bounds::from_vertices(&[]).is_none());
+
+        let bounds = Bounds::from_vertices(&[
+            VectorRow::from([-1.0, 0.0, 2.0]),
+            VectorRow::from([3.0, 4.0, 2.0]),
+            VectorRow::from([1.0, 2.0, 2.0]),
+        ])
+        .unwrap();
+
+        assert!(bounds.min == VectorRow::from([-1.0, 0.0, 2.0]));
+        assert!(bounds.max == VectorRow::from([3.0, 4.0, 2.0]));
+        assert!(bounds.center == VectorRow::from([1.0, 2.0, 2.0]));
+        assert!((bounds.radius - 8.0_f64.sqrt()).abs() < 1e-9);
+
+        let corners = bounds.corners();
+        assert!(corners[0] == bounds.min);
+        assert!(corners[7] == bounds.max);
+        assert!(corners[1] == VectorRow::from([3.0, 0.0, 2.0]));
+    }
+}
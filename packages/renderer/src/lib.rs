@@ -1,8 +1,12 @@
 /// Current implementation only supports a terminal (/text) output for rendering,
 /// and for simplicity only [f64] is used.
+pub mod bounds;
+pub mod lod;
+pub mod overlay;
+pub mod profile;
 pub mod strategy;
 
-use std::{cell::RefCell, fmt::Display, rc::Rc, str::FromStr};
+use std::{cell::RefCell, fmt::Display, ops::Range, rc::Rc, str::FromStr};
 
 use linear_algebra::quaternion::Quaternion;
 pub use linear_algebra::{
@@ -75,6 +79,18 @@ pub enum RenderOption {
     PolyfillAndCullingAndParticles,
 }
 
+impl RenderOption {
+    pub const ALL: [RenderOption; 7] = [
+        RenderOption::Vertices,
+        RenderOption::WireFrame,
+        RenderOption::WireFrameAndParticles,
+        RenderOption::Culling,
+        RenderOption::CullingAndParticles,
+        RenderOption::PolyfillAndCulling,
+        RenderOption::PolyfillAndCullingAndParticles,
+    ];
+}
+
 impl Display for RenderOption {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -106,6 +122,60 @@ impl FromStr for RenderOption {
     }
 }
 
+/// A part of the scene (e.g. a model, or a node of one), that is culled as a whole when out of view.
+/// Its line draw orders shall only index its own vertices.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Object {
+    /// Indices into [RendererTrait::set_vertices].
+    pub vertices: Range<usize>,
+    /// Indices into [RendererTrait::set_vertices_line_draw_order].
+    pub line_draw_order: Range<usize>,
+    /// Simplified versions of the object, drawn instead of it when it is small on screen (see [lod]).
+    pub levels: Vec<Level>,
+}
+
+/// A level of detail of an [Object], with vertices and line draw orders of its own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    pub vertices: Range<usize>,
+    pub line_draw_order: Range<usize>,
+    /// Used while the projected size (diameter in pixels) of the [Object] is at most this.
+    pub size: f64,
+    /// Of each vertex, the vertex of the [Object] it was simplified from (index into [RendererTrait::set_vertices]).
+    pub vertex_sources: Vec<usize>,
+    /// Of each line draw order, that of the [Object] it was simplified from
+    /// (index into [RendererTrait::set_vertices_line_draw_order]).
+    pub line_draw_order_sources: Vec<usize>,
+}
+
+impl Object {
+    /// The least detailed [Level] allowed at the given projected size, or [None] for the object itself.
+    pub fn level(&self, size: f64) -> Option<&Level> {
+        self.levels
+            .iter()
+            .filter(|level| size <= level.size)
+            .min_by(|a, b| a.size.total_cmp(&b.size))
+    }
+}
+
+/// An element of the scene, as picked from what is drawn (e.g. [renderer::terminal::Terminal::pick]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pick {
+    /// Index into [RendererTrait::set_vertices_line_draw_order]. [None] if the vertex was drawn on its own.
+    pub face: Option<usize>,
+    /// Index into [RendererTrait::set_vertices]. Of a face, the vertex nearest to what was picked.
+    pub vertex: usize,
+}
+
+/// Elements to highlight (see [renderer::terminal::TerminalExtras::selection]).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selection {
+    /// Index into [RendererTrait::set_vertices_line_draw_order].
+    pub face: Option<usize>,
+    /// Indices into [RendererTrait::set_vertices].
+    pub vertices: Vec<usize>,
+}
+
 #[derive(Default, Clone)]
 pub struct RendererConfiguration {
     pub camera: Camera,
@@ -149,6 +219,11 @@ pub trait RendererTrait: Sized {
     /// Index for each vertex given in [RendererTrait::set_vertices] decides drawing order.
     fn set_vertices_line_draw_order(&mut self, order: Rc<RefCell<Vec<Vec<usize>>>>);
 
+    /// Group vertices and line draw orders into [Object]s, skipped entirely when outside of the view.
+    /// Bounds are computed from the current vertices, so set again after moving them.
+    /// Vertices and line draw orders outside of all objects are always drawn.
+    fn set_objects(&mut self, objects: Vec<Object>);
+
     /// Do the render! What is rendered in the final artefact is decided by the [RenderOption]s.
     fn render(&mut self);
 }
@@ -0,0 +1,305 @@
+use linear_algebra::vector::VectorRow;
+
+use crate::{Level, Object};
+
+/// A [Level] to generate: the fraction of vertices to keep, and the [Level::size] to use it up to.
+#[derive(Clone, Debug)]
+pub struct Detail {
+    pub ratio: f64,
+    pub size: f64,
+}
+
+/// Levels that pay off at terminal resolutions, where objects are rarely more than a hundred pixels across.
+pub const DETAILS_DEFAULT: [Detail; 2] = [
+    Detail {
+        ratio: 0.5,
+        size: 48.0,
+    },
+    Detail {
+        ratio: 0.2,
+        size: 16.0,
+    },
+];
+
+/// A simplified mesh, and what of the original each of its elements was simplified from.
+#[derive(Clone, Debug)]
+pub struct Decimated {
+    pub vertices: Vec<VectorRow<f64, 3>>,
+    /// Indexing [Self::vertices].
+    pub line_draw_order: Vec<Vec<usize>>,
+    /// Of each vertex, the index of the original vertex the others were collapsed into.
+    pub vertex_sources: Vec<usize>,
+    /// Of each line draw order, the index of the original one.
+    pub line_draw_order_sources: Vec<usize>,
+}
+
+/// Simplify a mesh by collapsing its shortest edges into their midpoints, until at most `ratio` of the vertices are left.
+/// Collapses are done in passes, each vertex taking part in at most one collapse per pass.
+/// Only vertices connected by lines or faces are merged, so particles are kept as they are.
+pub fn decimate(
+    vertices: &[VectorRow<f64, 3>],
+    line_draw_order: &[Vec<usize>],
+    ratio: f64,
+) -> Decimated {
+    let mut vertices = vertices.to_vec();
+    let mut line_draw_order = line_draw_order.to_vec();
+    let mut line_draw_order_sources = (0..line_draw_order.len()).collect::<Vec<_>>();
+    let mut alive = vec![true; vertices.len()];
+    let mut alive_count = vertices.len();
+    let target = (vertices.len() as f64 * ratio).ceil() as usize;
+
+    while alive_count > target {
+        let mut edges = line_draw_order
+            .iter()
+            .flat_map(|order| {
+                let closing = (order.len() > 2).then(|| (order[order.len() - 1], order[0]));
+                order
+                    .windows(2)
+                    .map(|ab| (ab[0], ab[1]))
+                    .chain(closing)
+                    .map(|(a, b)| (a.min(b), a.max(b)))
+            })
+            .filter(|(a, b)| a != b)
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let length =
+            |&(a, b): &(usize, usize)| VectorRow::from(&vertices[a].0 - &vertices[b].0).length();
+        edges.sort_by(|a, b| length(a).total_cmp(&length(b)));
+
+        let mut touched = vec![false; vertices.len()];
+        let mut remap = (0..vertices.len()).collect::<Vec<_>>();
+        let alive_count_before = alive_count;
+
+        for (a, b) in edges {
+            if alive_count <= target {
+                break;
+            }
+
+            if touched[a] || touched[b] {
+                continue;
+            }
+
+            let mut midpoint = VectorRow::from(&vertices[a].0 + &vertices[b].0);
+            midpoint.0.scale(0.5);
+            vertices[a] = midpoint;
+            alive[b] = false;
+            remap[b] = a;
+            touched[a] = true;
+            touched[b] = true;
+            alive_count -= 1;
+        }
+
+        if alive_count == alive_count_before {
+            break;
+        }
+
+        (line_draw_order, line_draw_order_sources) = line_draw_order
+            .into_iter()
+            .zip(line_draw_order_sources)
+            .filter_map(|(order, source)| {
+                let len_min = order.len().min(3);
+                let mut order = order
+                    .into_iter()
+                    .map(|index| remap[index])
+                    .collect::<Vec<_>>();
+                order.dedup();
+
+                if order.len() > 1 && order.first() == order.last() {
+                    order.pop();
+                }
+
+                (order.len() >= len_min).then_some((order, source))
+            })
+            .unzip();
+    }
+
+    // Drop collapsed vertices and index the remaining ones anew.
+    let mut index_new = vec![0; vertices.len()];
+    let (vertices, vertex_sources) = vertices
+        .into_iter()
+        .zip(alive.iter())
+        .enumerate()
+        .filter(|(_, (_, alive))| **alive)
+        .enumerate()
+        .map(|(new, (old, (vertex, _)))| {
+            index_new[old] = new;
+            (vertex, old)
+        })
+        .unzip();
+
+    for order in line_draw_order.iter_mut() {
+        for index in order.iter_mut() {
+            *index = index_new[*index];
+        }
+    }
+
+    Decimated {
+        vertices,
+        line_draw_order,
+        vertex_sources,
+        line_draw_order_sources,
+    }
+}
+
+/// Generate [Level]s for an [Object] by [decimate]-ing it, appending their vertices and line draw orders to the given ones.
+/// Levels that would not have fewer vertices than the previous one are skipped.
+pub fn add_levels(
+    object: &mut Object,
+    vertices: &mut Vec<VectorRow<f64, 3>>,
+    line_draw_order: &mut Vec<Vec<usize>>,
+    details: &[Detail],
+) {
+    let start = object.vertices.start;
+    let object_vertices = &vertices[object.vertices.clone()];
+    let object_line_draw_order = line_draw_order[object.line_draw_order.clone()]
+        .iter()
+        .map(|order| order.iter().map(|index| index - start).collect())
+        .collect::<Vec<Vec<usize>>>();
+
+    let mut len_previous = object_vertices.len();
+    let mut levels = Vec::new();
+
+    for detail in details {
+        let decimated = decimate(object_vertices, &object_line_draw_order, detail.ratio);
+
+        if decimated.vertices.len() >= len_previous {
+            continue;
+        }
+
+        len_previous = decimated.vertices.len();
+        levels.push((decimated, detail.size));
+    }
+
+    for (mut decimated, size) in levels {
+        let offset = vertices.len();
+        let level = Level {
+            vertices: offset..offset + decimated.vertices.len(),
+            line_draw_order: line_draw_order.len()
+                ..line_draw_order.len() + decimated.line_draw_order.len(),
+            size,
+            vertex_sources: decimated
+                .vertex_sources
+                .iter()
+                .map(|source| source + start)
+                .collect(),
+            line_draw_order_sources: decimated
+                .line_draw_order_sources
+                .iter()
+                .map(|source| source + object.line_draw_order.start)
+                .collect(),
+        };
+
+        vertices.append(&mut decimated.vertices);
+        line_draw_order.extend(
+            decimated
+                .line_draw_order
+                .into_iter()
+                .map(|order| order.into_iter().map(|index| index + offset).collect()),
+        );
+        object.levels.push(level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Grid of `size` x `size` vertices, with a quad between each four neighbours.
+    fn grid(size: usize) -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>) {
+        let vertices = (0..size * size)
+            .map(|index| VectorRow::from([(index % size) as f64, 0.0, (index / size) as f64]))
+            .collect();
+        let faces = (0..size - 1)
+            .flat_map(|row| {
+                (0..size - 1).map(move |col| {
+                    let index = row * size + col;
+                    vec![index, index + 1, index + size + 1, index + size]
+                })
+            })
+            .collect();
+
+        (vertices, faces)
+    }
+
+    #[test]
+    fn decimate_grid() {
+        let (vertices, faces) = grid(10);
+        let Decimated {
+            vertices: decimated,
+            line_draw_order: decimated_faces,
+            vertex_sources,
+            line_draw_order_sources,
+        } = decimate(&vertices, &faces, 0.5);
+
+        assert!(decimated.len() <= 50 && !decimated.is_empty());
+        assert!(!decimated_faces.is_empty() && decimated_faces.len() < faces.len());
+
+        for face in decimated_faces.iter() {
+            assert!(face.len() >= 3);
+            assert!(face.iter().all(|&index| index < decimated.len()));
+        }
+
+        // Each element is simplified from one of the original, in order.
+        assert!(vertex_sources.len() == decimated.len() && vertex_sources.is_sorted());
+        assert!(line_draw_order_sources.len() == decimated_faces.len());
+        assert!(line_draw_order_sources.is_sorted());
+        assert!(line_draw_order_sources.last() < Some(&faces.len()));
+
+        // Nothing to collapse.
+        let decimated = decimate(&vertices, &[], 0.5);
+        assert!(decimated.vertices.len() == vertices.len() && decimated.line_draw_order.is_empty());
+    }
+
+    #[test]
+    fn levels_are_appended() {
+        let (mut vertices, mut line_draw_order) = grid(10);
+        // Something before the object, to offset it.
+        vertices.insert(0, VectorRow::from([0.0, 0.0, 0.0]));
+        line_draw_order
+            .iter_mut()
+            .flatten()
+            .for_each(|index| *index += 1);
+        line_draw_order.insert(0, vec![0]);
+
+        let mut object = Object {
+            vertices: 1..vertices.len(),
+            line_draw_order: 1..line_draw_order.len(),
+            levels: Vec::new(),
+        };
+        add_levels(
+            &mut object,
+            &mut vertices,
+            &mut line_draw_order,
+            &DETAILS_DEFAULT,
+        );
+
+        assert!(object.levels.len() == 2);
+        assert!(object.levels[1].vertices.end == vertices.len());
+        assert!(object.levels[1].line_draw_order.end == line_draw_order.len());
+
+        for level in object.levels.iter() {
+            assert!(level.vertices.len() < object.vertices.len());
+
+            for order in &line_draw_order[level.line_draw_order.clone()] {
+                assert!(order.iter().all(|index| level.vertices.contains(index)));
+            }
+
+            // Simplified from the object, not what is before it.
+            assert!(level
+                .vertex_sources
+                .iter()
+                .all(|source| object.vertices.contains(source)));
+            assert!(level
+                .line_draw_order_sources
+                .iter()
+                .all(|source| object.line_draw_order.contains(source)));
+        }
+
+        assert!(object.level(100.0).is_none());
+        assert!(object.level(48.0) == Some(&object.levels[0]));
+        assert!(object.level(20.0) == Some(&object.levels[0]));
+        assert!(object.level(10.0) == Some(&object.levels[1]));
+    }
+}
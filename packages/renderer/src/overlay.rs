@@ -0,0 +1,125 @@
+/// Guides drawn along with the model, to keep one's bearings: a ground grid, and an axis gizmo
+/// (see [crate::renderer::terminal::TerminalExtras]).
+use linear_algebra::vector::VectorRow;
+
+use crate::bounds::Bounds;
+
+/// Grid lines drawn from the point below the viewpoint, in each direction. Further lines have faded out.
+const GRID_LINES: f64 = 20.0;
+
+/// Points per grid cell side. Lines are drawn as short segments, so that they are not dropped as a whole
+/// when one of their ends is out of view.
+const GRID_SUBDIVISIONS: usize = 4;
+
+/// Lines on a horizontal plane, every `spacing` along x and y. The grid has no edge, but fades out with
+/// the distance (along the plane) from the viewpoint, gone at `fade`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid {
+    /// Height (z) of the plane.
+    pub height: f64,
+    pub spacing: f64,
+    pub fade: f64,
+}
+
+impl Grid {
+    /// Grid below what is bounded, with a spacing (a power of ten) around half its radius.
+    pub fn for_bounds(bounds: &Bounds) -> Self {
+        let spacing = 10f64.powf((bounds.radius / 2.0).max(0.001).log10().round());
+
+        Self {
+            height: bounds.min[2],
+            spacing,
+            fade: spacing * GRID_LINES,
+        }
+    }
+
+    /// The part of the grid around a viewpoint at `eye`, as polylines of points and their opacity
+    /// (1 opaque, 0 faded out).
+    pub fn lines(&self, eye: &VectorRow<f64, 3>) -> Vec<Vec<(VectorRow<f64, 3>, f64)>> {
+        let cells = (self.fade / self.spacing).ceil() as isize;
+        let step = self.spacing / GRID_SUBDIVISIONS as f64;
+        let steps = cells as usize * 2 * GRID_SUBDIVISIONS;
+
+        // Snap to the grid, so that the lines stay in place as the viewpoint moves.
+        let center = [
+            (eye[0] / self.spacing).round() * self.spacing,
+            (eye[1] / self.spacing).round() * self.spacing,
+        ];
+        let start = [
+            center[0] - cells as f64 * self.spacing,
+            center[1] - cells as f64 * self.spacing,
+        ];
+
+        let point = |x: f64, y: f64| {
+            let distance = ((x - eye[0]).powi(2) + (y - eye[1]).powi(2)).sqrt();
+            (
+                VectorRow::from([x, y, self.height]),
+                (1.0 - distance / self.fade).max(0.0),
+            )
+        };
+
+        (-cells..=cells)
+            .flat_map(|line| {
+                let offset = line as f64 * self.spacing;
+
+                // Along y, and along x.
+                [
+                    (0..=steps)
+                        .map(|step_| point(center[0] + offset, start[1] + step_ as f64 * step))
+                        .collect(),
+                    (0..=steps)
+                        .map(|step_| point(start[0] + step_ as f64 * step, center[1] + offset))
+                        .collect(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn for_bounds() {
+        let bounds = Bounds::from_vertices(&[
+            VectorRow::from([-30.0, -30.0, -5.0]),
+            VectorRow::from([30.0, 30.0, 5.0]),
+        ])
+        .unwrap();
+        let grid = Grid::for_bounds(&bounds);
+
+        assert!(grid.height == -5.0);
+        assert!(grid.spacing == 10.0);
+        assert!(grid.fade == 200.0);
+    }
+
+    #[test]
+    fn lines_fade() {
+        let grid = Grid {
+            height: 1.0,
+            spacing: 1.0,
+            fade: 2.0,
+        };
+        let eye = VectorRow::from([10.1, -3.0, 5.0]);
+        let lines = grid.lines(&eye);
+
+        // Lines up to 2 apart in either direction, around the point below the eye, snapped to the grid.
+        assert!(lines.len() == 10);
+        for line in lines.iter() {
+            for (point, opacity) in line.iter() {
+                assert!(point[2] == 1.0);
+                assert!((point[0] - eye[0]).abs() <= 3.0 && (point[1] - eye[1]).abs() <= 3.0);
+                assert!((0.0..=1.0).contains(opacity));
+            }
+        }
+
+        let opaque = lines
+            .iter()
+            .flatten()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        assert!(opaque.0 == VectorRow::from([10.0, -3.0, 1.0]));
+        assert!((opaque.1 - 0.95).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// A timed stage of a rendered frame. Times are relative to the creation of the [Profiler].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stage {
+    pub name: &'static str,
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// Per stage timings over a number of frames (see [Profiler::summary]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub name: &'static str,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+/// File formats the recorded frames can be written as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// One row per stage: "frame,stage,start_us,duration_us".
+    Csv,
+    /// Trace Event Format, as read by chrome://tracing and Perfetto.
+    ChromeTrace,
+}
+
+impl Format {
+    /// Pick format by file extension, ".csv" or ".json".
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::ChromeTrace),
+            _ => None,
+        }
+    }
+}
+
+/// Times the stages of each rendered frame, keeping the most recent frames.
+pub struct Profiler {
+    epoch: Instant,
+    frames: VecDeque<Vec<Stage>>,
+    /// Number of frames kept. Older are dropped.
+    history: usize,
+    /// Number of frames dropped so far, to keep frame numbers stable.
+    dropped: usize,
+}
+
+impl Profiler {
+    pub fn new(history: usize) -> Self {
+        Self {
+            epoch: Instant::now(),
+            frames: VecDeque::new(),
+            history: history.max(1),
+            dropped: 0,
+        }
+    }
+
+    /// Change the number of frames kept, e.g. to keep all of them for a trace of the whole session.
+    pub fn set_history(&mut self, history: usize) {
+        self.history = history.max(1);
+        self.truncate();
+    }
+
+    fn truncate(&mut self) {
+        while self.frames.len() > self.history {
+            self.frames.pop_front();
+            self.dropped += 1;
+        }
+    }
+
+    /// Start recording a new frame.
+    pub fn frame_begin(&mut self) {
+        self.frames.push_back(Vec::new());
+        self.truncate();
+    }
+
+    /// Record a stage of the current frame, that began at `start` and ends now.
+    pub fn stage_end(&mut self, name: &'static str, start: Instant) {
+        let stage = Stage {
+            name,
+            start: start.duration_since(self.epoch),
+            duration: start.elapsed(),
+        };
+
+        match self.frames.back_mut() {
+            Some(frame) => frame.push(stage),
+            None => self.frames.push_back(vec![stage]),
+        }
+    }
+
+    /// Recorded frames, oldest first.
+    pub fn frames(&self) -> impl Iterator<Item = &[Stage]> {
+        self.frames.iter().map(|frame| frame.as_slice())
+    }
+
+    /// Mean and maximum time of each stage, over the last `count` (completed) frames.
+    /// Stages are in the order they were first recorded.
+    pub fn summary(&self, count: usize) -> Vec<Summary> {
+        // The last frame may still be in progress.
+        let frames = self.frames.len().saturating_sub(1);
+        let mut summaries: Vec<(Summary, u32)> = Vec::new();
+
+        for frame in self
+            .frames
+            .iter()
+            .take(frames)
+            .skip(frames.saturating_sub(count))
+        {
+            for stage in frame {
+                match summaries
+                    .iter_mut()
+                    .find(|(summary, _)| summary.name == stage.name)
+                {
+                    Some((summary, samples)) => {
+                        summary.mean += stage.duration;
+                        summary.max = summary.max.max(stage.duration);
+                        *samples += 1;
+                    }
+                    None => summaries.push((
+                        Summary {
+                            name: stage.name,
+                            mean: stage.duration,
+                            max: stage.duration,
+                        },
+                        1,
+                    )),
+                }
+            }
+        }
+
+        summaries
+            .into_iter()
+            .map(|(mut summary, samples)| {
+                summary.mean /= samples;
+                summary
+            })
+            .collect()
+    }
+
+    pub fn write(&self, format: Format, w: &mut impl Write) -> io::Result<()> {
+        match format {
+            Format::Csv => self.write_csv(w),
+            Format::ChromeTrace => self.write_chrome_trace(w),
+        }
+    }
+
+    fn write_csv(&self, w: &mut impl Write) -> io::Result<()> {
+        writeln!(w, "frame,stage,start_us,duration_us")?;
+
+        for (index, frame) in self.frames().enumerate() {
+            for stage in frame {
+                writeln!(
+                    w,
+                    "{},{},{},{}",
+                    self.dropped + index,
+                    stage.name,
+                    stage.start.as_micros(),
+                    stage.duration.as_micros()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Each frame is a complete event, with its stages nested in it.
+    fn write_chrome_trace(&self, w: &mut impl Write) -> io::Result<()> {
+        write!(w, "{{\"displayTimeUnit\":\"ms\",\"traceEvents\":[")?;
+        let mut first = true;
+
+        for (index, frame) in self.frames().enumerate() {
+            let (Some(begin), Some(end)) = (frame.first(), frame.last()) else {
+                continue;
+            };
+            let name = format!("frame {}", self.dropped + index);
+            trace_event(
+                w,
+                first,
+                &name,
+                begin.start,
+                end.start + end.duration - begin.start,
+            )?;
+            first = false;
+
+            for stage in frame {
+                trace_event(w, false, stage.name, stage.start, stage.duration)?;
+            }
+        }
+
+        writeln!(w, "\n]}}")
+    }
+}
+
+/// A complete event ("ph":"X") of the Trace Event Format, preceded by a comma unless it is the first.
+fn trace_event(
+    w: &mut impl Write,
+    first: bool,
+    name: &str,
+    start: Duration,
+    duration: Duration,
+) -> io::Result<()> {
+    write!(
+        w,
+        "{}\n{{\"name\":\"{name}\",\"ph\":\"X\",\"pid\":1,\"tid\":1,\"ts\":{:.3},\"dur\":{:.3}}}",
+        if first { "" } else { "," },
+        start.as_secs_f64() * 1e6,
+        duration.as_secs_f64() * 1e6
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(name: &'static str, start_us: u64, duration_us: u64) -> Stage {
+        Stage {
+            name,
+            start: Duration::from_micros(start_us),
+            duration: Duration::from_micros(duration_us),
+        }
+    }
+
+    /// Three frames of "clear" and "write", of which the first has been dropped.
+    fn profiler() -> Profiler {
+        let mut profiler = Profiler::new(2);
+
+        for frame in 0..3 {
+            profiler.frame_begin();
+            profiler.frames.back_mut().unwrap().extend([
+                stage("clear", frame * 1000, 100 + frame * 100),
+                stage("write", frame * 1000 + 500, 200),
+            ]);
+        }
+
+        profiler
+    }
+
+    #[test]
+    fn history() {
+        let mut profiler = profiler();
+        assert!(profiler.frames().count() == 2);
+
+        profiler.set_history(1);
+        assert!(profiler.frames().count() == 1);
+        assert!(profiler.dropped == 2);
+
+        profiler.stage_end("clear", Instant::now());
+        assert!(profiler.frames().next().unwrap().len() == 3);
+    }
+
+    #[test]
+    fn summary() {
+        let mut profiler = profiler();
+        profiler.set_history(10);
+        profiler.frame_begin();
+        profiler.stage_end("clear", Instant::now());
+
+        // The frame in progress is left out.
+        let summary = profiler.summary(10);
+        assert!(summary.len() == 2);
+        assert!(summary[0].name == "clear");
+        assert!(summary[0].mean == Duration::from_micros(250));
+        assert!(summary[0].max == Duration::from_micros(300));
+        assert!(summary[1].name == "write");
+        assert!(summary[1].mean == Duration::from_micros(200));
+
+        assert!(profiler.summary(1)[0].mean == Duration::from_micros(300));
+    }
+
+    #[test]
+    fn csv() {
+        let mut csv = Vec::new();
+        profiler().write(Format::Csv, &mut csv).unwrap();
+
+        assert!(
+            String::from_utf8(csv).unwrap()
+                == "frame,stage,start_us,duration_us\n\
+                    1,clear,1000,200\n\
+                    1,write,1500,200\n\
+                    2,clear,2000,300\n\
+                    2,write,2500,200\n"
+        );
+    }
+
+    #[test]
+    fn chrome_trace() {
+        let mut json = Vec::new();
+        profiler().write(Format::ChromeTrace, &mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+
+        assert!(json.starts_with("{\"displayTimeUnit\":\"ms\",\"traceEvents\":[\n{"));
+        assert!(json.ends_with("}\n]}\n"));
+        assert!(json.contains(
+            "{\"name\":\"frame 1\",\"ph\":\"X\",\"pid\":1,\"tid\":1,\"ts\":1000.000,\"dur\":700.000}"
+        ));
+        assert!(json.contains(
+            ",\n{\"name\":\"clear\",\"ph\":\"X\",\"pid\":1,\"tid\":1,\"ts\":2000.000,\"dur\":300.000}"
+        ));
+        assert!(json.matches("\"ph\"").count() == 6);
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert!(Format::from_path(Path::new("frames.csv")) == Some(Format::Csv));
+        assert!(Format::from_path(Path::new("/tmp/trace.json")) == Some(Format::ChromeTrace));
+        assert!(Format::from_path(Path::new("trace")).is_none());
+    }
+}
@@ -1,107 +1,18 @@
-pub mod pixel {
-    pub struct Meta<'a> {
-        /// Pixels cover both upper and lower part of a "real" pixel, so depth is represented for two pixels.
-        pub depth_flag: (&'a mut bool, &'a mut bool),
-        pub depth: (&'a mut f64, &'a mut f64),
-
-        /// Temporary storage of depth information for polygon border. Upper, lower.
-        pub polygon_border_flag: (&'a mut bool, &'a mut bool),
-        pub polygon_border: (&'a mut f64, &'a mut f64),
-    }
-
-    impl<'a> Meta<'a> {
-        pub fn from_slice(slice: &'a mut [u8]) -> Self {
-            // Ensure the slice has the correct alignment for f64 (typically 8 bytes) and that it has a valid size.
-            debug_assert!(
-                slice.as_mut_ptr() as usize % std::mem::align_of::<f64>() == 0,
-                "Slice is not properly aligned!"
-            );
-            debug_assert!(
-                slice.len() == Self::required_buffer_size(),
-                "Slice does not have a valid size!"
-            );
-
-            let f64_slice = slice.as_mut_ptr() as *mut f64;
-            let bool_slice = unsafe { f64_slice.add(4) as *mut bool };
-
-            unsafe {
-                Self {
-                    depth_flag: (&mut *bool_slice.add(0), &mut *bool_slice.add(1)),
-                    depth: (&mut *f64_slice.add(0), &mut *f64_slice.add(1)),
-                    polygon_border_flag: (&mut *bool_slice.add(2), &mut *bool_slice.add(3)),
-                    polygon_border: (&mut *f64_slice.add(2), &mut *f64_slice.add(3)),
-                }
-            }
-        }
-
-        /// Calculates the minimum buffer size required for the struct to have proper references.
-        /// Assumes data is packed in a way such that no INTERNAL padding is required, i.e:
-        /// [f64, f64, f64, f64, bool, bool, bool, bool]
-        /// EXTERNAL padding (after or before) is probably still required.
-        #[inline]
-        pub const fn required_buffer_size() -> usize {
-            (4 * std::mem::size_of::<f64>()) + (4 * std::mem::size_of::<bool>())
-        }
-    }
-
-    // This can be used instead if color is wanted. Though, it takes a lot of performance.
-    // pub const VALUE_LEN: usize = 20;
-    // pub const EMPTY: [char; VALUE_LEN] = [
-    //     '\x1B',
-    //     '[',
-    //     '3',
-    //     '8',
-    //     ';',
-    //     '2',
-    //     ';',
-    //     '2',
-    //     '0',
-    //     '0',
-    //     ';',
-    //     '0',
-    //     '0',
-    //     '0',
-    //     ';',
-    //     '0',
-    //     '0',
-    //     '0',
-    //     'm',
-    //     Char::Empty.value(),
-    // ];
-    pub const VALUE_LEN: usize = 1;
-    pub const EMPTY: [char; VALUE_LEN] = [Char::Empty.value()];
-
-    pub struct Value<'a> {
-        value: &'a mut [char; VALUE_LEN],
-    }
-
-    impl<'a> Value<'a> {
-        pub fn from_slice(slice: &'a mut [char; VALUE_LEN]) -> Self {
-            slice.copy_from_slice(&EMPTY);
-            Self { value: slice }
-        }
-
-        pub fn get(&self) -> char {
-            self.value[VALUE_LEN - 1]
-        }
-
-        pub fn set(&mut self, c: Char) {
-            self.value[VALUE_LEN - 1] = c.value();
-        }
-    }
+use std::ops::Range;
 
-    /// The [Pixel] type only contains references to the owned buffer types, but adds a layer
-    /// of abstraction to more easily manipulate the memory.
-    pub struct Pixel<'a> {
-        pub meta: Meta<'a>,
-        pub value: Value<'a>,
-    }
+pub mod pixel {
+    // Cells could also be prefixed with color, e.g. "\x1B[38;2;200;000;000m" (19 bytes). Though, it takes a lot of performance.
+    /// Bytes per cell. Every [Char] is encoded as exactly this many bytes of UTF-8, so that cells can be
+    /// indexed directly, and the buffer be written as is.
+    pub const VALUE_LEN: usize = 3;
+    pub const EMPTY: [u8; VALUE_LEN] = Char::Empty.encoded();
 
     #[derive(PartialEq, Clone)]
     pub enum Char {
         Upper,
         Lower,
         Full,
+        /// Has to be [VALUE_LEN] bytes in UTF-8, i.e. in the range [U+0800,U+FFFF].
         Custom(char),
         Empty,
     }
@@ -113,11 +24,35 @@ pub mod pixel {
                 Self::Lower => '\u{2584}', // ▄
                 Self::Full => '\u{2588}',  // █
                 Self::Custom(c) => *c,
-                Self::Empty => ' ',
+                Self::Empty => '\u{2002}', // En space. An ordinary space is only one byte.
             }
         }
 
-        /// Get appropriate character to use given a vertical position (z).
+        /// UTF-8 encoding of [Char::value].
+        pub const fn encoded(&self) -> [u8; VALUE_LEN] {
+            let c = self.value() as u32;
+            assert!(
+                matches!(c, 0x800..=0xFFFF),
+                "Character is not three bytes in UTF-8."
+            );
+
+            [
+                0xE0 | (c >> 12) as u8,
+                0x80 | ((c >> 6) & 0x3F) as u8,
+                0x80 | (c & 0x3F) as u8,
+            ]
+        }
+    }
+
+    /// Pixels cover both the upper and lower half of a terminal character, so most data is kept per half.
+    #[derive(PartialEq, Clone, Copy)]
+    pub enum Half {
+        Upper,
+        Lower,
+    }
+
+    impl Half {
+        /// Get appropriate half to use given a vertical position (z).
         pub fn at(z: usize) -> Self {
             if z % 2 != 0 {
                 Self::Upper
@@ -125,21 +60,104 @@ pub mod pixel {
                 Self::Lower
             }
         }
+
+        /// The character that draws (only) this half.
+        pub fn char(self) -> Char {
+            match self {
+                Self::Upper => Char::Upper,
+                Self::Lower => Char::Lower,
+            }
+        }
+
+        /// The other half.
+        pub fn opposite(self) -> Self {
+            match self {
+                Self::Upper => Self::Lower,
+                Self::Lower => Self::Upper,
+            }
+        }
+
+        /// Offset into per half data.
+        pub(super) fn offset(self) -> usize {
+            self as usize
+        }
+    }
+
+    /// What is drawn in a half: indices of the face (into the line draw order), and of its vertex
+    /// nearest to the pixel, if known. Only valid where the depth is set.
+    #[derive(PartialEq, Clone, Copy, Debug)]
+    pub struct Id {
+        pub face: u32,
+        pub vertex: u32,
+    }
+
+    impl Id {
+        /// Not drawn from the scene, e.g. a guide.
+        pub const NONE: Self = Self {
+            face: u32::MAX,
+            vertex: u32::MAX,
+        };
+
+        /// A vertex drawn on its own, not as part of a face.
+        pub fn vertex(vertex: usize) -> Self {
+            Self {
+                face: u32::MAX,
+                vertex: vertex as u32,
+            }
+        }
+
+        /// Inside a face. Which vertex is nearest is left to be found when needed.
+        pub fn fill(face: usize) -> Self {
+            Self {
+                face: face as u32,
+                vertex: u32::MAX,
+            }
+        }
+
+        pub fn face(face: usize, vertex: usize) -> Self {
+            Self {
+                face: face as u32,
+                vertex: vertex as u32,
+            }
+        }
+    }
+
+    /// Bits of the per pixel flags.
+    pub(super) mod flag {
+        use super::Half;
+
+        /// Depth has been set.
+        pub const fn depth(half: Half) -> u8 {
+            1 << half as u8
+        }
+
+        /// Depth of a polygon border is stored (temporarily).
+        pub const fn border(half: Half) -> u8 {
+            4 << half as u8
+        }
     }
 }
 
+use pixel::{flag, Char, Half, Id};
+
 /// The main purpose of [TerminalBuffer] is to keep continuous buffers for various data, to allow for fast IO and memory manipulation.
-/// Editing values in the buffer should only be done via the [pixel::Pixel] (via [TerminalBuffer::pixel_mut]) type.
+/// Data is stored per pixel in separate arrays, indexed the same way (see [pixel::Half] for data per half).
+/// The characters are kept as UTF-8 (see [pixel::VALUE_LEN]), with newlines between rows, so the whole buffer can be written as is.
+/// Editing values in the buffer is done via [Band]s (via [TerminalBuffer::bands_mut]).
 /// Batch memory manipulations however can be done via the [TerminalBuffer] and are faster.
-pub struct TerminalBuffer<'a> {
-    metas_bytes: Vec<u8>,
-    chars_clear: Vec<char>,
-    chars: Vec<char>,
-    pixels: Vec<pixel::Pixel<'a>>,
+pub struct TerminalBuffer {
+    depth: Vec<f64>,
+    /// Temporary storage of depth information for polygon borders.
+    border_depth: Vec<f64>,
+    /// What is drawn, per half (see [pixel::Id]).
+    ids: Vec<Id>,
+    flags: Vec<u8>,
+    bytes_clear: Vec<u8>,
+    bytes: Vec<u8>,
     pixels_dimensions: (usize, usize),
 }
 
-impl<'a> TerminalBuffer<'a> {
+impl TerminalBuffer {
     pub fn pixels_required(resolution: &(u64, u64)) -> usize {
         ((resolution.0) * (resolution.1 / 2)) as usize
     }
@@ -148,90 +166,213 @@ impl<'a> TerminalBuffer<'a> {
     /// "+ (resolution.1 / 2)" is needed to add space for '\n' on every row.
     /// "- 1" to not incude a newline for last line.
     /// Required if resolution != terminal size.
-    pub fn chars_required(resolution: &(u64, u64)) -> usize {
+    pub fn bytes_required(resolution: &(u64, u64)) -> usize {
         Self::pixels_required(resolution) * pixel::VALUE_LEN + (resolution.1 / 2) as usize - 1
     }
 
-    /// Bytes required for metadata depends on the [pixel::Meta] struct and its memory layout.
-    pub fn metas_bytes_required(resolution: &(u64, u64)) -> usize {
-        let len = Self::pixels_required(resolution);
-        let no_padding = len * pixel::Meta::required_buffer_size();
-        let with_padding = no_padding + (len * 4); // +4 for alignment.
-        with_padding
+    pub fn new(resolution: &(u64, u64)) -> Self {
+        let mut buffer = Self {
+            depth: Vec::new(),
+            border_depth: Vec::new(),
+            ids: Vec::new(),
+            flags: Vec::new(),
+            bytes_clear: Vec::new(),
+            bytes: Vec::new(),
+            pixels_dimensions: (0, 0),
+        };
+        buffer.resize(resolution);
+        buffer
     }
 
-    pub fn new(resolution: &(u64, u64)) -> Self {
+    /// Change resolution. Memory is reused, if there is enough of it. Content is cleared.
+    pub fn resize(&mut self, resolution: &(u64, u64)) {
         debug_assert!(resolution.0 > 0 && resolution.1 > 0);
 
-        let metas_len = Self::metas_bytes_required(resolution);
-        let chars_len = Self::chars_required(resolution);
         let pixels_len = Self::pixels_required(resolution);
-        let pixels_dimensions = (resolution.0 as usize, (resolution.1 / 2) as usize);
-
-        let mut metas_bytes: Vec<u8> = vec![0; metas_len];
-        let mut chars: Vec<char> = vec!['\n' as char; chars_len]; // Might as well inject newlines here.
-        let mut pixels: Vec<pixel::Pixel> = Vec::with_capacity(pixels_len);
-
-        let meta_step = pixel::Meta::required_buffer_size() + 4; // +4 for alignment.
-        let char_step = pixel::VALUE_LEN;
-
-        let mut col = 0;
-        let mut row = 0;
-        let index = |col: usize, row: usize| -> usize { col + row * resolution.0 as usize };
-
-        while pixels.len() < pixels_len {
-            let index = index(col, row);
-
-            let meta_start = index * meta_step;
-            let char_start = index * char_step + row; // +row because we want to skip injected newlines '\n'.
-
-            pixels.push(pixel::Pixel {
-                meta: pixel::Meta::from_slice(unsafe {
-                    std::slice::from_raw_parts_mut(
-                        metas_bytes.as_mut_ptr().add(meta_start),
-                        pixel::Meta::required_buffer_size(),
-                    )
-                }),
-                value: pixel::Value::from_slice(unsafe {
-                    std::slice::from_raw_parts_mut(chars.as_mut_ptr().add(char_start), char_step)
-                        .try_into()
-                        .unwrap()
-                }),
-            });
-
-            col += 1;
-            if col == pixels_dimensions.0 {
-                // Go to next row.
-                // Has to be incremented here (before setting values) to set correct row offset.
-                row += 1;
-                col = 0;
-            }
+        self.pixels_dimensions = (resolution.0 as usize, (resolution.1 / 2) as usize);
+
+        self.depth.resize(pixels_len * 2, 0.0);
+        self.border_depth.resize(pixels_len * 2, 0.0);
+        self.ids.resize(pixels_len * 2, Id::NONE);
+        self.flags.resize(pixels_len, 0);
+
+        // Empty cells, with a newline after every row but the last.
+        let row = [pixel::EMPTY.repeat(self.pixels_dimensions.0), vec![b'\n']].concat();
+        self.bytes_clear.clear();
+        for _ in 0..self.pixels_dimensions.1 {
+            self.bytes_clear.extend_from_slice(&row);
         }
+        self.bytes_clear.pop();
+        debug_assert!(self.bytes_clear.len() == Self::bytes_required(resolution));
 
-        Self {
-            metas_bytes,
-            chars_clear: chars.clone(),
-            chars,
-            pixels,
-            pixels_dimensions,
-        }
+        self.bytes.clone_from(&self.bytes_clear);
+        self.clear();
     }
 
-    pub fn chars(&self) -> &[char] {
-        &self.chars
+    pub fn resolution_matches(&self, resolution: &(u64, u64)) -> bool {
+        self.pixels_dimensions == (resolution.0 as usize, (resolution.1 / 2) as usize)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
     }
 
     pub fn clear(&mut self) {
-        self.metas_bytes.fill(0);
-        self.chars.clone_from_slice(&self.chars_clear);
+        // Depths (and ids) are only read if flagged, so they need no clearing.
+        self.flags.fill(0);
+        self.bytes.copy_from_slice(&self.bytes_clear);
     }
 
-    pub fn pixel(&self, row: usize, col: usize) -> &pixel::Pixel<'a> {
-        &self.pixels[col + row * self.pixels_dimensions.0]
+    /// What is drawn in the nearest half of a cell, if anything.
+    pub fn id(&self, row: usize, col: usize) -> Option<Id> {
+        let (width, height) = self.pixels_dimensions;
+        if row >= height || col >= width {
+            return None;
+        }
+
+        let index = col + row * width;
+        [Half::Upper, Half::Lower]
+            .into_iter()
+            .filter(|half| self.flags[index] & flag::depth(*half) != 0)
+            .map(|half| index * 2 + half.offset())
+            .min_by(|a, b| self.depth[*a].total_cmp(&self.depth[*b]))
+            .map(|index| self.ids[index])
+            .filter(|id| *id != Id::NONE)
     }
 
-    pub fn pixel_mut(&mut self, row: usize, col: usize) -> &mut pixel::Pixel<'a> {
-        &mut self.pixels[col + row * self.pixels_dimensions.0]
+    /// Whether the character at a position is the given one.
+    #[cfg(test)]
+    pub fn glyph_is(&self, row: usize, col: usize, c: &Char) -> bool {
+        let start = (col + row * self.pixels_dimensions.0) * pixel::VALUE_LEN + row; // +row to skip newlines.
+        self.bytes[start..start + pixel::VALUE_LEN] == c.encoded()
+    }
+
+    /// All rows as a single [Band].
+    #[cfg(test)]
+    pub fn band_mut(&mut self) -> Band<'_> {
+        Band {
+            depth: &mut self.depth,
+            border_depth: &mut self.border_depth,
+            ids: &mut self.ids,
+            flags: &mut self.flags,
+            bytes: &mut self.bytes,
+            rows: 0..self.pixels_dimensions.1,
+            width: self.pixels_dimensions.0,
+        }
+    }
+
+    /// Split the rows into (at most) `count` bands of equal height, e.g. to draw to them in parallel.
+    pub fn bands_mut(&mut self, count: usize) -> Vec<Band<'_>> {
+        let (width, height) = self.pixels_dimensions;
+        let rows = height.div_ceil(count.max(1));
+        let pixels = rows * width;
+        let bytes = rows * (width * pixel::VALUE_LEN + 1); // +1 for newlines.
+
+        self.depth
+            .chunks_mut(pixels * 2)
+            .zip(self.border_depth.chunks_mut(pixels * 2))
+            .zip(self.ids.chunks_mut(pixels * 2))
+            .zip(self.flags.chunks_mut(pixels))
+            .zip(self.bytes.chunks_mut(bytes))
+            .enumerate()
+            .map(|(index, ((((depth, border_depth), ids), flags), bytes))| Band {
+                rows: index * rows..index * rows + flags.len() / width,
+                width,
+                depth,
+                border_depth,
+                ids,
+                flags,
+                bytes,
+            })
+            .collect()
+    }
+}
+
+/// A horizontal band of rows of a [TerminalBuffer]. Bands do not overlap, so each can be drawn to on its own thread.
+/// Pixels are addressed by the same row and column as in the whole buffer.
+pub struct Band<'b> {
+    depth: &'b mut [f64],
+    border_depth: &'b mut [f64],
+    ids: &'b mut [Id],
+    flags: &'b mut [u8],
+    bytes: &'b mut [u8],
+    rows: Range<usize>,
+    width: usize,
+}
+
+impl<'b> Band<'b> {
+    pub fn rows(&self) -> &Range<usize> {
+        &self.rows
+    }
+
+    pub fn contains(&self, row: usize) -> bool {
+        self.rows.contains(&row)
+    }
+
+    #[inline]
+    fn index(&self, row: usize, col: usize) -> usize {
+        debug_assert!(self.contains(row) && col < self.width);
+        col + (row - self.rows.start) * self.width
+    }
+
+    #[inline]
+    fn byte_index(&self, row: usize, col: usize) -> usize {
+        self.index(row, col) * pixel::VALUE_LEN + (row - self.rows.start) // +row to skip newlines.
+    }
+
+    /// Depth of the nearest thing drawn in a half, if anything.
+    pub fn depth(&self, row: usize, col: usize, half: Half) -> Option<f64> {
+        let index = self.index(row, col);
+        (self.flags[index] & flag::depth(half) != 0).then(|| self.depth[index * 2 + half.offset()])
+    }
+
+    pub fn set_depth(&mut self, row: usize, col: usize, half: Half, depth: f64) {
+        let index = self.index(row, col);
+        self.flags[index] |= flag::depth(half);
+        self.depth[index * 2 + half.offset()] = depth;
+    }
+
+    /// Set what is drawn in a half, along with its depth.
+    pub fn set_depth_and_id(&mut self, row: usize, col: usize, half: Half, depth: f64, id: Id) {
+        self.set_depth(row, col, half, depth);
+        let index = self.index(row, col);
+        self.ids[index * 2 + half.offset()] = id;
+    }
+
+    /// Depth of a polygon border drawn in a half, if any.
+    pub fn border_depth(&self, row: usize, col: usize, half: Half) -> Option<f64> {
+        let index = self.index(row, col);
+        (self.flags[index] & flag::border(half) != 0)
+            .then(|| self.border_depth[index * 2 + half.offset()])
+    }
+
+    pub fn set_border_depth(&mut self, row: usize, col: usize, half: Half, depth: f64) {
+        let index = self.index(row, col);
+        self.flags[index] |= flag::border(half);
+        self.border_depth[index * 2 + half.offset()] = depth;
+    }
+
+    /// Forget polygon borders of both halves.
+    pub fn clear_border(&mut self, row: usize, col: usize) {
+        let index = self.index(row, col);
+        self.flags[index] &= !(flag::border(Half::Upper) | flag::border(Half::Lower));
+    }
+
+    /// Erase what is drawn in both halves, as if cleared.
+    pub fn clear_cell(&mut self, row: usize, col: usize) {
+        let index = self.index(row, col);
+        self.flags[index] = 0;
+        self.set_glyph(row, col, Char::Empty);
+    }
+
+    pub fn glyph_is(&self, row: usize, col: usize, c: &Char) -> bool {
+        let start = self.byte_index(row, col);
+        self.bytes[start..start + pixel::VALUE_LEN] == c.encoded()
+    }
+
+    pub fn set_glyph(&mut self, row: usize, col: usize, c: Char) {
+        let start = self.byte_index(row, col);
+        self.bytes[start..start + pixel::VALUE_LEN].copy_from_slice(&c.encoded());
     }
 }
 
@@ -239,17 +380,19 @@ impl<'a> TerminalBuffer<'a> {
 mod tests {
     use super::*;
 
+    fn cell(buffer: &TerminalBuffer, row: usize, col: usize) -> [u8; pixel::VALUE_LEN] {
+        let start = (col + row * buffer.pixels_dimensions.0) * pixel::VALUE_LEN + row; // ("+ row" |  Take newlines into consideration, i.e. ignore them).
+        buffer.bytes[start..start + pixel::VALUE_LEN]
+            .try_into()
+            .unwrap()
+    }
+
     fn set_and_check(buffer: &mut TerminalBuffer, c: pixel::Char, at: &[(usize, usize)]) {
         for (row, col) in at {
-            let pixel = buffer.pixel_mut(*row, *col);
-            pixel.value.set(c.clone());
-            assert!(pixel.value.get() == c.value());
-            assert!(
-                buffer.chars[(col + row * buffer.pixels_dimensions.0) * pixel::VALUE_LEN
-                    + (pixel::VALUE_LEN - 1) // ("+ pixel::VALUE_LEN - 1" | Ignore pixel ANSI formatting data, and just look at the actual value.).
-                    + row] // ("+ row" |  Take newlines into consideration, i.e. ignore them).
-                    == c.value()
-            );
+            let mut band = buffer.band_mut();
+            band.set_glyph(*row, *col, c.clone());
+            assert!(band.glyph_is(*row, *col, &c));
+            assert!(cell(buffer, *row, *col) == c.encoded());
         }
     }
 
@@ -266,13 +409,8 @@ mod tests {
                     }
                 }
 
-                assert!(buffer.pixel(row, col).value.get() == c.value());
-                assert!(
-                    buffer.chars[(col + row * buffer.pixels_dimensions.0) * pixel::VALUE_LEN
-                    + (pixel::VALUE_LEN - 1) // ("+ pixel::VALUE_LEN - 1" | Ignore pixel ANSI formatting data, and just look at the actual value.).
-                    + row] // ("+ row" |  Take newlines into consideration, i.e. ignore them).
-                        == c.value()
-                );
+                assert!(buffer.glyph_is(row, col, &c));
+                assert!(cell(buffer, row, col) == c.encoded());
             }
         }
     }
@@ -280,10 +418,10 @@ mod tests {
     fn newlines_are_present(buffer: &TerminalBuffer) {
         for row in 0..(buffer.pixels_dimensions.1 - 1) {
             assert!(
-                buffer.chars[(buffer.pixels_dimensions.0 + (row * buffer.pixels_dimensions.0))
+                buffer.bytes[(buffer.pixels_dimensions.0 + (row * buffer.pixels_dimensions.0))
                     * pixel::VALUE_LEN
                     + row]
-                    == '\n'
+                    == b'\n'
             );
         }
     }
@@ -293,20 +431,21 @@ mod tests {
         let tester = |width: u64, height: u64| {
             let resolution = (width, height);
             let buffer = TerminalBuffer::new(&resolution);
+            let pixels = TerminalBuffer::pixels_required(&resolution);
             assert!(
-                buffer.metas_bytes.len() == TerminalBuffer::metas_bytes_required(&resolution),
+                buffer.depth.len() == pixels * 2 && buffer.border_depth.len() == pixels * 2,
                 "Actual: {}",
-                buffer.metas_bytes.len()
+                buffer.depth.len()
             );
             assert!(
-                buffer.chars.len() == TerminalBuffer::chars_required(&resolution),
+                buffer.bytes.len() == TerminalBuffer::bytes_required(&resolution),
                 "Actual: {}",
-                buffer.chars.len()
+                buffer.bytes.len()
             );
             assert!(
-                buffer.pixels.len() == TerminalBuffer::pixels_required(&resolution),
+                buffer.flags.len() == pixels,
                 "Actual: {}",
-                buffer.pixels.len()
+                buffer.flags.len()
             );
         };
         for (i, j) in (1..=100).zip(2..=100) {
@@ -315,6 +454,146 @@ mod tests {
         tester(742, 393);
     }
 
+    #[test]
+    fn bands() {
+        let resolution = (7, 20);
+        let mut buffer = TerminalBuffer::new(&resolution);
+
+        for count in 1..=12 {
+            let bands = buffer.bands_mut(count);
+            assert!(bands.len() <= count);
+            assert!(bands.first().unwrap().rows().start == 0);
+            assert!(bands.last().unwrap().rows().end == 10);
+            assert!(bands
+                .windows(2)
+                .all(|b| b[0].rows().end == b[1].rows().start));
+        }
+
+        let mut bands = buffer.bands_mut(3);
+        bands[1].set_glyph(5, 6, pixel::Char::Full);
+        assert!(!bands[0].contains(5) && bands[1].contains(5));
+        assert!(bands[1].glyph_is(5, 6, &pixel::Char::Full));
+        drop(bands);
+        assert!(buffer.glyph_is(5, 6, &pixel::Char::Full));
+    }
+
+    #[test]
+    fn valid_utf8() {
+        let resolution = (37, 20);
+        let mut buffer = TerminalBuffer::new(&resolution);
+        let mut band = buffer.band_mut();
+        band.set_glyph(3, 4, pixel::Char::Upper);
+        band.set_glyph(9, 36, pixel::Char::Custom('\u{2591}'));
+
+        let s = std::str::from_utf8(buffer.bytes()).unwrap();
+        assert!(s.lines().count() == 10);
+        assert!(s.lines().all(|line| line.chars().count() == 37));
+        assert!(buffer.glyph_is(3, 4, &pixel::Char::Upper));
+        assert!(buffer.glyph_is(9, 36, &pixel::Char::Custom('\u{2591}')));
+    }
+
+    #[test]
+    fn depth_and_borders() {
+        let resolution = (10, 10);
+        let mut buffer = TerminalBuffer::new(&resolution);
+        let mut band = buffer.band_mut();
+
+        assert!(band.depth(2, 3, Half::Upper).is_none());
+        band.set_depth(2, 3, Half::Upper, 1.5);
+        assert!(band.depth(2, 3, Half::Upper) == Some(1.5));
+        assert!(band.depth(2, 3, Half::Lower).is_none());
+        assert!(band.depth(2, 4, Half::Upper).is_none());
+
+        band.set_border_depth(2, 3, Half::Lower, -1.0);
+        band.set_border_depth(2, 3, Half::Upper, 2.0);
+        assert!(band.border_depth(2, 3, Half::Lower) == Some(-1.0));
+        band.clear_border(2, 3);
+        assert!(band.border_depth(2, 3, Half::Lower).is_none());
+        assert!(band.border_depth(2, 3, Half::Upper).is_none());
+        assert!(band.depth(2, 3, Half::Upper) == Some(1.5));
+
+        band.set_glyph(2, 3, pixel::Char::Full);
+        band.clear_cell(2, 3);
+        assert!(band.depth(2, 3, Half::Upper).is_none());
+        assert!(band.glyph_is(2, 3, &pixel::Char::Empty));
+
+        band.set_depth(2, 3, Half::Upper, 1.5);
+        buffer.clear();
+        assert!(buffer.band_mut().depth(2, 3, Half::Upper).is_none());
+    }
+
+    #[test]
+    fn ids() {
+        let mut buffer = TerminalBuffer::new(&(10, 10));
+        let mut band = buffer.band_mut();
+
+        band.set_depth_and_id(2, 3, Half::Upper, 2.0, pixel::Id::face(1, 4));
+        band.set_depth_and_id(2, 3, Half::Lower, 1.0, pixel::Id::vertex(7));
+        band.set_depth_and_id(2, 4, Half::Upper, 1.0, pixel::Id::NONE);
+        band.set_depth(2, 5, Half::Upper, 1.0);
+
+        // The nearer half is picked.
+        assert!(buffer.id(2, 3) == Some(pixel::Id::vertex(7)));
+        assert!(buffer.id(2, 4).is_none());
+        assert!(buffer.id(2, 6).is_none());
+        assert!(buffer.id(10, 3).is_none());
+
+        buffer.clear();
+        assert!(buffer.id(2, 3).is_none());
+    }
+
+    #[test]
+    fn resize() {
+        let mut buffer = TerminalBuffer::new(&(10, 10));
+        buffer.band_mut().set_depth(4, 9, Half::Lower, 1.0);
+
+        for resolution in [(20, 6), (3, 40), (10, 10)] {
+            buffer.resize(&resolution);
+            assert!(buffer.resolution_matches(&resolution));
+            assert!(buffer.bytes().len() == TerminalBuffer::bytes_required(&resolution));
+            check_for_value_in_buffer(&buffer, pixel::Char::Empty, &[]);
+            newlines_are_present(&buffer);
+        }
+
+        assert!(buffer.band_mut().depth(4, 9, Half::Lower).is_none());
+    }
+
+    /// Clearing, drawing and writing a frame at a large resolution.
+    /// Run with "cargo test --release -p renderer -- --ignored --nocapture".
+    #[test]
+    #[ignore]
+    fn benchmark_742x393() {
+        use std::io::Write;
+
+        let resolution = (742, 393);
+        let frames = 1000;
+        let mut buffer = TerminalBuffer::new(&resolution);
+        let mut out: Vec<u8> = Vec::with_capacity(buffer.bytes().len());
+
+        let start = std::time::Instant::now();
+        for frame in 0..frames {
+            buffer.clear();
+
+            let (width, height) = buffer.pixels_dimensions;
+            let mut band = buffer.band_mut();
+            for row in 0..height {
+                band.set_glyph(row, (row + frame) % width, pixel::Char::Full);
+            }
+
+            out.clear();
+            out.write_all(buffer.bytes()).unwrap();
+        }
+        let elapsed = start.elapsed();
+
+        println!(
+            "{}x{}: {:?} per frame, {} bytes per frame.",
+            resolution.0,
+            resolution.1,
+            elapsed / frames as u32,
+            out.len()
+        );
+    }
+
     #[test]
     fn set_pixel_value() {
         {
@@ -351,10 +630,10 @@ mod tests {
             set_and_check(&mut buffer, pixel::Char::Full, &row_col);
             check_for_value_in_buffer(&buffer, pixel::Char::Empty, &row_col);
             newlines_are_present(&buffer);
-            buffer.pixel_mut(2, 5).value.set(pixel::Char::Empty);
+            buffer.band_mut().set_glyph(2, 5, pixel::Char::Empty);
             check_for_value_in_buffer(&buffer, pixel::Char::Empty, &[(4, 3)]);
             newlines_are_present(&buffer);
-            buffer.pixel_mut(4, 3).value.set(pixel::Char::Empty);
+            buffer.band_mut().set_glyph(4, 3, pixel::Char::Empty);
             check_for_value_in_buffer(&buffer, pixel::Char::Empty, &[]);
             newlines_are_present(&buffer);
         }
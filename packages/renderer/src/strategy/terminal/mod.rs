@@ -1,3 +1,3 @@
 pub mod terminal;
 
-mod buffer;
\ No newline at end of file
+pub mod buffer;
\ No newline at end of file
@@ -1,18 +1,25 @@
 use std::io::Write;
+use std::ops::Range;
 use std::rc::Rc;
+use std::time::Instant;
 use std::cell::RefCell;
 
 use super::buffer::*;
+use super::buffer::pixel::{Char, Half, Id};
 use crate::{
-    Camera, ProjectionMode, RenderOption, RendererBuilderTrait, RendererConfiguration,
-    RendererTrait, ViewMode, __RendererTrait,
+    bounds::Bounds, overlay::Grid, profile::Profiler, Camera, Object, Pick, ProjectionMode,
+    RenderOption, Selection,
+    RendererBuilderTrait, RendererConfiguration, RendererTrait, ViewMode, __RendererTrait,
 };
 use linear_algebra::{quaternion::rotate, quaternion::Quaternion, vector::VectorRow};
 
+type IntersectionChecker =
+    dyn Fn(&VectorRow<f64, 3>) -> Option<(VectorRow<f64, 3>, f64)> + Send + Sync;
+
 struct Canvas {
-    buffer: TerminalBuffer<'static>, // TODO: I don't want to specify more lifetimes...
+    buffer: TerminalBuffer,
     /// Returns [None] if no intersection is found. Otherwise point at which line between vertex and viewpoint intersects the viewport, and it's depth.
-    line_intersection_checker: Box<dyn Fn(&VectorRow<f64, 3>) -> Option<(VectorRow<f64, 3>, f64)>>,
+    line_intersection_checker: Box<IntersectionChecker>,
 }
 
 impl Canvas {
@@ -45,7 +52,7 @@ impl Canvas {
         camera_view_mode: &ViewMode,
         rotation: Quaternion<f64>,
         rotation_inverse: Quaternion<f64>,
-    ) -> Box<dyn Fn(&VectorRow<f64, 3>) -> Option<(VectorRow<f64, 3>, f64)>> {
+    ) -> Box<IntersectionChecker> {
         Box::new({
             // Cached values for closure.
             let normal = VectorRow::<f64, 3>::from([0.0, 1.0, 0.0]);
@@ -106,10 +113,8 @@ impl Canvas {
 
     fn update(&mut self, config: &RendererConfiguration) -> Result<(), &'static str> {
         let resolution = config.camera.resolution;
-        let len = TerminalBuffer::chars_required(&resolution);
-
-        if self.buffer.chars().len() != len {
-            self.buffer = TerminalBuffer::new(&resolution);
+        if !self.buffer.resolution_matches(&resolution) {
+            self.buffer.resize(&resolution);
         }
 
         // TODO: Fix orthographic option
@@ -136,6 +141,18 @@ impl Canvas {
 pub struct TerminalExtras {
     pub pixel_width_scaling: f64,
     pub pixel_height_scaling: f64,
+    pub invert_colors: bool,
+    /// Wrap frames in synchronized update mode (2026), so that the terminal shows them whole.
+    /// Only enable if the terminal supports it.
+    pub synchronized_output: bool,
+    /// Upper limit of threads to project and rasterize with. Defaults to the available parallelism.
+    pub threads: usize,
+    /// Draw the world axes, as seen by the camera, in a corner.
+    pub gizmo: bool,
+    /// Ground grid to draw, hidden behind the model where it is in front.
+    pub grid: Option<Grid>,
+    /// Elements to highlight, over everything else.
+    pub selection: Selection,
 }
 
 impl Default for TerminalExtras {
@@ -143,6 +160,12 @@ impl Default for TerminalExtras {
         Self {
             pixel_width_scaling: 1.0,
             pixel_height_scaling: 1.0,
+            invert_colors: false,
+            synchronized_output: false,
+            threads: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            gizmo: false,
+            grid: None,
+            selection: Selection::default(),
         }
     }
 }
@@ -150,6 +173,15 @@ impl Default for TerminalExtras {
 #[derive(Default)]
 pub struct TerminalBuilder {
     config: RendererConfiguration,
+    sink: Option<Box<dyn Write>>,
+}
+
+impl TerminalBuilder {
+    /// Write frames to `sink` instead of stdout, and leave the terminal as is. E.g. for benchmarks.
+    pub fn with_sink(mut self, sink: Box<dyn Write>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
 }
 
 impl RendererBuilderTrait for TerminalBuilder {
@@ -168,14 +200,14 @@ impl RendererBuilderTrait for TerminalBuilder {
     }
 
     fn build(self) -> Result<Self::Renderer, &'static str> {
-        Self::Renderer::new(self.config)
+        Terminal::with_sink(self.config, self.sink)
     }
 
     fn build_with_config(
         self,
         config: RendererConfiguration,
     ) -> Result<Self::Renderer, &'static str> {
-        Self::Renderer::new(config)
+        Terminal::with_sink(config, self.sink)
     }
 }
 
@@ -189,18 +221,431 @@ pub struct Terminal {
     vertices: Option<Rc<RefCell<Vec<VectorRow<f64, 3>>>>>,
     vertices_projected: Vec<Option<VectorRow<f64, 3>>>,
     line_draw_order: Option<Rc<RefCell<Vec<Vec<usize>>>>>,
+    objects: Vec<(Object, Option<Bounds>)>,
+    /// Ranges of [Self::line_draw_order] not belonging to objects culled this frame.
+    line_draw_order_visible: Vec<Range<usize>>,
+    /// Segments of the [TerminalExtras::grid] in view, projected, with their opacity.
+    grid_projected: Vec<(VectorRow<f64, 3>, VectorRow<f64, 3>, f64)>,
+
+    // Output. Everything written for a frame is gathered in [Self::frame], to be written at once.
+    // The previously rendered canvas is kept to only write what changed.
+    frame: Vec<u8>,
+    frame_previous: Vec<u8>,
+    hud: String,
+    redraw: bool,
+    /// Written to instead of stdout, if set.
+    sink: Option<Box<dyn Write>>,
 
     // Extra
     extras: TerminalExtras,
+    profiler: Profiler,
+}
+
+/// Fraction of changed cells above which the whole frame is written instead of only the changes.
+const REDRAW_THRESHOLD: f64 = 0.5;
+
+/// Unchanged cells between two changed runs that are rewritten rather than skipped with a cursor move.
+/// (A cursor move is at least 6 bytes, a block character 3.)
+const RUN_GAP_MAX: usize = 2;
+
+/// Least number of vertices worth projecting on a thread of its own.
+const PROJECTION_CHUNK_MIN: usize = 4096;
+
+/// Frames the [Profiler] keeps by default.
+const PROFILER_HISTORY: usize = 120;
+
+/// Least number of rows worth rasterizing on a thread of its own.
+const BAND_ROWS_MIN: usize = 8;
+
+/// Length of the axes of the [TerminalExtras::gizmo], in (vertical) pixels.
+const GIZMO_RADIUS: f64 = 6.0;
+
+/// Labels at the ends of the x, y, and z axes of the [TerminalExtras::gizmo]: Ⓧ, Ⓨ, and Ⓩ.
+const GIZMO_LABELS: [char; 3] = ['\u{24CD}', '\u{24CE}', '\u{24CF}'];
+
+/// Drawn over the edges of the selected face (see [TerminalExtras::selection]): ▒.
+const SELECTION_EDGE: char = '\u{2592}';
+
+/// Drawn over the selected vertex: ◆.
+const SELECTION_VERTEX: char = '\u{25C6}';
+
+/// Ordered dithering thresholds (in sixteenths), to draw a fraction of the pixels of an area evenly.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Everything rasterization reads, shared by the threads drawing the [Band]s.
+struct Scene<'s> {
+    camera: &'s Camera,
+    option: &'s RenderOption,
+    vertices_projected: &'s [Option<VectorRow<f64, 3>>],
+    line_draw_order: Option<&'s [Vec<usize>]>,
+    /// The parts of [Scene::line_draw_order] to draw.
+    line_draw_order_visible: &'s [Range<usize>],
+    extras: &'s TerminalExtras,
+    grid_projected: &'s [(VectorRow<f64, 3>, VectorRow<f64, 3>, f64)],
+}
+
+/// Run `work` for each item on scoped threads, the first one on the current thread. Returns when all are done.
+fn parallel<T: Send>(items: impl IntoIterator<Item = T>, work: impl Fn(T) + Sync) {
+    let work = &work;
+
+    std::thread::scope(|scope| {
+        let mut items = items.into_iter();
+        let first = items.next();
+
+        for item in items {
+            scope.spawn(move || work(item));
+        }
+
+        if let Some(first) = first {
+            work(first);
+        }
+    });
+}
+
+/// Project a vertex onto the canvas: its (centered) position in pixels, with the depth in between.
+/// [None] if it is not in view.
+fn project_vertex(
+    camera: &Camera,
+    extras: &TerminalExtras,
+    line_intersection_checker: &IntersectionChecker,
+    vertex: &VectorRow<f64, 3>,
+) -> Option<VectorRow<f64, 3>> {
+    let x_min = -((camera.resolution.0 / 2) as isize);
+    let x_max = (camera.resolution.0 / 2) as isize;
+    let z_min = -((camera.resolution.1 / 2) as isize);
+    let z_max = (camera.resolution.1 / 2) as isize;
+
+    let (mut intersection, depth) = line_intersection_checker(vertex)?;
+
+    // Adjust points according to width heigh pixel ratio.
+    intersection[0] *= extras.pixel_width_scaling;
+    intersection[2] *= extras.pixel_height_scaling;
+
+    // Do not store the point if it is outside of visible viewport space.
+    if !((x_min..x_max).contains(&(intersection[0] as isize))
+        && (z_min..z_max).contains(&(intersection[2] as isize)))
+    {
+        return None;
+    }
+
+    // Store depth information.
+    intersection[1] = depth;
+
+    Some(intersection)
+}
+
+#[inline]
+fn interpolate_depth(start: f64, end: f64, steps_max: f64, step_current: f64) -> f64 {
+    let div = step_current / steps_max;
+    (1.0 - div) * start + div * end
+}
+
+/// Step along the line between two projected vertices, calling `pixel` with the position and (interpolated)
+/// depth of each pixel on it. Lines entirely outside of the [Band] are skipped.
+#[inline]
+fn line(
+    band: &mut Band,
+    camera: &Camera,
+    a: &VectorRow<f64, 3>,
+    b: &VectorRow<f64, 3>,
+    mut pixel: impl FnMut(&mut Band, isize, f64, isize),
+) {
+    // Skip lines entirely outside of the band.
+    let rows = [row_at(camera, a[2] as isize), row_at(camera, b[2] as isize)];
+    if rows[0].max(rows[1]) < band.rows().start || rows[0].min(rows[1]) >= band.rows().end {
+        return;
+    }
+
+    let mut x0 = a[0] as isize;
+    let x1 = b[0] as isize;
+    let mut z0 = a[2] as isize;
+    let z1 = b[2] as isize;
+
+    let dx = (x1 - x0).abs();
+    let dz = (z1 - z0).abs();
+
+    let sx = (x1 - x0).signum();
+    let sz = (z1 - z0).signum();
+
+    let mut err = dx - dz;
+    let steps_max = (b[0] - a[0]).abs() + (b[2] - a[2]).abs();
+    let mut steps_taken = 0;
+
+    while x0 != x1 || z0 != z1 {
+        pixel(
+            band,
+            x0,
+            interpolate_depth(a[1], b[1], steps_max, steps_taken as f64),
+            z0,
+        );
+
+        let e2 = 2 * err;
+
+        if e2 > -dz {
+            err -= dz;
+            x0 += sx;
+        }
+
+        if e2 < dx {
+            err += dx;
+            z0 += sz;
+        }
+
+        steps_taken += 1;
+    }
+}
+
+/// Whether to draw a pixel of something with the given opacity (1 opaque, 0 invisible), see [BAYER].
+fn dither(opacity: f64, x: isize, z: isize) -> bool {
+    let threshold = BAYER[z.rem_euclid(4) as usize][x.rem_euclid(4) as usize];
+    opacity * 16.0 > threshold as f64 + 0.5
+}
+
+/// Buffer row of a (centered) vertical position.
+fn row_at(camera: &Camera, z: isize) -> usize {
+    let z = (z + (camera.resolution.1 / 2) as isize) / 2;
+    camera.resolution.1 as usize / 2 - z as usize - 1 // TODO: Ugly fix for the buffer being upside down.
+}
+
+/// Index of the vertex of a face projected nearest to a pixel (x, z), if any of them are projected.
+fn nearest_vertex(
+    order: &[usize],
+    vertices_projected: &[Option<VectorRow<f64, 3>>],
+    x: f64,
+    z: f64,
+) -> Option<usize> {
+    order
+        .iter()
+        .filter_map(|&index| {
+            let vertex = vertices_projected.get(index)?.as_ref()?;
+            Some((index, (vertex[0] - x).powi(2) + (vertex[2] - z).powi(2)))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}
+
+/// The part of the scene that projects onto the canvas: a pyramid with its apex in the viewpoint.
+/// Planes are kept in camera space, relative to the viewpoint, so all of them pass through the origin.
+struct Frustum {
+    position: VectorRow<f64, 3>,
+    rotation: Quaternion<f64>,
+    rotation_inverse: Quaternion<f64>,
+    viewpoint: VectorRow<f64, 3>,
+    /// Distance from the viewpoint to the viewport, in pixels of the larger pixel side.
+    focal: f64,
+    /// Normals (pointing inwards) of the near plane and the four sides.
+    normals: [VectorRow<f64, 3>; 5],
+}
+
+impl Frustum {
+    /// Mirrors [Canvas::create_intersection_checker] and the bounds check of the projection.
+    fn new(camera: &Camera, extras: &TerminalExtras) -> Self {
+        // TODO: Fix orthographic option
+        let fov = if let ProjectionMode::Perspective { fov } = camera.projection_mode {
+            fov
+        } else {
+            90
+        };
+
+        // Distance from the viewpoint to the viewport.
+        let focal = (camera.resolution.0 as f64 / 2.0)
+            / f64::tan((fov as f64 / 2.0) * (std::f64::consts::PI / 180.0));
+        let viewpoint = match camera.view_mode {
+            ViewMode::FirstPerson => VectorRow::from([0.0, 0.0, 0.0]),
+            ViewMode::Orbital => VectorRow::from([0.0, -focal, 0.0]),
+        };
+
+        // Half the canvas, plus a pixel for positions rounded towards zero.
+        let x = camera.resolution.0 as f64 / 2.0 + 1.0;
+        let z = camera.resolution.1 as f64 / 2.0 + 1.0;
+        let x_focal = focal * extras.pixel_width_scaling;
+        let z_focal = focal * extras.pixel_height_scaling;
+
+        let normalized = |normal: [f64; 3]| {
+            let mut normal = VectorRow::from(normal);
+            let length = normal.length();
+            normal.0.scale(1.0 / length);
+            normal
+        };
+
+        Self {
+            position: camera.position.clone(),
+            rotation: camera.rotation.0.clone(),
+            rotation_inverse: camera.rotation.1.clone(),
+            viewpoint,
+            focal: x_focal.max(z_focal),
+            normals: [
+                VectorRow::from([0.0, 1.0, 0.0]),
+                normalized([-x_focal, x, 0.0]),
+                normalized([x_focal, x, 0.0]),
+                normalized([0.0, z, -z_focal]),
+                normalized([0.0, z, z_focal]),
+            ],
+        }
+    }
+
+    /// World position of the viewpoint.
+    fn eye(&self) -> VectorRow<f64, 3> {
+        let viewpoint = rotate(&self.viewpoint, &self.rotation, &self.rotation_inverse);
+        (&self.position.0 + &viewpoint.0).into()
+    }
+
+    fn to_camera_space(&self, vertex: &VectorRow<f64, 3>) -> VectorRow<f64, 3> {
+        let vertex: VectorRow<f64, 3> = (&vertex.0 - &self.position.0).into();
+        let vertex = rotate(&vertex, &self.rotation_inverse, &self.rotation);
+        (&vertex.0 - &self.viewpoint.0).into()
+    }
+
+    /// Projected diameter of the bounds (their sphere), in pixels. Infinite if the viewpoint is inside of them.
+    fn size(&self, bounds: &Bounds) -> f64 {
+        let distance = self.to_camera_space(&bounds.center)[1];
+
+        if distance <= bounds.radius {
+            return f64::INFINITY;
+        }
+
+        2.0 * bounds.radius * self.focal / distance
+    }
+
+    /// Whether any part of the bounds may be visible. Never false for visible bounds, but may be true for hidden ones.
+    fn sees(&self, bounds: &Bounds) -> bool {
+        let center = self.to_camera_space(&bounds.center);
+        let mut contained = true;
+
+        for normal in self.normals.iter() {
+            let distance = normal.dot(&center);
+
+            if distance < -bounds.radius {
+                return false;
+            }
+
+            contained &= distance >= bounds.radius;
+        }
+
+        if contained {
+            return true;
+        }
+
+        // The sphere crosses a plane, the box might still be entirely outside of one.
+        let corners = bounds.corners().map(|corner| self.to_camera_space(&corner));
+        self.normals
+            .iter()
+            .all(|normal| corners.iter().any(|corner| normal.dot(corner) >= 0.0))
+    }
+}
+
+/// Split `0..len` into the ranges not covered by any of `excluded`.
+fn ranges_except(len: usize, excluded: impl Iterator<Item = Range<usize>>) -> Vec<Range<usize>> {
+    let mut excluded = excluded.collect::<Vec<_>>();
+    excluded.sort_by_key(|range| range.start);
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    for range in excluded {
+        if range.start.min(len) > start {
+            ranges.push(start..range.start.min(len));
+        }
+        start = start.max(range.end.min(len));
+    }
+
+    if start < len {
+        ranges.push(start..len);
+    }
+
+    ranges
+}
+
+/// Append the changes between two frames to `diff`, as cursor movements followed by the changed runs of cells.
+/// Returns false (and appends nothing) if the frames differ too much (or in size) for it to be worth it.
+/// Frames are rows of `width` cells (see [pixel::VALUE_LEN]) separated by newlines, drawn from `row_offset` (1 indexed).
+fn frame_diff(
+    previous: &[u8],
+    current: &[u8],
+    width: usize,
+    row_offset: usize,
+    diff: &mut Vec<u8>,
+) -> bool {
+    if previous.len() != current.len() {
+        return false;
+    }
+
+    let row_len = width * pixel::VALUE_LEN + 1;
+    let cell = |row: &[u8], col: usize| -> [u8; pixel::VALUE_LEN] {
+        row[col * pixel::VALUE_LEN..(col + 1) * pixel::VALUE_LEN]
+            .try_into()
+            .unwrap()
+    };
+    let rows = || previous.chunks(row_len).zip(current.chunks(row_len));
+
+    let changed = rows()
+        .map(|(previous, current)| {
+            (0..width)
+                .filter(|col| cell(previous, *col) != cell(current, *col))
+                .count()
+        })
+        .sum::<usize>();
+    let cells = (current.len() + 1) / row_len * width;
+
+    if changed as f64 > cells as f64 * REDRAW_THRESHOLD {
+        return false;
+    }
+
+    for (row, (previous, current)) in rows().enumerate() {
+        let mut col = 0;
+
+        while col < width {
+            if cell(previous, col) == cell(current, col) {
+                col += 1;
+                continue;
+            }
+
+            // Extend run until the gap of unchanged cells gets too long.
+            let start = col;
+            let mut end = col + 1;
+            col += 1;
+
+            while col < width && col - end <= RUN_GAP_MAX {
+                if cell(previous, col) != cell(current, col) {
+                    end = col + 1;
+                }
+                col += 1;
+            }
+
+            write!(diff, "\x1B[{};{}H", row + row_offset, start + 1).unwrap();
+            diff.extend_from_slice(&current[start * pixel::VALUE_LEN..end * pixel::VALUE_LEN]);
+        }
+    }
+
+    true
 }
 
 /// This implementation can be seen as being the pipeline stages for the renderer, in the order of definitions.
 impl Terminal {
-    pub fn clear_screen(&self) {
-        print!("\x1B[2J");
+    pub fn clear_screen(&mut self) {
+        if self.sink.is_none() {
+            print!("\x1B[2J");
+        }
+        self.redraw = true;
+    }
+
+    /// Text written after the canvas every frame, e.g. title and status lines.
+    /// It is written as is, so it has to position (and style) itself with escape sequences.
+    pub fn set_hud(&mut self, hud: String) {
+        self.hud = hud;
+    }
+
+    /// Write the whole next frame, instead of only what changed since the previous one.
+    /// Needed if something else has drawn over the canvas, or changed how it is displayed.
+    pub fn redraw(&mut self) {
+        self.redraw = true;
     }
 
     pub fn set_extras(&mut self, extras: TerminalExtras) {
+        if extras.invert_colors != self.extras.invert_colors {
+            // Unchanged cells would otherwise keep the previous colors.
+            self.redraw = true;
+        }
         self.extras = extras;
     }
 
@@ -208,6 +653,15 @@ impl Terminal {
         &self.extras
     }
 
+    /// Timings of the pipeline stages of recently rendered frames.
+    pub fn profiler(&self) -> &Profiler {
+        &self.profiler
+    }
+
+    pub fn profiler_mut(&mut self) -> &mut Profiler {
+        &mut self.profiler
+    }
+
     /// Check all members of given [Camera].
     /// Unchecked fields shall simply have a comment.
     fn check_config_camera(camera: &mut Camera) -> Result<(), &'static str> {
@@ -237,132 +691,161 @@ impl Terminal {
         Ok(())
     }
 
-    /// Clear the canvas buffer and the terminal screen.
+    /// Clear the canvas buffer.
     fn clear(&mut self) {
         self.canvas.buffer.clear();
-        print!("\x1B[2H"); // Move to row 1 (zero indexed).
     }
 
     fn render_pixel(
-        buffer: &mut TerminalBuffer,
+        band: &mut Band,
         camera: &Camera,
         x: isize,
         y: f64,
         z: isize,
         polygon_border: bool,
+        id: Id,
     ) {
-        // Extract and adjust position based on camera resolution.
-        let x = x + (camera.resolution.0 / 2) as isize;
-        let mut z = z + (camera.resolution.1 / 2) as isize;
+        let row = row_at(camera, z);
 
-        // (Some z-axis gymnastics below due to terminal characters always taking two slots in the vertical/z-axis.)
-        let mut character = pixel::Char::at(z as usize);
-        z = z / 2;
-        let pixel = buffer.pixel_mut(
-            camera.resolution.1 as usize / 2 - z as usize - 1,
-            x as usize,
-        ); // TODO: Ugly fix for the buffer being upside down.
+        if !band.contains(row) {
+            // Drawn by another band.
+            return;
+        }
 
-        // Update depth.
-        let current_depth;
-        let current_depth_flag; // TODO: Instead of using the flag, implemented a better interface (hide the flag) but use a getter that returns an Option if flag is set or not.
+        // Extract and adjust position based on camera resolution.
+        let x = x + (camera.resolution.0 / 2) as isize;
+        let z = z + (camera.resolution.1 / 2) as isize;
 
-        match character {
-            pixel::Char::Upper => {
-                current_depth = &mut pixel.meta.depth.0;
-                current_depth_flag = &mut pixel.meta.depth_flag.0;
-            }
-            pixel::Char::Lower => {
-                current_depth = &mut pixel.meta.depth.1;
-                current_depth_flag = &mut pixel.meta.depth_flag.1;
-            }
-            _ => unreachable!(),
-        }
+        let (row, x) = (row, x as usize);
+        let half = Half::at(z as usize);
 
         if polygon_border {
             // Store depth temporarily for later processing.
-            match character {
-                pixel::Char::Upper => {
-                    *pixel.meta.polygon_border.0 = y;
-                    *pixel.meta.polygon_border_flag.0 = true;
-                }
-                pixel::Char::Lower => {
-                    *pixel.meta.polygon_border.1 = y;
-                    *pixel.meta.polygon_border_flag.1 = true;
-                }
-                _ => unreachable!(),
-            }
+            band.set_border_depth(row, x, half, y);
         }
 
-        if **current_depth_flag {
-            if **current_depth > y {
-                **current_depth = y;
-            } else {
-                return;
-            }
-        } else {
-            **current_depth = y;
-            **current_depth_flag = true;
+        // Update depth.
+        match band.depth(row, x, half) {
+            Some(depth) if depth <= y => return,
+            _ => band.set_depth_and_id(row, x, half, y, id),
         }
 
         // Update character.
-        let pixel_value = pixel.value.get();
-
-        if pixel_value == pixel::Char::Full.value() {
+        if band.glyph_is(row, x, &Char::Full) {
             // Already filled.
             return;
-        } else if (pixel_value == pixel::Char::Upper.value() && character == pixel::Char::Lower)
-            || (pixel_value == pixel::Char::Lower.value() && character == pixel::Char::Upper)
-        {
-            character = pixel::Char::Full;
         }
 
-        pixel.value.set(character);
+        let character = if band.glyph_is(row, x, &half.opposite().char()) {
+            Char::Full
+        } else {
+            half.char()
+        };
+
+        band.set_glyph(row, x, character);
     }
 
     /// Projects vertices ([VectorRow]) onto the plane of the viewport that is the [Camera]/[Canvas].
+    /// [Object]s outside of the view are culled first, leaving their vertices unprojected ([None]), as are unused [crate::Level]s.
+    /// Large amounts of vertices are split in chunks, projected in parallel.
     fn project_vertices_on_viewport(&mut self) {
         let vertices = self.vertices.as_ref().unwrap().as_ref().borrow();
-        self.vertices_projected.fill(None);
-
-        let x_min = -((self.config.camera.resolution.0 / 2) as isize);
-        let x_max = (self.config.camera.resolution.0 / 2) as isize;
-        let z_min = -((self.config.camera.resolution.1 / 2) as isize);
-        let z_max = (self.config.camera.resolution.1 / 2) as isize;
+        let line_intersection_checker = &self.canvas.line_intersection_checker;
+        let extras = &self.extras;
+
+        // Cull objects, and pick their level of detail. Whatever is not drawn is excluded from projection.
+        let frustum = Frustum::new(&self.config.camera, extras);
+        let mut excluded = Vec::new();
+
+        for (object, bounds) in self.objects.iter() {
+            let drawn = match bounds {
+                Some(bounds) if frustum.sees(bounds) => Some(
+                    object
+                        .level(frustum.size(bounds))
+                        .map_or(&object.vertices, |level| &level.vertices),
+                ),
+                _ => None,
+            };
+            let ranges = std::iter::once((&object.vertices, &object.line_draw_order)).chain(
+                object
+                    .levels
+                    .iter()
+                    .map(|level| (&level.vertices, &level.line_draw_order)),
+            );
 
-        for (index, vertex) in vertices.iter().enumerate() {
-            if let Some((mut intersection, depth)) = (self.canvas.line_intersection_checker)(vertex)
-            {
-                // Adjust points according to width heigh pixel ratio.
-                intersection[0] = intersection[0] * self.extras.pixel_width_scaling;
-                intersection[2] = intersection[2] * self.extras.pixel_height_scaling;
-
-                // Do not store the point if it is outside of visible viewport space.
-                if !(((intersection[0] as isize) >= x_min && (intersection[0] as isize) < x_max)
-                    && ((intersection[2] as isize) >= z_min && (intersection[2] as isize) < z_max))
-                {
-                    continue;
+            for (vertices, line_draw_order) in ranges {
+                if drawn != Some(vertices) {
+                    excluded.push((vertices.clone(), line_draw_order.clone()));
                 }
+            }
+        }
 
-                // Store depth information.
-                intersection[1] = depth;
+        let line_draw_order_len = self
+            .line_draw_order
+            .as_ref()
+            .map_or(0, |order| order.borrow().len());
+        self.line_draw_order_visible = ranges_except(
+            line_draw_order_len,
+            excluded
+                .iter()
+                .map(|(_, line_draw_order)| line_draw_order.clone()),
+        );
 
-                self.vertices_projected[index] = Some(intersection);
-            }
+        let visible = ranges_except(
+            vertices.len().min(self.vertices_projected.len()),
+            excluded.iter().map(|(vertices, _)| vertices.clone()),
+        );
+
+        let camera = &self.config.camera;
+        let project =
+            |vertex| project_vertex(camera, extras, line_intersection_checker.as_ref(), vertex);
+
+        let chunk_len = visible
+            .iter()
+            .map(|range| range.len())
+            .sum::<usize>()
+            .div_ceil(extras.threads.max(1))
+            .max(PROJECTION_CHUNK_MIN);
+
+        // Pair up chunks of visible vertices with where to store them, and clear the rest.
+        let mut chunks = Vec::new();
+        let mut vertices_projected = self.vertices_projected.as_mut_slice();
+        let mut offset = 0;
+
+        for range in visible {
+            let (culled, rest) = vertices_projected.split_at_mut(range.start - offset);
+            let (projected, rest) = rest.split_at_mut(range.len());
+            culled.fill(None);
+            chunks.extend(
+                vertices[range.clone()]
+                    .chunks(chunk_len)
+                    .zip(projected.chunks_mut(chunk_len)),
+            );
+            vertices_projected = rest;
+            offset = range.end;
         }
+
+        vertices_projected.fill(None);
+
+        parallel(chunks, |(vertices, vertices_projected)| {
+            for (vertex, projected) in vertices.iter().zip(vertices_projected) {
+                *projected = project(vertex);
+            }
+        });
     }
 
-    /// Maps projected vertices to a [Canvas::buffer].
-    fn render_projected_vertices(&mut self) {
-        for vertex in self.vertices_projected.iter() {
+    /// Maps projected vertices to a [Band] of the [Canvas::buffer].
+    fn render_projected_vertices(band: &mut Band, scene: &Scene) {
+        for (index, vertex) in scene.vertices_projected.iter().enumerate() {
             if let Some(vertex) = vertex {
                 Self::render_pixel(
-                    &mut self.canvas.buffer,
-                    &self.config.camera,
+                    band,
+                    scene.camera,
                     vertex[0] as isize,
                     vertex[1],
                     vertex[2] as isize,
                     false,
+                    Id::vertex(index),
                 );
             }
         }
@@ -373,119 +856,90 @@ impl Terminal {
     /// - Wireframe lines (lines between vertices of a face).
     /// - Filled polygons (Technical detail: Will utilize depth buffer fully).
     /// Note: This method is doing a bit too much. Might need refactoring soon.
-    fn render_entities(&mut self, culling: bool, polyfill: bool) {
+    ///
+    /// Only the rows of the given [Band] are drawn.
+    fn render_entities(band: &mut Band, scene: &Scene, culling: bool, polyfill: bool) {
         if polyfill {
             assert!(culling);
         }
 
-        #[inline]
-        fn interpolate_depth(start: f64, end: f64, steps_max: f64, step_current: f64) -> f64 {
-            let div = step_current / steps_max;
-            (1.0 - div) * start + div * end
-        }
-
+        /// Draws the lines of the `face`th line draw order.
         fn render_lines(
+            face: usize,
             order: &[usize],
-            vertices_projected: &Vec<Option<VectorRow<f64, 3>>>,
-            buffer: &mut TerminalBuffer,
+            vertices_projected: &[Option<VectorRow<f64, 3>>],
+            band: &mut Band,
             camera: &Camera,
             polygon_border: bool,
         ) {
+            /// Pixels are identified by the nearer end of the line.
             #[inline]
             fn render_line(
-                buffer: &mut TerminalBuffer,
+                band: &mut Band,
                 camera: &Camera,
                 polygon_border: bool,
-                a: &VectorRow<f64, 3>,
-                b: &VectorRow<f64, 3>,
+                a: (&VectorRow<f64, 3>, Id),
+                b: (&VectorRow<f64, 3>, Id),
             ) {
-                let mut x0 = a[0] as isize;
-                let x1 = b[0] as isize;
-                let mut z0 = a[2] as isize;
-                let z1 = b[2] as isize;
-
-                let dx = (x1 - x0).abs();
-                let dz = (z1 - z0).abs();
-
-                let sx = (x1 - x0).signum();
-                let sz = (z1 - z0).signum();
-
-                let mut err = dx - dz;
-                let steps_max = (b[0] - a[0]).abs() + (b[2] - a[2]).abs();
-                let mut steps_taken = 0;
-
-                while x0 != x1 || z0 != z1 {
-                    Terminal::render_pixel(
-                        buffer,
-                        camera,
-                        x0,
-                        interpolate_depth(a[1], b[1], steps_max, steps_taken as f64),
-                        z0,
-                        polygon_border,
-                    );
-
-                    let e2 = 2 * err;
-
-                    if e2 > -dz {
-                        err = err - dz;
-                        x0 = x0 + sx;
-                    }
-
-                    if e2 < dx {
-                        err = err + dx;
-                        z0 = z0 + sz;
-                    }
-
-                    steps_taken += 1;
-                }
+                line(band, camera, a.0, b.0, |band, x, y, z| {
+                    let distance = |vertex: &VectorRow<f64, 3>| {
+                        (vertex[0] - x as f64).abs() + (vertex[2] - z as f64).abs()
+                    };
+                    let id = match distance(a.0) <= distance(b.0) {
+                        true => a.1,
+                        false => b.1,
+                    };
+                    Terminal::render_pixel(band, camera, x, y, z, polygon_border, id)
+                });
             }
 
+            let vertex = |index: usize| {
+                vertices_projected[index]
+                    .as_ref()
+                    .map(|vertex| (vertex, Id::face(face, index)))
+            };
+
             for ab in order.windows(2) {
-                if let (Some(a), Some(b)) = (&vertices_projected[ab[0]], &vertices_projected[ab[1]])
-                {
-                    render_line(buffer, camera, polygon_border, a, b);
+                if let (Some(a), Some(b)) = (vertex(ab[0]), vertex(ab[1])) {
+                    render_line(band, camera, polygon_border, a, b);
                 }
             }
 
             if order.len() > 2 {
                 // Close the polygon by drawing line between start and end vertices.
-                if let (Some(a), Some(b)) = (
-                    &vertices_projected[order[order.len() - 1]],
-                    &vertices_projected[order[0]],
-                ) {
-                    render_line(buffer, camera, polygon_border, a, b);
+                if let (Some(a), Some(b)) = (vertex(order[order.len() - 1]), vertex(order[0])) {
+                    render_line(band, camera, polygon_border, a, b);
                 }
             }
         }
 
-        let line_draw_order = self.line_draw_order.as_ref().unwrap().as_ref().borrow();
+        let line_draw_order = scene.line_draw_order.unwrap();
+        let line_draw_order = scene
+            .line_draw_order_visible
+            .iter()
+            .flat_map(|range| range.clone().map(|face| (face, &line_draw_order[face])));
 
-        'outer: for order in line_draw_order.iter() {
+        'outer: for (face, order) in line_draw_order {
             if let RenderOption::WireFrameAndParticles
             | RenderOption::CullingAndParticles
-            | RenderOption::PolyfillAndCullingAndParticles = self.config.option
+            | RenderOption::PolyfillAndCullingAndParticles = scene.option
             {
                 if order.len() == 1 {
                     // Render as single point particle.
-                    if let Some(particle) = &self.vertices_projected[order[0]] {
+                    if let Some(particle) = &scene.vertices_projected[order[0]] {
                         Self::render_pixel(
-                            &mut self.canvas.buffer,
-                            &self.config.camera,
+                            band,
+                            scene.camera,
                             particle[0] as isize,
                             particle[1],
                             particle[2] as isize,
                             false,
+                            Id::face(face, order[0]),
                         );
                     }
                     continue;
                 } else if order.len() == 2 {
-                    render_lines(
-                        order,
-                        &self.vertices_projected,
-                        &mut self.canvas.buffer,
-                        &self.config.camera,
-                        false,
-                    );
+                    render_lines(face, order, scene.vertices_projected, band, scene.camera, false);
                     continue;
                 }
             }
@@ -499,7 +953,7 @@ impl Terminal {
                 let mut bottom_right: Option<(usize, &VectorRow<f64, 3>)> = None;
 
                 for index in 0..order.len() {
-                    if let Some(vertex) = &self.vertices_projected[order[index]].as_ref() {
+                    if let Some(vertex) = &scene.vertices_projected[order[index]].as_ref() {
                         if bottom_right.is_none() {
                             bottom_right = Some((index, vertex));
                             continue;
@@ -521,14 +975,14 @@ impl Terminal {
                 let next_index = (bottom_right.0 + 1) % order.len();
                 let prev_index = bottom_right.0.checked_sub(1).unwrap_or(order.len() - 1);
 
-                let current_to_next: VectorRow<f64, 3> = (&self.vertices_projected
+                let current_to_next: VectorRow<f64, 3> = (&scene.vertices_projected
                     [order[next_index]]
                     .as_ref()
                     .unwrap()
                     .0
                     - &bottom_right.1 .0)
                     .into();
-                let current_to_prev: VectorRow<f64, 3> = (&self.vertices_projected
+                let current_to_prev: VectorRow<f64, 3> = (&scene.vertices_projected
                     [order[prev_index]]
                     .as_ref()
                     .unwrap()
@@ -541,21 +995,16 @@ impl Terminal {
                     continue;
                 }
 
-                render_lines(
-                    &order,
-                    &self.vertices_projected,
-                    &mut self.canvas.buffer,
-                    &self.config.camera,
-                    true,
-                );
+                render_lines(face, order, scene.vertices_projected, band, scene.camera, true);
 
                 if polyfill {
                     // Save some performance by only doing polyfill if face was not culled.
                     // Filter out only relevant vertices.
                     let vertices = order
                         .iter()
-                        .filter_map(|&index| self.vertices_projected[index].as_ref())
+                        .filter_map(|&index| scene.vertices_projected[index].as_ref())
                         .collect::<Vec<&VectorRow<f64, 3>>>();
+                    let id = Id::fill(face);
 
                     // Generate bounds/ranges.
                     let iter_x = vertices.iter().map(|vertex| vertex[0] as isize);
@@ -568,192 +1017,328 @@ impl Terminal {
 
                     // Scan from "bottom-left" to "top-right" and fill polygon.
                     for z in (start_z..=end_z).step_by(2) {
-                        let mut start_upper: Option<isize> = None;
-                        let mut start_lower: Option<isize> = None;
+                        if !band.contains(row_at(scene.camera, z)) {
+                            // Filled by another band.
+                            continue;
+                        }
+
+                        let mut starts: [Option<isize>; 2] = [None, None];
 
                         for x in start_x..=end_x {
                             // Extract and adjust position based on camera resolution.
-                            let x = x + (self.config.camera.resolution.0 / 2) as isize;
-                            let z = (z + (self.config.camera.resolution.1 / 2) as isize) / 2;
-                            let z = self.config.camera.resolution.1 as usize / 2 - z as usize - 1;
-                            let polygon_border =
-                                &self.canvas.buffer.pixel(z, x as usize).meta.polygon_border;
-                            let polygon_border_flag = &self
-                                .canvas
-                                .buffer
-                                .pixel(z, x as usize)
-                                .meta
-                                .polygon_border_flag;
-                            let polygon_border =
-                                (polygon_border.0.clone(), polygon_border.1.clone());
-                            let polygon_border_flag =
-                                (polygon_border_flag.0.clone(), polygon_border_flag.1.clone());
-
-                            if polygon_border_flag.0 {
-                                let depth_end = polygon_border.0;
-
-                                if let Some(start) = start_upper.as_mut() {
-                                    let depth_start = self
-                                        .canvas
-                                        .buffer
-                                        .pixel(z, *start as usize)
-                                        .meta
-                                        .polygon_border
-                                        .0
-                                        .clone();
-                                    let steps_max = (x - *start) as f64;
+                            let x = x + (scene.camera.resolution.0 / 2) as isize;
+                            let z = (z + (scene.camera.resolution.1 / 2) as isize) / 2;
+                            let z = scene.camera.resolution.1 as usize / 2 - z as usize - 1;
+
+                            for half in [Half::Upper, Half::Lower] {
+                                let Some(depth_end) = band.border_depth(z, x as usize, half) else {
+                                    continue;
+                                };
+
+                                if let Some(start) = starts[half as usize] {
+                                    let depth_start =
+                                        band.border_depth(z, start as usize, half).unwrap();
+                                    let steps_max = (x - start) as f64;
                                     let mut steps_taken = 1; // Start at 1, because we skip first.
 
-                                    for step in (*start..x).skip(1) {
+                                    for step in (start..x).skip(1) {
+                                        let step = step as usize;
                                         let depth_new = interpolate_depth(
                                             depth_start,
                                             depth_end,
                                             steps_max,
                                             steps_taken as f64,
                                         );
-                                        let pixel = self.canvas.buffer.pixel_mut(z, step as usize);
-
-                                        if *pixel.meta.depth_flag.0 {
-                                            let depth_old = pixel.meta.depth.0.clone();
-
-                                            if !*pixel.meta.polygon_border_flag.0
-                                                && depth_old > depth_new
-                                            {
-                                                // Fill with empty space.
-                                                if pixel.value.get() == pixel::Char::Upper.value() {
-                                                    pixel.value.set(pixel::Char::Empty);
-                                                } else if pixel.value.get()
-                                                    == pixel::Char::Full.value()
-                                                {
-                                                    pixel.value.set(pixel::Char::Lower);
-                                                }
 
-                                                *pixel.meta.depth.0 = depth_new;
-                                                *pixel.meta.depth_flag.0 = true;
-                                            }
-                                        } else {
-                                            *pixel.meta.depth.0 = depth_new;
-                                            *pixel.meta.depth_flag.0 = true;
-                                        }
-
-                                        // Fill pixel instead of leaving empty.
-                                        // if pixel.value.get() == pixel::Char::Empty.value() {
-                                        //     pixel.value.set(pixel::Char::Upper);
-                                        // } else if pixel.value.get() == pixel::Char::Lower.value() {
-                                        //     pixel.value.set(pixel::Char::Full);
-                                        // }
-
-                                        steps_taken += 1;
-                                    }
-                                }
-
-                                start_upper = Some(x);
-                            }
-
-                            if polygon_border_flag.1 {
-                                let depth_end = polygon_border.1;
-
-                                if let Some(start) = start_lower.as_mut() {
-                                    let depth_start = self
-                                        .canvas
-                                        .buffer
-                                        .pixel(z, *start as usize)
-                                        .meta
-                                        .polygon_border
-                                        .1
-                                        .clone();
-                                    let steps_max = (x - *start) as f64;
-                                    let mut steps_taken = 1; // Start at 1, because we skip first.
-
-                                    for step in (*start..x).skip(1) {
-                                        let depth_new = interpolate_depth(
-                                            depth_start,
-                                            depth_end,
-                                            steps_max,
-                                            steps_taken as f64,
-                                        );
-                                        let pixel = self.canvas.buffer.pixel_mut(z, step as usize);
-
-                                        if *pixel.meta.depth_flag.1 {
-                                            let depth_old = pixel.meta.depth.1.clone();
-
-                                            if !*pixel.meta.polygon_border_flag.1
-                                                && depth_old > depth_new
-                                            {
-                                                // Fill with empty space.
-                                                if pixel.value.get() == pixel::Char::Lower.value() {
-                                                    pixel.value.set(pixel::Char::Empty);
-                                                } else if pixel.value.get()
-                                                    == pixel::Char::Full.value()
+                                        match band.depth(z, step, half) {
+                                            Some(depth_old) => {
+                                                if band.border_depth(z, step, half).is_none()
+                                                    && depth_old > depth_new
                                                 {
-                                                    pixel.value.set(pixel::Char::Upper);
+                                                    // Fill with empty space.
+                                                    if band.glyph_is(z, step, &half.char()) {
+                                                        band.set_glyph(z, step, Char::Empty);
+                                                    } else if band.glyph_is(z, step, &Char::Full) {
+                                                        band.set_glyph(
+                                                            z,
+                                                            step,
+                                                            half.opposite().char(),
+                                                        );
+                                                    }
+
+                                                    band.set_depth_and_id(
+                                                        z,
+                                                        step,
+                                                        half,
+                                                        depth_new,
+                                                        id,
+                                                    );
                                                 }
-
-                                                *pixel.meta.depth.1 = depth_new;
-                                                *pixel.meta.depth_flag.1 = true;
                                             }
-                                        } else {
-                                            *pixel.meta.depth.1 = depth_new;
-                                            *pixel.meta.depth_flag.1 = true;
+                                            None => band.set_depth_and_id(
+                                                z,
+                                                step,
+                                                half,
+                                                depth_new,
+                                                id,
+                                            ),
                                         }
 
-                                        // Fill pixel instead of leaving empty.
-                                        // if pixel.value.get() == pixel::Char::Empty.value() {
-                                        //     pixel.value.set(pixel::Char::Lower);
-                                        // } else if pixel.value.get() == pixel::Char::Upper.value() {
-                                        //     pixel.value.set(pixel::Char::Full);
-                                        // }
-
                                         steps_taken += 1;
                                     }
                                 }
 
-                                start_lower = Some(x);
+                                starts[half as usize] = Some(x);
                             }
                         }
 
                         // Clear border flags.
                         for x in start_x..=end_x {
                             // Extract and adjust position based on camera resolution.
-                            let x = x + (self.config.camera.resolution.0 / 2) as isize;
-                            let z = (z + (self.config.camera.resolution.1 / 2) as isize) / 2;
-                            let z = self.config.camera.resolution.1 as usize / 2 - z as usize - 1;
-                            let flags = &mut self
-                                .canvas
-                                .buffer
-                                .pixel_mut(z, x as usize)
-                                .meta
-                                .polygon_border_flag;
-                            *flags.0 = false;
-                            *flags.1 = false;
+                            let x = x + (scene.camera.resolution.0 / 2) as isize;
+                            let z = (z + (scene.camera.resolution.1 / 2) as isize) / 2;
+                            let z = scene.camera.resolution.1 as usize / 2 - z as usize - 1;
+                            band.clear_border(z, x as usize);
                         }
                     }
                 }
             } else {
-                render_lines(
-                    order,
-                    &self.vertices_projected,
-                    &mut self.canvas.buffer,
-                    &self.config.camera,
-                    false,
-                );
+                render_lines(face, order, scene.vertices_projected, band, scene.camera, false);
+            }
+        }
+    }
+
+    /// Projects the lines of the [TerminalExtras::grid] around the viewpoint, as segments with their opacity.
+    fn project_grid(&mut self) {
+        self.grid_projected.clear();
+
+        let Some(grid) = self.extras.grid.as_ref() else {
+            return;
+        };
+
+        let camera = &self.config.camera;
+        let eye = Frustum::new(camera, &self.extras).eye();
+
+        for points in grid.lines(&eye) {
+            // Points that have faded out are not projected, nor drawn to.
+            let projected = points
+                .iter()
+                .map(|(point, opacity)| {
+                    let checker = self.canvas.line_intersection_checker.as_ref();
+                    let projected = (*opacity > 0.0)
+                        .then(|| project_vertex(camera, &self.extras, checker, point))
+                        .flatten();
+                    (projected, *opacity)
+                })
+                .collect::<Vec<_>>();
+
+            for ab in projected.windows(2) {
+                if let [(Some(a), opacity_a), (Some(b), opacity_b)] = ab {
+                    let opacity = (opacity_a + opacity_b) / 2.0;
+                    self.grid_projected.push((a.clone(), b.clone(), opacity));
+                }
             }
         }
     }
 
-    /// Print canvas buffer to terminal.
+    /// Draws the projected [TerminalExtras::grid] through the depth buffer, after the model, so that the model
+    /// hides it. Faded parts are dithered: the fainter, the fewer pixels are drawn.
+    fn render_grid(band: &mut Band, scene: &Scene) {
+        for (a, b, opacity) in scene.grid_projected.iter() {
+            line(band, scene.camera, a, b, |band, x, y, z| {
+                if dither(*opacity, x, z) {
+                    Self::render_pixel(band, scene.camera, x, y, z, false, Id::NONE);
+                }
+            });
+        }
+    }
+
+    /// Marks the [TerminalExtras::selection] over everything else, where it is in view.
+    fn render_selection(band: &mut Band, scene: &Scene) {
+        let selection = &scene.extras.selection;
+        let camera = scene.camera;
+        let mark = |band: &mut Band, x: isize, z: isize, c: char| {
+            let row = row_at(camera, z);
+            let col = x + (camera.resolution.0 / 2) as isize;
+
+            if band.contains(row) && (0..camera.resolution.0 as isize).contains(&col) {
+                band.set_glyph(row, col as usize, Char::Custom(c));
+            }
+        };
+        let projected = |index: usize| scene.vertices_projected.get(index)?.as_ref();
+
+        let order = selection
+            .face
+            .and_then(|face| scene.line_draw_order?.get(face))
+            .map_or(&[][..], |order| order.as_slice());
+        let edges = order.iter().zip(order.iter().cycle().skip(1));
+
+        // Closed, unless a line.
+        let closing = (order.len() > 2) as usize;
+        for (a, b) in edges.take((order.len() + closing).saturating_sub(1)) {
+            if let (Some(a), Some(b)) = (projected(*a), projected(*b)) {
+                line(band, camera, a, b, |band, x, _, z| mark(band, x, z, SELECTION_EDGE));
+            }
+        }
+
+        for vertex in selection.vertices.iter().filter_map(|&index| projected(index)) {
+            mark(band, vertex[0] as isize, vertex[2] as isize, SELECTION_VERTEX);
+        }
+    }
+
+    /// Draws the world axes as seen by the camera in the bottom right corner, over everything else.
+    /// The end of each axis is labeled (see [GIZMO_LABELS]).
+    fn render_gizmo(band: &mut Band, scene: &Scene) {
+        // Labels are drawn this far out, relative to the length of the axes.
+        const LABEL_SCALE: f64 = 1.25;
+
+        let camera = scene.camera;
+        let (width, height) = (camera.resolution.0 as isize, camera.resolution.1 as isize);
+        let radius = [
+            GIZMO_RADIUS * scene.extras.pixel_width_scaling,
+            GIZMO_RADIUS * scene.extras.pixel_height_scaling,
+        ];
+
+        // Room for the axes and the labels past their ends, from the center.
+        let extent = radius.map(|radius| (LABEL_SCALE * radius).ceil() as isize + 1);
+
+        if width < extent[0] * 4 || height < extent[1] * 4 {
+            return;
+        }
+
+        let center = [width / 2 - extent[0] - 1, -(height / 2) + extent[1] + 1];
+
+        // Clear the corner, so that the axes are not hidden by (or mixed up with) the model.
+        for z in center[1] - extent[1]..=center[1] + extent[1] {
+            let row = row_at(camera, z);
+
+            if band.contains(row) {
+                for x in center[0] - extent[0]..=center[0] + extent[0] {
+                    band.clear_cell(row, (x + width / 2) as usize);
+                }
+            }
+        }
+
+        // World axes in camera space.
+        let axes = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]].map(|axis| {
+            rotate(&VectorRow::from(axis), &camera.rotation.1, &camera.rotation.0)
+        });
+        let origin = VectorRow::from([center[0] as f64, 0.0, center[1] as f64]);
+        let at = |axis: &VectorRow<f64, 3>, scale: f64| {
+            VectorRow::from([
+                origin[0] + axis[0] * radius[0] * scale,
+                axis[1] * GIZMO_RADIUS * scale,
+                origin[2] + axis[2] * radius[1] * scale,
+            ])
+        };
+
+        for axis in axes.iter() {
+            line(band, camera, &origin, &at(axis, 1.0), |band, x, y, z| {
+                Self::render_pixel(band, camera, x, y, z, false, Id::NONE)
+            });
+        }
+
+        // Labels past the ends, the nearest last (on top).
+        let mut labels = axes
+            .iter()
+            .zip(GIZMO_LABELS)
+            .map(|(axis, label)| (at(axis, LABEL_SCALE), label))
+            .collect::<Vec<_>>();
+        labels.sort_by(|a, b| b.0[1].total_cmp(&a.0[1]));
+
+        for (position, label) in labels {
+            let row = row_at(camera, position[2].round() as isize);
+            let col = position[0].round() as isize + width / 2;
+
+            if band.contains(row) && (0..width).contains(&col) {
+                band.set_glyph(row, col as usize, Char::Custom(label));
+            }
+        }
+    }
+
+    /// What of the scene is drawn at a cell of the terminal, given as reported by the mouse (1-indexed), if anything.
+    /// Of the two pixels in a cell, the nearer one is picked.
+    pub fn pick(&self, col: u32, row: u32) -> Option<Pick> {
+        // The canvas is written from the second row (see [Self::write_rendered_scene_to_stdout]).
+        let (row, col) = ((row as usize).checked_sub(2)?, (col as usize).checked_sub(1)?);
+        let id = self.canvas.buffer.id(row, col)?;
+        let mut face = (id.face != Id::NONE.face).then_some(id.face as usize);
+
+        // Filled pixels only know their face, the vertex is looked up now rather than for each pixel.
+        let mut vertex = match id.vertex != Id::NONE.vertex {
+            true => id.vertex as usize,
+            false => {
+                let (width, height) = self.config.camera.resolution;
+                let x = col as f64 - (width / 2) as f64;
+                let z = (height / 2) as f64 - 2.0 * row as f64 - 1.0;
+                let line_draw_order = self.line_draw_order.as_ref()?.borrow();
+
+                nearest_vertex(line_draw_order.get(face?)?, &self.vertices_projected, x, z)?
+            }
+        };
+
+        // A level of detail is drawn in place of its object, but it is the object that is picked.
+        let level = self
+            .objects
+            .iter()
+            .flat_map(|(object, _)| object.levels.iter())
+            .find(|level| level.vertices.contains(&vertex));
+        if let Some(level) = level {
+            vertex = level.vertex_sources[vertex - level.vertices.start];
+            face = face
+                .map(|face| level.line_draw_order_sources[face - level.line_draw_order.start]);
+        }
+
+        Some(Pick { face, vertex })
+    }
+
+    /// Print canvas buffer, and HUD, to terminal (or the sink) in a single write. Only the cells that changed
+    /// since the previous frame are written, unless most of them did, or a full redraw is needed.
     fn write_rendered_scene_to_stdout(&mut self) {
-        std::io::stdout()
-            .write_all(
-                &self
-                    .canvas
-                    .buffer
-                    .chars()
-                    .iter()
-                    .collect::<String>()
-                    .as_bytes(),
-            )
-            .expect("Failed to write to stdout");
+        let bytes = self.canvas.buffer.bytes();
+        self.frame.clear();
+
+        if self.extras.synchronized_output {
+            self.frame.extend_from_slice(b"\x1B[?2026h"); // Begin synchronized update.
+        }
+
+        if self.extras.invert_colors {
+            self.frame.extend_from_slice(b"\x1B[7m");
+        }
+
+        let diffed = !self.redraw
+            && frame_diff(
+                &self.frame_previous,
+                bytes,
+                self.config.camera.resolution.0 as usize,
+                2, // Row 1 (zero indexed).
+                &mut self.frame,
+            );
+
+        if !diffed {
+            self.frame.extend_from_slice(b"\x1B[2H"); // Move to row 1 (zero indexed).
+            self.frame.extend_from_slice(bytes);
+        }
+
+        self.frame.extend_from_slice(b"\x1B[0m");
+        self.frame.extend_from_slice(self.hud.as_bytes());
+
+        if self.extras.synchronized_output {
+            self.frame.extend_from_slice(b"\x1B[?2026l"); // End synchronized update.
+        }
+
+        match self.sink.as_mut() {
+            Some(sink) => sink.write_all(&self.frame).and_then(|_| sink.flush()),
+            None => {
+                let mut stdout = std::io::stdout().lock();
+                stdout.write_all(&self.frame).and_then(|_| stdout.flush())
+            }
+        }
+        .expect("Failed to write frame");
+
+        self.frame_previous.clear();
+        self.frame_previous.extend_from_slice(bytes);
+        self.redraw = false;
     }
 }
 
@@ -764,6 +1349,9 @@ impl RendererTrait for Terminal {
 
     fn set_camera(mut self, mut camera: Camera) -> Result<Self, &'static str> {
         Terminal::check_config_camera(&mut camera)?;
+        if camera.resolution != self.config.camera.resolution {
+            self.redraw = true;
+        }
         self.config.camera = camera;
         self.canvas.update(&self.config)?;
         Ok(self)
@@ -791,36 +1379,117 @@ impl RendererTrait for Terminal {
         self.line_draw_order = Some(order);
     }
 
+    fn set_objects(&mut self, objects: Vec<Object>) {
+        let vertices = self.vertices.as_ref().map(|vertices| vertices.borrow());
+        let vertices = vertices
+            .as_ref()
+            .map_or(&[][..], |vertices| vertices.as_slice());
+
+        self.objects = objects
+            .into_iter()
+            .map(|object| {
+                let range = object.vertices.start.min(vertices.len())
+                    ..object.vertices.end.min(vertices.len());
+                let bounds = Bounds::from_vertices(&vertices[range]);
+                (object, bounds)
+            })
+            .collect();
+    }
+
     fn render(&mut self) {
+        self.profiler.frame_begin();
+
+        let start = Instant::now();
         self.clear();
+        self.profiler.stage_end("clear", start);
+
+        let start = Instant::now();
         self.project_vertices_on_viewport();
+        self.profiler
+            .stage_end("project_vertices_on_viewport", start);
 
-        match self.config.option {
-            RenderOption::Vertices => self.render_projected_vertices(),
-            RenderOption::WireFrame | RenderOption::WireFrameAndParticles => {
-                self.render_entities(false, false)
-            }
-            RenderOption::Culling | RenderOption::CullingAndParticles => {
-                self.render_entities(true, false)
+        if self.extras.grid.is_some() {
+            let start = Instant::now();
+            self.project_grid();
+            self.profiler.stage_end("project_grid", start);
+        }
+
+        let line_draw_order = self.line_draw_order.as_ref().map(|order| order.borrow());
+        let scene = Scene {
+            camera: &self.config.camera,
+            option: &self.config.option,
+            vertices_projected: &self.vertices_projected,
+            line_draw_order: line_draw_order.as_ref().map(|order| order.as_slice()),
+            line_draw_order_visible: &self.line_draw_order_visible,
+            extras: &self.extras,
+            grid_projected: &self.grid_projected,
+        };
+
+        // Rasterize in horizontal bands, in parallel.
+        let rows = (self.config.camera.resolution.1 / 2) as usize;
+        let bands = self.extras.threads.min(rows / BAND_ROWS_MIN).max(1);
+
+        let start = Instant::now();
+        parallel(self.canvas.buffer.bands_mut(bands), |mut band| {
+            match scene.option {
+                RenderOption::Vertices => Self::render_projected_vertices(&mut band, &scene),
+                RenderOption::WireFrame | RenderOption::WireFrameAndParticles => {
+                    Self::render_entities(&mut band, &scene, false, false)
+                }
+                RenderOption::Culling | RenderOption::CullingAndParticles => {
+                    Self::render_entities(&mut band, &scene, true, false)
+                }
+                RenderOption::PolyfillAndCulling | RenderOption::PolyfillAndCullingAndParticles => {
+                    Self::render_entities(&mut band, &scene, true, true)
+                }
             }
-            RenderOption::PolyfillAndCulling | RenderOption::PolyfillAndCullingAndParticles => {
-                self.render_entities(true, true)
+
+            // Guides, after the model, so that depth decides what is in front.
+            Self::render_grid(&mut band, &scene);
+            Self::render_selection(&mut band, &scene);
+
+            if scene.extras.gizmo {
+                Self::render_gizmo(&mut band, &scene);
             }
-        }
+        });
+
+        self.profiler.stage_end(
+            match scene.option {
+                RenderOption::Vertices => "render_projected_vertices",
+                _ => "render_entities",
+            },
+            start,
+        );
+
+        drop(line_draw_order);
 
+        let start = Instant::now();
         self.write_rendered_scene_to_stdout();
+        self.profiler
+            .stage_end("write_rendered_scene_to_stdout", start);
     }
 }
 
 impl __RendererTrait for Terminal {
-    fn new(mut config: RendererConfiguration) -> Result<Self, &'static str> {
-        println!("\x1B[?1049h"); // Enter alternative buffer mode. I.e., do not affect previous terminal history.
+    fn new(config: RendererConfiguration) -> Result<Self, &'static str> {
+        Terminal::with_sink(config, None)
+    }
+}
 
-        let prev_hook = std::panic::take_hook();
-        std::panic::set_hook(Box::new(move |info| {
-            println!("\x1B[?1049l");
-            prev_hook(info);
-        }));
+impl Terminal {
+    fn with_sink(
+        mut config: RendererConfiguration,
+        sink: Option<Box<dyn Write>>,
+    ) -> Result<Self, &'static str> {
+        if sink.is_none() {
+            println!("\x1B[?1049h"); // Enter alternative buffer mode. I.e., do not affect previous terminal history.
+
+            let prev_hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                println!("\x1B[?1049l");
+                prev_hook(info);
+            }));
+        }
 
         Terminal::check_config_camera(&mut config.camera)?;
         Terminal::check_config_option(&mut config.option)?;
@@ -829,15 +1498,475 @@ impl __RendererTrait for Terminal {
             vertices: None,
             vertices_projected: Vec::new(),
             line_draw_order: None,
+            objects: Vec::new(),
+            line_draw_order_visible: Vec::new(),
+            grid_projected: Vec::new(),
+            frame: Vec::new(),
+            frame_previous: Vec::new(),
+            hud: String::new(),
+            redraw: true,
+            sink,
             canvas: Canvas::new(&config),
             config,
             extras: TerminalExtras::default(),
+            profiler: Profiler::new(PROFILER_HISTORY),
         })
     }
 }
 
 impl Drop for Terminal {
     fn drop(&mut self) {
-        println!("\x1B[?1049l");
+        if self.sink.is_none() {
+            println!("\x1B[?1049l");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Spaces are replaced by [pixel::Char::Empty], to keep cells the same size.
+    fn empty(s: &str) -> String {
+        s.replace(' ', &pixel::Char::Empty.value().to_string())
+    }
+
+    fn frame(rows: &[&str]) -> Vec<u8> {
+        empty(&rows.join("\n")).into_bytes()
+    }
+
+    fn diff(previous: &[u8], current: &[u8], width: usize) -> Option<String> {
+        let mut diff = Vec::new();
+        frame_diff(previous, current, width, 2, &mut diff).then(|| String::from_utf8(diff).unwrap())
+    }
+
+    #[test]
+    fn bands_match_single_band() {
+        let camera = Camera {
+            resolution: (40, 40),
+            ..Default::default()
+        };
+        let vertex = |x: f64, y: f64, z: f64| Some(VectorRow::from([x, y, z]));
+        let vertices_projected = [
+            // Square, both windings so that one survives culling.
+            vertex(-15.0, 5.0, -12.0),
+            vertex(10.0, 5.0, -14.0),
+            vertex(12.0, 8.0, 15.0),
+            vertex(-11.0, 8.0, 11.0),
+            // Line and particle, in front of the square.
+            vertex(-19.0, 1.0, 19.0),
+            vertex(19.0, 1.0, -19.0),
+            vertex(0.0, 1.0, 0.0),
+        ];
+        let line_draw_order = [vec![0, 1, 2, 3], vec![3, 2, 1, 0], vec![4, 5], vec![6]];
+
+        let line_draw_order_visible = 0..line_draw_order.len();
+        let extras = TerminalExtras {
+            gizmo: true,
+            ..Default::default()
+        };
+        // Grid line, partly behind the square.
+        let grid_projected = [(
+            VectorRow::from([-20.0, 20.0, -5.0]),
+            VectorRow::from([19.0, 2.0, 3.0]),
+            0.75,
+        )];
+
+        let render = |option: &RenderOption, bands: usize| {
+            let mut buffer = TerminalBuffer::new(&camera.resolution);
+            let scene = Scene {
+                camera: &camera,
+                option,
+                vertices_projected: &vertices_projected,
+                line_draw_order: Some(&line_draw_order),
+                line_draw_order_visible: std::slice::from_ref(&line_draw_order_visible),
+                extras: &extras,
+                grid_projected: &grid_projected,
+            };
+
+            for mut band in buffer.bands_mut(bands) {
+                match option {
+                    RenderOption::Vertices => {
+                        Terminal::render_projected_vertices(&mut band, &scene)
+                    }
+                    RenderOption::WireFrameAndParticles => {
+                        Terminal::render_entities(&mut band, &scene, false, false)
+                    }
+                    _ => Terminal::render_entities(&mut band, &scene, true, true),
+                }
+
+                Terminal::render_grid(&mut band, &scene);
+                Terminal::render_gizmo(&mut band, &scene);
+            }
+
+            buffer.bytes().to_vec()
+        };
+
+        for option in [
+            RenderOption::Vertices,
+            RenderOption::WireFrameAndParticles,
+            RenderOption::PolyfillAndCullingAndParticles,
+        ] {
+            let expected = render(&option, 1);
+            assert!(expected != TerminalBuffer::new(&camera.resolution).bytes());
+
+            for bands in 2..=7 {
+                assert!(render(&option, bands) == expected, "Bands: {bands}");
+            }
+        }
+    }
+
+    #[test]
+    fn guides() {
+        let camera = Camera {
+            resolution: (40, 40),
+            ..Default::default()
+        };
+        let vertex = |x: f64, y: f64, z: f64| Some(VectorRow::from([x, y, z]));
+        let vertices_projected = [
+            vertex(-10.0, 5.0, -10.0),
+            vertex(10.0, 5.0, -10.0),
+            vertex(10.0, 5.0, 10.0),
+            vertex(-10.0, 5.0, 10.0),
+        ];
+        let line_draw_order = [vec![0, 1, 2, 3], vec![3, 2, 1, 0]];
+        let line_draw_order_visible = 0..line_draw_order.len();
+        let extras = TerminalExtras {
+            gizmo: true,
+            ..Default::default()
+        };
+        // Grid line through, and behind, the square.
+        let grid_projected = [(
+            VectorRow::from([-19.0, 10.0, 0.0]),
+            VectorRow::from([19.0, 10.0, 0.0]),
+            1.0,
+        )];
+        let scene = Scene {
+            camera: &camera,
+            option: &RenderOption::PolyfillAndCulling,
+            vertices_projected: &vertices_projected,
+            line_draw_order: Some(&line_draw_order),
+            line_draw_order_visible: std::slice::from_ref(&line_draw_order_visible),
+            extras: &extras,
+            grid_projected: &grid_projected,
+        };
+
+        let mut buffer = TerminalBuffer::new(&camera.resolution);
+        let mut band = buffer.band_mut();
+        Terminal::render_entities(&mut band, &scene, true, true);
+        Terminal::render_grid(&mut band, &scene);
+        Terminal::render_gizmo(&mut band, &scene);
+
+        // The square hides the grid.
+        let row = row_at(&camera, 0);
+        assert!(buffer.glyph_is(row, 20 - 15, &pixel::Char::Lower));
+        assert!(buffer.glyph_is(row, 20, &pixel::Char::Empty));
+
+        // Looking along y: x to the right, z up, y (away) in the middle.
+        let s = std::str::from_utf8(buffer.bytes()).unwrap();
+        let find = |label| {
+            s.lines()
+                .enumerate()
+                .find_map(|(row, line)| Some((row, line.chars().position(|c| c == label)?)))
+                .unwrap()
+        };
+        let [x, y, z] = GIZMO_LABELS.map(find);
+        assert!(x.0 == y.0 && x.1 > y.1);
+        assert!(z.0 < y.0 && z.1 == y.1);
+    }
+
+    #[test]
+    fn gizmo_wide_pixels() {
+        let camera = Camera {
+            resolution: (80, 60),
+            ..Default::default()
+        };
+
+        for pixel_width_scaling in [1.5, 2.0] {
+            let extras = TerminalExtras {
+                gizmo: true,
+                pixel_width_scaling,
+                ..Default::default()
+            };
+            let scene = Scene {
+                camera: &camera,
+                option: &RenderOption::PolyfillAndCulling,
+                vertices_projected: &[],
+                line_draw_order: None,
+                line_draw_order_visible: &[],
+                extras: &extras,
+                grid_projected: &[],
+            };
+
+            let mut buffer = TerminalBuffer::new(&camera.resolution);
+            Terminal::render_gizmo(&mut buffer.band_mut(), &scene);
+
+            // Every label is drawn, and no row is overrun.
+            let s = std::str::from_utf8(buffer.bytes()).unwrap();
+            assert!(s.lines().all(|line| line.chars().count() == 80));
+            assert!(GIZMO_LABELS.iter().all(|label| s.contains(*label)));
+        }
+    }
+
+    #[test]
+    fn picking() {
+        let camera = Camera {
+            resolution: (40, 40),
+            ..Default::default()
+        };
+        let vertex = |x: f64, y: f64, z: f64| Some(VectorRow::from([x, y, z]));
+        let vertices_projected = [
+            vertex(-10.0, 5.0, -10.0),
+            vertex(10.0, 5.0, -10.0),
+            vertex(10.0, 5.0, 10.0),
+            vertex(-10.0, 5.0, 10.0),
+            vertex(15.0, 1.0, 15.0),
+        ];
+        let line_draw_order = [vec![0, 1, 2, 3], vec![3, 2, 1, 0], vec![4]];
+        let line_draw_order_visible = 0..line_draw_order.len();
+        let extras = TerminalExtras::default();
+        let selected = TerminalExtras {
+            selection: Selection {
+                face: Some(0),
+                vertices: vec![1],
+            },
+            ..Default::default()
+        };
+        let scene = |extras| Scene {
+            camera: &camera,
+            option: &RenderOption::PolyfillAndCullingAndParticles,
+            vertices_projected: &vertices_projected,
+            line_draw_order: Some(&line_draw_order),
+            line_draw_order_visible: std::slice::from_ref(&line_draw_order_visible),
+            extras,
+            grid_projected: &[],
+        };
+
+        let mut buffer = TerminalBuffer::new(&camera.resolution);
+        Terminal::render_entities(&mut buffer.band_mut(), &scene(&extras), true, true);
+
+        // Inside the square, near its lower left corner, and the particle.
+        let id = buffer.id(row_at(&camera, -8), 20 - 8).unwrap();
+        assert!(id.face < 2 && id.vertex == pixel::Id::NONE.vertex);
+        let order = &line_draw_order[id.face as usize];
+        assert!(nearest_vertex(order, &vertices_projected, -8.0, -8.0) == Some(0));
+        assert!(nearest_vertex(order, &vertices_projected, 9.0, 2.0) == Some(2));
+        let id = buffer.id(row_at(&camera, 15), 20 + 15).unwrap();
+        assert!(id == pixel::Id::face(2, 4));
+        assert!(buffer.id(row_at(&camera, 0), 20 + 18).is_none());
+
+        Terminal::render_selection(&mut buffer.band_mut(), &scene(&selected));
+
+        let selected_vertex = pixel::Char::Custom(SELECTION_VERTEX);
+        let selected_edge = pixel::Char::Custom(SELECTION_EDGE);
+        assert!(buffer.glyph_is(row_at(&camera, -10), 20 + 10, &selected_vertex));
+        assert!(buffer.glyph_is(row_at(&camera, -10), 20, &selected_edge));
+        assert!(buffer.glyph_is(row_at(&camera, 10), 20 - 10 + 1, &selected_edge));
+        assert!(!buffer.glyph_is(row_at(&camera, 0), 20, &selected_edge));
+    }
+
+    #[test]
+    fn ranges_except_excluded() {
+        let except = |excluded: &[(usize, usize)]| {
+            ranges_except(10, excluded.iter().map(|&(start, end)| start..end))
+                .iter()
+                .map(|range| (range.start, range.end))
+                .collect::<Vec<_>>()
+        };
+
+        assert!(except(&[]) == [(0, 10)]);
+        assert!(except(&[(6, 8), (0, 2)]) == [(2, 6), (8, 10)]);
+        assert!(except(&[(2, 5), (3, 7), (9, 20)]) == [(0, 2), (7, 9)]);
+        assert!(except(&[(0, 10)]).is_empty());
+    }
+
+    #[test]
+    fn frustum_sees_what_is_projected() {
+        let half = std::f64::consts::FRAC_PI_8;
+        let rotation = &Quaternion(half.cos(), half.sin(), 0.0, 0.0)
+            * &Quaternion(half.cos(), 0.0, 0.0, half.sin());
+
+        for view_mode in [ViewMode::FirstPerson, ViewMode::Orbital] {
+            let camera = Camera {
+                resolution: (60, 40),
+                position: VectorRow::from([10.0, -20.0, 5.0]),
+                rotation: (rotation.clone(), rotation.inverse()),
+                view_mode,
+                ..Default::default()
+            };
+            let extras = TerminalExtras {
+                pixel_width_scaling: 0.5,
+                ..Default::default()
+            };
+            let frustum = Frustum::new(&camera, &extras);
+            let checker = Canvas::new(&RendererConfiguration {
+                camera: camera.clone(),
+                ..Default::default()
+            })
+            .line_intersection_checker;
+
+            let (mut projected, mut seen) = (0, 0);
+
+            for x in (-200..=200).step_by(10) {
+                for y in (-200..=200).step_by(10) {
+                    for z in (-200..=200).step_by(10) {
+                        let vertex = VectorRow::from([x as f64, y as f64, z as f64]);
+                        let bounds = Bounds::from_vertices(std::slice::from_ref(&vertex)).unwrap();
+                        let sees = frustum.sees(&bounds);
+                        let is_projected = checker(&vertex).is_some_and(|(intersection, _)| {
+                            (intersection[0] * extras.pixel_width_scaling).abs() < 30.0
+                                && intersection[2].abs() < 20.0
+                        });
+
+                        assert!(sees || !is_projected, "{vertex:?}");
+                        projected += is_projected as usize;
+                        seen += sees as usize;
+                    }
+                }
+            }
+
+            assert!(projected > 0);
+            assert!(seen < projected * 11 / 10, "{seen} {projected}");
+        }
+    }
+
+    #[test]
+    fn frustum_culls_by_bounds() {
+        let camera = Camera {
+            resolution: (40, 40),
+            ..Default::default()
+        };
+        let frustum = Frustum::new(&camera, &TerminalExtras::default());
+        let bounds = |vertices: &[[f64; 3]]| {
+            Bounds::from_vertices(&vertices.iter().map(VectorRow::from).collect::<Vec<_>>())
+                .unwrap()
+        };
+
+        // In front, behind, and to the side.
+        assert!(frustum.sees(&bounds(&[[-5.0, 50.0, -5.0], [5.0, 60.0, 5.0]])));
+        assert!(!frustum.sees(&bounds(&[[-5.0, -500.0, -5.0], [5.0, -400.0, 5.0]])));
+        assert!(!frustum.sees(&bounds(&[[500.0, 50.0, 0.0], [600.0, 60.0, 0.0]])));
+
+        // Crossing the view.
+        assert!(frustum.sees(&bounds(&[[-1000.0, 50.0, 0.0], [1000.0, 50.0, 0.0]])));
+
+        // Projected size shrinks with distance, and is unbounded from inside.
+        let near = bounds(&[[-5.0, 30.0, 0.0], [5.0, 30.0, 0.0]]);
+        let far = bounds(&[[-5.0, 180.0, 0.0], [5.0, 180.0, 0.0]]);
+        assert!((frustum.size(&near) - 4.0).abs() < 1e-9);
+        assert!((frustum.size(&far) - 1.0).abs() < 1e-9);
+        assert!(frustum.size(&bounds(&[[0.0, -30.0, 0.0], [0.0, 30.0, 0.0]])) == f64::INFINITY);
+
+        // A bar to the side, whose sphere reaches into the view but box does not.
+        let bar = bounds(&[[60.0, 20.0, 0.0], [200.0, 21.0, 0.0]]);
+        let center = frustum.to_camera_space(&bar.center);
+        assert!(frustum
+            .normals
+            .iter()
+            .any(|normal| normal.dot(&center).abs() < bar.radius));
+        assert!(!frustum.sees(&bar));
+    }
+
+    #[test]
+    fn diff_changed_runs() {
+        let previous = frame(&["          ", "          ", "          "]);
+
+        let current = frame(&["          ", "  ▀▀      ", "         █"]);
+        assert!(diff(&previous, &current, 10) == Some(empty("\x1B[3;3H▀▀\x1B[4;10H█")));
+
+        // Short gaps are rewritten, longer ones skipped.
+        let current = frame(&["█  █   █  ", "          ", "          "]);
+        assert!(diff(&previous, &current, 10) == Some(empty("\x1B[2;1H█  █\x1B[2;8H█")));
+
+        assert!(diff(&previous, &previous, 10) == Some(String::new()));
+    }
+
+    #[test]
+    fn diff_falls_back_to_redraw() {
+        let previous = frame(&["    ", "    "]);
+        assert!(diff(&previous, &frame(&["████", "██  "]), 4).is_none());
+        assert!(diff(&previous, &frame(&["    "]), 4).is_none());
+        assert!(diff(&previous, &frame(&["█   ", "    "]), 4).is_some());
+    }
+
+    #[test]
+    fn picking_levels() {
+        // Grid of quads facing the camera, both windings so that one survives culling.
+        let size = 10;
+        let mut vertices = (0..size * size)
+            .map(|index| {
+                let (x, z) = ((index % size) as f64, (index / size) as f64);
+                VectorRow::from([(x - 4.5) * 4.0, 50.0, (z - 4.5) * 4.0])
+            })
+            .collect::<Vec<_>>();
+        let mut line_draw_order = (0..(size - 1) * (size - 1))
+            .flat_map(|cell| {
+                let index = cell % (size - 1) + cell / (size - 1) * size;
+                let face = vec![index, index + 1, index + size + 1, index + size];
+                [face.iter().rev().cloned().collect(), face]
+            })
+            .collect::<Vec<Vec<usize>>>();
+        let (vertices_len, faces_len) = (vertices.len(), line_draw_order.len());
+
+        // A level of detail always drawn in place of the object.
+        let mut object = Object {
+            vertices: 0..vertices_len,
+            line_draw_order: 0..faces_len,
+            levels: Vec::new(),
+        };
+        let details = [crate::lod::Detail {
+            ratio: 0.5,
+            size: f64::MAX,
+        }];
+        crate::lod::add_levels(&mut object, &mut vertices, &mut line_draw_order, &details);
+        assert!(object.levels.len() == 1);
+
+        let mut terminal = TerminalBuilder::default()
+            .with_sink(Box::new(std::io::sink()))
+            .build()
+            .unwrap();
+        terminal.set_vertices(Rc::new(RefCell::new(vertices)));
+        terminal.set_vertices_line_draw_order(Rc::new(RefCell::new(line_draw_order)));
+        terminal.set_objects(vec![object]);
+        terminal.render();
+
+        // The middle of the canvas, on the grid. What is picked is of the object, not the level.
+        let pick = terminal.pick(33, 18).unwrap();
+        assert!(pick.face.is_some_and(|face| face < faces_len));
+        assert!(pick.vertex < vertices_len);
+    }
+
+    #[test]
+    fn frames_written_to_sink() {
+        struct Sink(Rc<RefCell<Vec<Vec<u8>>>>);
+
+        impl Write for Sink {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.borrow_mut().push(buf.to_vec());
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let frames = Rc::new(RefCell::new(Vec::new()));
+        let mut terminal = TerminalBuilder::default()
+            .with_sink(Box::new(Sink(Rc::clone(&frames))))
+            .build()
+            .unwrap();
+        terminal.set_vertices(Rc::new(RefCell::new(vec![VectorRow::from([0.0, 0.0, 0.0])])));
+        terminal.set_vertices_line_draw_order(Rc::new(RefCell::new(vec![vec![0]])));
+        terminal.set_objects(Vec::new());
+        terminal.set_hud("hud".to_string());
+        terminal.render();
+        terminal.render();
+
+        let frames = frames.borrow();
+        let canvas = TerminalBuffer::bytes_required(&terminal.config.camera.resolution);
+        assert!(frames.len() == 2);
+        assert!(frames[0].starts_with(b"\x1B[2H") && frames[0].len() > canvas);
+        assert!(frames[1] == b"\x1B[0mhud");
     }
 }
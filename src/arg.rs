@@ -1,16 +1,206 @@
-use std::env;
+use std::{env, fmt::Display, path::PathBuf};
 
-use renderer::RenderOption;
+use renderer::{profile, RenderOption};
 
-use crate::model::Model;
+use crate::model::{
+    primitive::{Primitive, Shape},
+    Model,
+};
 
 enum Arg {
     Help,
+    Version,
     Resolution,
     RenderOption,
     Info,
     Model,
     Fps,
+    Threads,
+    Profile,
+    Trace,
+    View,
+    Edit,
+}
+
+/// Declaration of a command line option. The help section is generated from these.
+struct Definition {
+    arg: Arg,
+    short: char,
+    long: &'static str,
+    /// Other accepted long names, for backwards compatibility.
+    aliases: &'static [&'static str],
+    /// Names of the values following the option. Empty for flags.
+    values: &'static [&'static str],
+    default: &'static str,
+    description: &'static str,
+}
+
+const DEFINITIONS: [Definition; 12] = [
+    Definition {
+        arg: Arg::Help,
+        short: 'h',
+        long: "help",
+        aliases: &[],
+        values: &[],
+        default: "false",
+        description: "Print this help section.",
+    },
+    Definition {
+        arg: Arg::Version,
+        short: 'V',
+        long: "version",
+        aliases: &[],
+        values: &[],
+        default: "false",
+        description: "Print version.",
+    },
+    Definition {
+        arg: Arg::Resolution,
+        short: 'r',
+        long: "resolution",
+        aliases: &[],
+        values: &["width", "height"],
+        default: "terminal size",
+        description: "Set the resolution.",
+    },
+    Definition {
+        arg: Arg::RenderOption,
+        short: 'o',
+        long: "render-option",
+        aliases: &["option"],
+        values: &["option"],
+        default: "polyfillandcullingandparticles",
+        description: "Set what to render.",
+    },
+    Definition {
+        arg: Arg::Info,
+        short: 'i',
+        long: "info",
+        aliases: &[],
+        values: &[],
+        default: "false",
+        description: "During execution, print additional information at the bottom.\n\
+                      This includes fps, events handled, fov, etc.",
+    },
+    Definition {
+        arg: Arg::Model,
+        short: 'm',
+        long: "model",
+        aliases: &[],
+        values: &["model"],
+        default: "plane",
+        description: "What to display. Primitives are generated from parameters given after the\n\
+                      name, any left out are the defaults, e.g. \"sphere:segments=8,rings=4\".\n\
+                      The profile of a revolution is given as radius:height points from the bottom up.\n\
+                      Expressions are plotted as a surface, \"z = f(x, y); x = -1..1; y = -1..1; resolution = 32\",\n\
+                      or a curve, \"(x(t), y(t), z(t)); t = 0..2*pi; steps = 256\", settings as shown being the\n\
+                      defaults. Expressions may use + - * / ^, pi, e, and sin, cos, tan, asin, acos, atan,\n\
+                      sinh, cosh, tanh, sqrt, abs, exp, ln, log, floor, ceil.\n\
+                      Files are read as point clouds, or meshes if they have faces: \".ply\" (ASCII or binary\n\
+                      little-endian), \".xyz\" (\"x y z\" lines), and \".csv\" with the columns to use given by\n\
+                      number or header name, e.g. \"scan.csv:x=lon,y=lat,z=3\" (by default the first three).\n\
+                      Clouds with more points than the resolution has pixels are subsampled.",
+    },
+    Definition {
+        arg: Arg::Fps,
+        short: 'f',
+        long: "fps",
+        aliases: &[],
+        values: &["fps"],
+        default: "unlimited",
+        description: "Set the frames per second.",
+    },
+    Definition {
+        arg: Arg::Threads,
+        short: 't',
+        long: "threads",
+        aliases: &[],
+        values: &["count"],
+        default: "number of cores",
+        description: "Set the number of threads to render with.",
+    },
+    Definition {
+        arg: Arg::Profile,
+        short: 'p',
+        long: "profile",
+        aliases: &[],
+        values: &["name"],
+        default: "\"profile\" in config file, if any",
+        description: "Use the named profile from the configuration file\n\
+                      ($XDG_CONFIG_HOME/grpe/config.toml or ~/.config/grpe/config.toml).\n\
+                      Options given on the command line override the profile.",
+    },
+    Definition {
+        arg: Arg::Trace,
+        short: 'T',
+        long: "trace",
+        aliases: &[],
+        values: &["file"],
+        default: "none",
+        description: "Record how long each render stage takes, and write it to the file on exit.\n\
+                      \".csv\" for a table, \".json\" for Chrome trace (chrome://tracing, Perfetto).\n\
+                      Toggle an overlay of recent timings with 'p'.",
+    },
+    Definition {
+        arg: Arg::View,
+        short: 'v',
+        long: "view",
+        aliases: &[],
+        values: &["name"],
+        default: "none",
+        description: "Start from a saved view ($XDG_CONFIG_HOME/grpe/views.toml or ~/.config/grpe/views.toml).\n\
+                      Save the current view to slot 1-9 with 'b' followed by the digit, recall it with the digit.",
+    },
+    Definition {
+        arg: Arg::Edit,
+        short: 'e',
+        long: "edit",
+        aliases: &[],
+        values: &["file"],
+        default: "none",
+        description: "Edit the model, and save it to the \".obj\" file with 'W'. An existing file is edited\n\
+                      instead of the model. Levels of detail are not used.\n\
+                      Click vertices to select them (again to deselect), and move them along the view\n\
+                      with 'J', 'L', 'I', 'K', and away or toward with 'Y', 'H'. Make a face through them\n\
+                      with 'n', delete the selected face with 'X'. Undo with 'u', redo with 'U'.",
+    },
+];
+
+enum Subcommand {
+    Check,
+}
+
+/// Declaration of a command, given instead of options. The help section is generated from these,
+/// like that of the options.
+struct CommandDefinition {
+    subcommand: Subcommand,
+    name: &'static str,
+    /// Names of the arguments following the command.
+    arguments: &'static [&'static str],
+    description: &'static str,
+}
+
+const COMMANDS: [CommandDefinition; 1] = [CommandDefinition {
+    subcommand: Subcommand::Check,
+    name: "check",
+    arguments: &["model"],
+    description: "Validate the faces of the model against its vertices, and report problems by face index:\n\
+                  out of range indices, degenerate faces, duplicate vertices, inconsistent winding (which\n\
+                  breaks backface culling), non-manifold edges, and non-planar faces. Exits with 1 if any.",
+}];
+
+fn render_option_description(option: &RenderOption) -> &'static str {
+    match option {
+        RenderOption::Vertices => "Renders only vertices.",
+        RenderOption::WireFrame => "Renders only wireframe (lines between vertices).",
+        RenderOption::WireFrameAndParticles => "Wireframe + particles (single point vertices).",
+        RenderOption::Culling => "Renders wireframe, but with backface culling.",
+        RenderOption::CullingAndParticles => "Culling + particles (single point vertices).",
+        RenderOption::PolyfillAndCulling => "Fill polygons + culling.",
+        RenderOption::PolyfillAndCullingAndParticles => {
+            "Fill polygons + culling + particles (single point vertices)."
+        }
+    }
 }
 
 #[derive(Default)]
@@ -20,97 +210,523 @@ pub struct Args {
     pub info: Option<()>,
     pub model: Option<Model>,
     pub fps: Option<u64>,
+    pub threads: Option<usize>,
+    pub profile: Option<String>,
+    pub trace: Option<PathBuf>,
+    pub view: Option<String>,
+    pub edit: Option<PathBuf>,
+}
+
+pub enum Command {
+    Run(Args),
+    Help,
+    Version,
+    /// Validate the model, rather than display it.
+    Check(Model),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    UnknownOption(String),
+    MissingValue {
+        option: &'static str,
+        value: &'static str,
+    },
+    InvalidValue {
+        option: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+    UnexpectedValue(&'static str),
+    MissingArgument {
+        command: &'static str,
+        argument: &'static str,
+    },
+    InvalidArgument {
+        command: &'static str,
+        argument: String,
+        reason: &'static str,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownOption(option) => write!(f, "Unknown option \"{option}\"."),
+            Error::MissingValue { option, value } => {
+                write!(f, "Option \"--{option}\" is missing value <{value}>.")
+            }
+            Error::InvalidValue {
+                option,
+                value,
+                reason,
+            } => write!(f, "Invalid value \"{value}\" for \"--{option}\": {reason}"),
+            Error::UnexpectedValue(option) => write!(f, "Option \"--{option}\" takes no value."),
+            Error::MissingArgument { command, argument } => {
+                write!(f, "Command \"{command}\" is missing <{argument}>.")
+            }
+            Error::InvalidArgument {
+                command,
+                argument,
+                reason,
+            } => write!(
+                f,
+                "Invalid argument \"{argument}\" for \"{command}\": {reason}"
+            ),
+        }
+    }
+}
+
+/// Generate the help section from [COMMANDS] and [DEFINITIONS].
+pub fn help() -> String {
+    let usages = COMMANDS
+        .iter()
+        .map(|command| format!("{}{}", command.name, arguments(command.arguments)))
+        .collect::<Vec<_>>();
+    let mut help = String::from("GRPE Usage:\ngrpe [OPTION]\n");
+
+    for usage in usages.iter() {
+        help += &format!("grpe {usage}\n");
+    }
+
+    help += "\nCOMMANDS:\n";
+    for (command, usage) in COMMANDS.iter().zip(usages.iter()) {
+        help += &format!("{usage}\n{}\n\n", command.description);
+    }
+
+    help += "OPTIONS:\n";
+
+    for definition in DEFINITIONS.iter() {
+        let values = arguments(definition.values);
+        help += &format!(
+            "-{}{values}, --{}{values}\nDefault: {}\n{}\n",
+            definition.short, definition.long, definition.default, definition.description
+        );
+
+        match definition.arg {
+            Arg::RenderOption => {
+                help += "Available options:\n";
+                for option in RenderOption::ALL.iter() {
+                    help += &format!("{option} - {}\n", render_option_description(option));
+                }
+            }
+            Arg::Model => {
+                help += "Available models:\n";
+                for model in Model::ALL.iter() {
+                    help += &format!("{model}\n");
+                }
+                for shape in Shape::ALL {
+                    help += &format!("{}\n", Primitive::new(shape));
+                }
+            }
+            _ => (),
+        }
+
+        help += "\n";
+    }
+
+    help += "Values may also be given as \"--option=value\", comma separated if several.";
+    help
+}
+
+/// Names of values or arguments, as `" <name>"` each.
+fn arguments(names: &[&str]) -> String {
+    names.iter().map(|name| format!(" <{name}>")).collect()
+}
+
+/// Parse value of option, or describe why it is invalid.
+fn parse_value<T: std::str::FromStr>(
+    definition: &Definition,
+    value: String,
+    check: impl Fn(&T) -> Result<(), &'static str>,
+) -> Result<T, Error> {
+    let invalid = |value, reason| Error::InvalidValue {
+        option: definition.long,
+        value,
+        reason,
+    };
+
+    match value.parse() {
+        Ok(parsed) => match check(&parsed) {
+            Ok(()) => Ok(parsed),
+            Err(reason) => Err(invalid(value, reason)),
+        },
+        Err(_) => Err(invalid(value, "Could not be parsed.")),
+    }
+}
+
+fn positive(value: &u64) -> Result<(), &'static str> {
+    if *value > 0 {
+        Ok(())
+    } else {
+        Err("Must be greater than zero.")
+    }
+}
+
+/// Two pixels share a row of characters, so less would leave no rows.
+fn height(value: &u64) -> Result<(), &'static str> {
+    if *value > 1 {
+        Ok(())
+    } else {
+        Err("Must be at least 2.")
+    }
 }
 
-pub fn parse_args() -> Args {
-    let args_raw: Vec<String> = env::args().skip(1).collect();
+pub fn parse(args_raw: impl IntoIterator<Item = String>) -> Result<Command, Error> {
     let mut args = Args::default();
-    let mut arg_it = args_raw.iter();
+    let mut arg_it = args_raw.into_iter().peekable();
+
+    if let Some(command) = arg_it
+        .peek()
+        .and_then(|name| COMMANDS.iter().find(|command| command.name == name))
+    {
+        arg_it.next();
+        return parse_command(command, arg_it);
+    }
 
     while let Some(option) = arg_it.next() {
-        let option = match option.as_str() {
-            "-h" | "--help" => Arg::Help,
-            "-r" | "--resolution" => Arg::Resolution,
-            "-o" | "--option" => Arg::RenderOption,
-            "-i" | "--info" => Arg::Info,
-            "-m" | "--model" => Arg::Model,
-            "-f" | "--fps" => Arg::Fps,
-            _ => {
-                println!("Unknown option \"{}\"", option);
-                std::process::exit(1);
-            }
+        // Split "--option=value".
+        let (name, inline) = match option.split_once('=') {
+            Some((name, inline)) if name.starts_with("--") => (name, Some(inline.to_string())),
+            _ => (option.as_str(), None),
         };
 
-        match option {
-            Arg::Help => {
-                println!(
-                    "GRPE Usage:
-grpe [OPTION]
-
-OPTIONS:
--h, --help
-Default: false
-Print this help section.
-
--r <width height>, --resolution <width height>
-Default: 64 64
-Set the resolution.
-
--o <option>, --render-option <option>
-Default: vertices
-Available options:
-vertices - Renders only vertices.
-wireframe - Renders only wireframe (lines between vertices).
-wireframeandparticles - Wireframe + particles (single point vertices).
-culling - Renders wireframe, but with backface culling.
-cullingandparticles - Culling + particles (single point vertices).
-polyfillandculling - Fill polygons + culling.
-polyfillandcullingandparticles - Fill polygons + culling + particles (single point vertices).
-
--i, --info
-Default: true
-During execution, print additional information at the bottom.
-This includes fps, missed frames, fov, etc.
-
--m, --model
-Default: \"plane\"
-What to display.
-Available models:
-plane
-spiral
-
--f, --fps
-Default: 60
-Set the frames per second.
-                    "
-                );
-                std::process::exit(0);
+        let definition = DEFINITIONS
+            .iter()
+            .find(|definition| match name.strip_prefix("--") {
+                Some(long) => long == definition.long || definition.aliases.contains(&long),
+                None => name == format!("-{}", definition.short),
+            })
+            .ok_or_else(|| Error::UnknownOption(name.to_string()))?;
+
+        let mut values = match inline {
+            Some(_) if definition.values.is_empty() => {
+                return Err(Error::UnexpectedValue(definition.long));
             }
+            // A single value may contain commas itself, e.g. "--model=sphere:segments=8,rings=4".
+            Some(inline) if definition.values.len() == 1 => vec![inline],
+            Some(inline) => inline.split(',').map(str::to_string).collect::<Vec<_>>(),
+            None => arg_it.by_ref().take(definition.values.len()).collect(),
+        }
+        .into_iter();
+
+        let mut next = |value| {
+            values.next().ok_or(Error::MissingValue {
+                option: definition.long,
+                value,
+            })
+        };
+
+        match definition.arg {
+            Arg::Help => return Ok(Command::Help),
+            Arg::Version => return Ok(Command::Version),
             Arg::Resolution => {
-                let width: u64 = arg_it.next().unwrap().parse().unwrap();
-                let height: u64 = arg_it.next().unwrap().parse().unwrap();
+                let width = parse_value(definition, next("width")?, positive)?;
+                let height = parse_value(definition, next("height")?, height)?;
                 args.resolution = Some((width, height));
             }
             Arg::RenderOption => {
-                let option: RenderOption = arg_it.next().unwrap().parse().unwrap();
-                args.render_option = Some(option);
+                args.render_option = Some(parse_value(definition, next("option")?, |_| Ok(()))?);
             }
             Arg::Info => {
                 args.info = Some(());
             }
             Arg::Model => {
-                let model = arg_it.next().unwrap().parse().unwrap_or_else(|_| {
-                    println!("Unkown model type given. Please run with '-h' or '--help' option for full list.");
-                    std::process::exit(0);
-                });
-                args.model = Some(model);
+                let model = next("model")?;
+                args.model = Some(model.parse().map_err(|reason| Error::InvalidValue {
+                    option: definition.long,
+                    value: model.clone(),
+                    reason,
+                })?);
             }
             Arg::Fps => {
-                let fps = arg_it.next().unwrap().parse().unwrap();
-                args.fps = Some(fps);
+                args.fps = Some(parse_value(definition, next("fps")?, positive)?);
+            }
+            Arg::Threads => {
+                let threads: u64 = parse_value(definition, next("count")?, positive)?;
+                args.threads = Some(threads as usize);
+            }
+            Arg::Profile => {
+                args.profile = Some(next("name")?);
+            }
+            Arg::Trace => {
+                args.trace = Some(parse_value(definition, next("file")?, |path: &PathBuf| {
+                    profile::Format::from_path(path)
+                        .map(|_| ())
+                        .ok_or("Expected a \".csv\" or \".json\" file.")
+                })?);
+            }
+            Arg::View => {
+                args.view = Some(next("name")?);
+            }
+            Arg::Edit => {
+                args.edit = Some(parse_value(
+                    definition,
+                    next("file")?,
+                    |path: &PathBuf| match path
+                        .extension()
+                        .is_some_and(|extension| extension == "obj")
+                    {
+                        true => Ok(()),
+                        false => Err("Expected a \".obj\" file."),
+                    },
+                )?);
             }
         }
+
+        if let Some(value) = values.next() {
+            return Err(Error::InvalidValue {
+                option: definition.long,
+                value,
+                reason: "Too many values.",
+            });
+        }
     }
 
-    args
+    Ok(Command::Run(args))
+}
+
+/// Parse the arguments of a command.
+fn parse_command(
+    definition: &CommandDefinition,
+    args_raw: impl Iterator<Item = String>,
+) -> Result<Command, Error> {
+    let mut arguments = args_raw.collect::<Vec<_>>().into_iter();
+    let invalid = |argument, reason| Error::InvalidArgument {
+        command: definition.name,
+        argument,
+        reason,
+    };
+    let mut next = |argument| {
+        arguments.next().ok_or(Error::MissingArgument {
+            command: definition.name,
+            argument,
+        })
+    };
+
+    let command = match definition.subcommand {
+        Subcommand::Check => {
+            let model = next("model")?;
+            match model.parse() {
+                Ok(parsed) => Command::Check(parsed),
+                Err(reason) => return Err(invalid(model, reason)),
+            }
+        }
+    };
+
+    match arguments.next() {
+        Some(argument) => Err(invalid(argument, "Too many arguments.")),
+        None => Ok(command),
+    }
+}
+
+/// Parse the arguments of the process, or exit with why they are invalid.
+pub fn parse_args() -> Command {
+    parse(env::args().skip(1)).unwrap_or_else(|e| {
+        println!("{e}\nPlease run with '-h' or '--help' option for usage.");
+        std::process::exit(1);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &str) -> Result<Args, Error> {
+        match parse(args.split_whitespace().map(str::to_string))? {
+            Command::Run(args) => Ok(args),
+            _ => panic!("Not a run command: {args}"),
+        }
+    }
+
+    #[test]
+    fn values() {
+        let args = run("-r 80 40 -o wireframe -i -m cube -f 30 -t 3 -p small -T t.json -v 2 -e m.obj").unwrap();
+        assert!(args.resolution == Some((80, 40)));
+        assert!(matches!(args.render_option, Some(RenderOption::WireFrame)));
+        assert!(args.info.is_some());
+        assert!(matches!(args.model, Some(Model::Cube)));
+        assert!(args.fps == Some(30));
+        assert!(args.threads == Some(3));
+        assert!(args.profile.as_deref() == Some("small"));
+        assert!(args.trace == Some(PathBuf::from("t.json")));
+        assert!(args.view.as_deref() == Some("2"));
+        assert!(args.edit == Some(PathBuf::from("m.obj")));
+    }
+
+    #[test]
+    fn inline_values() {
+        let args = run("--resolution=80,40 --option=vertices --fps=144 --model=spiral").unwrap();
+        assert!(args.resolution == Some((80, 40)));
+        assert!(matches!(args.render_option, Some(RenderOption::Vertices)));
+        assert!(args.fps == Some(144));
+        assert!(matches!(args.model, Some(Model::Spiral)));
+
+        let args = run("--model=z=x*y;x=-2..2").unwrap();
+        assert!(matches!(args.model, Some(Model::Plot(_))));
+
+        let args = run("--model=cone:radius=8,segments=5").unwrap();
+        assert!(args.model.is_some_and(|model| model.to_string() == "cone:radius=8,height=64,segments=5"));
+    }
+
+    #[test]
+    fn commands() {
+        let parse = |args: &[&str]| parse(args.iter().map(|arg| arg.to_string()));
+        assert!(matches!(parse(&["-h"]), Ok(Command::Help)));
+        assert!(matches!(parse(&["-i", "--help"]), Ok(Command::Help)));
+        assert!(matches!(parse(&["--version"]), Ok(Command::Version)));
+        assert!(matches!(parse(&["-V"]), Ok(Command::Version)));
+        assert!(matches!(
+            parse(&["check", "cube"]),
+            Ok(Command::Check(Model::Cube))
+        ));
+        assert!(matches!(
+            parse(&["-i", "check", "cube"]),
+            Err(Error::UnknownOption(_))
+        ));
+    }
+
+    #[test]
+    fn errors() {
+        let tester = |args: &str, expected: Error| {
+            let result = run(args);
+            assert!(
+                matches!(&result, Err(e) if *e == expected),
+                "Args: {args}. Actual: {:?}",
+                result.err()
+            );
+        };
+
+        tester("--nope", Error::UnknownOption("--nope".into()));
+        tester("-rr", Error::UnknownOption("-rr".into()));
+        tester(
+            "-r 80",
+            Error::MissingValue {
+                option: "resolution",
+                value: "height",
+            },
+        );
+        tester(
+            "-r 80 x",
+            Error::InvalidValue {
+                option: "resolution",
+                value: "x".into(),
+                reason: "Could not be parsed.",
+            },
+        );
+        tester(
+            "-r 80 1",
+            Error::InvalidValue {
+                option: "resolution",
+                value: "1".into(),
+                reason: "Must be at least 2.",
+            },
+        );
+        tester(
+            "--resolution=80,40,1",
+            Error::InvalidValue {
+                option: "resolution",
+                value: "1".into(),
+                reason: "Too many values.",
+            },
+        );
+        tester(
+            "-f 0",
+            Error::InvalidValue {
+                option: "fps",
+                value: "0".into(),
+                reason: "Must be greater than zero.",
+            },
+        );
+        tester(
+            "-m teapot",
+            Error::InvalidValue {
+                option: "model",
+                value: "teapot".into(),
+                reason: "Unknown model.",
+            },
+        );
+        tester(
+            "--trace trace.txt",
+            Error::InvalidValue {
+                option: "trace",
+                value: "trace.txt".into(),
+                reason: "Expected a \".csv\" or \".json\" file.",
+            },
+        );
+        tester(
+            "--edit model.txt",
+            Error::InvalidValue {
+                option: "edit",
+                value: "model.txt".into(),
+                reason: "Expected a \".obj\" file.",
+            },
+        );
+        tester(
+            "-m sphere:segments=2",
+            Error::InvalidValue {
+                option: "model",
+                value: "sphere:segments=2".into(),
+                reason: "Parameter out of range.",
+            },
+        );
+        tester(
+            "-m missing.ply",
+            Error::InvalidValue {
+                option: "model",
+                value: "missing.ply".into(),
+                reason: "Could not read the file.",
+            },
+        );
+        tester("--info=yes", Error::UnexpectedValue("info"));
+        tester(
+            "check",
+            Error::MissingArgument {
+                command: "check",
+                argument: "model",
+            },
+        );
+        tester(
+            "check teapot",
+            Error::InvalidArgument {
+                command: "check",
+                argument: "teapot".into(),
+                reason: "Unknown model.",
+            },
+        );
+        tester(
+            "check cube plane",
+            Error::InvalidArgument {
+                command: "check",
+                argument: "plane".into(),
+                reason: "Too many arguments.",
+            },
+        );
+    }
+
+    #[test]
+    fn help_is_complete() {
+        let help = help();
+
+        for definition in DEFINITIONS.iter() {
+            assert!(help.contains(&format!("--{}", definition.long)));
+        }
+
+        for command in COMMANDS.iter() {
+            assert!(help.contains(&format!("grpe {} <model>", command.name)));
+            assert!(help.contains(command.description));
+        }
+
+        for option in RenderOption::ALL.iter() {
+            assert!(help.contains(&option.to_string()));
+        }
+
+        for model in Model::ALL.iter() {
+            assert!(help.contains(&model.to_string()));
+        }
+
+        for shape in Shape::ALL {
+            assert!(help.contains(&Primitive::new(shape).to_string()));
+        }
+    }
 }
@@ -0,0 +1,383 @@
+//! Camera orientation helpers, the motion of the free-fly (first person) camera, and the target of the orbit camera.
+//!
+//! Camera space: x is right, y is forward, and z is up.
+
+use linear_algebra::quaternion::{self, Quaternion};
+use renderer::{bounds::Bounds, Camera, ProjectionMode, VectorRow};
+
+/// Rotation (and its inverse) that pitches, then yaws, the camera. Angles are in radians.
+pub fn rotation(pitch: f64, yaw: f64) -> (Quaternion<f64>, Quaternion<f64>) {
+    let rotation = (pitch / 2.0, yaw / 2.0); // Half angles for quaternions.
+    let pitch = Quaternion(
+        rotation.0.cos(),
+        rotation.0.sin() * (rotation.1 * 2.0).cos(),
+        rotation.0.sin() * (rotation.1 * 2.0).sin(),
+        0.0,
+    );
+    let yaw = Quaternion(rotation.1.cos(), 0.0, 0.0, rotation.1.sin());
+    let rotation = &pitch * &yaw;
+    let rotation_prim = rotation.inverse();
+    (rotation, rotation_prim)
+}
+
+/// Pitch and yaw of the direction the camera looks in, i.e. [rotation] without any roll.
+pub fn pitch_yaw(rotation: &(Quaternion<f64>, Quaternion<f64>)) -> (f64, f64) {
+    let forward = quaternion::rotate(&VectorRow::from([0.0, 1.0, 0.0]), &rotation.0, &rotation.1);
+    (
+        forward[2].clamp(-1.0, 1.0).asin(),
+        f64::atan2(-forward[0], forward[1]),
+    )
+}
+
+/// Direction of an axis of camera space in world space, e.g. [0.0, 1.0, 0.0] for forward.
+pub fn axis(camera: &Camera, axis: [f64; 3]) -> VectorRow<f64, 3> {
+    quaternion::rotate(
+        &VectorRow::from(axis),
+        &camera.rotation.0,
+        &camera.rotation.1,
+    )
+}
+
+/// Distance from the viewpoint to the viewport, in pixels. Mirrors the renderer.
+pub fn focal(camera: &Camera) -> f64 {
+    let fov = match camera.projection_mode {
+        ProjectionMode::Perspective { fov } => fov,
+        ProjectionMode::Orthographic => 90,
+    };
+    (camera.resolution.0 as f64 / 2.0) / f64::tan((fov as f64 / 2.0).to_radians())
+}
+
+/// Distance from the viewpoint at which a sphere fits the view, horizontally and vertically.
+/// `pixel_aspect` is the width/height ratio of a pixel.
+pub fn fit_distance(camera: &Camera, pixel_aspect: f64, radius: f64) -> f64 {
+    let focal = focal(camera);
+    let half_width = f64::atan((camera.resolution.0 as f64 / 2.0) / (focal * pixel_aspect));
+    let half_height = f64::atan((camera.resolution.1 as f64 / 2.0) / focal);
+    radius / half_width.min(half_height).sin()
+}
+
+/// Rotate about the camera's own axes, by `angles` (x: pitch, y: roll, z: yaw) in radians.
+pub fn rotate_local(
+    rotation: &(Quaternion<f64>, Quaternion<f64>),
+    angles: [f64; 3],
+) -> (Quaternion<f64>, Quaternion<f64>) {
+    let angle = VectorRow::from(angles).length();
+
+    if angle < f64::EPSILON {
+        return rotation.clone();
+    }
+
+    let scale = (angle / 2.0).sin() / angle;
+    let local = Quaternion(
+        (angle / 2.0).cos(),
+        angles[0] * scale,
+        angles[1] * scale,
+        angles[2] * scale,
+    );
+    let rotation = &rotation.0 * &local;
+    let rotation_prim = rotation.inverse();
+    (rotation, rotation_prim)
+}
+
+/// Velocities decay by a factor e per this many seconds.
+const DAMPING_TIME: f64 = 0.25;
+
+/// Time a key press thrusts for. About the key repeat interval, so that holding a key keeps
+/// the camera moving at about [Fly::speed].
+const PRESS_TIME: f64 = 1.0 / 30.0;
+
+/// Angular speed reached when holding a rotation key, in radians per second.
+const ANGULAR_SPEED: f64 = std::f64::consts::FRAC_PI_2;
+
+const SPEED_DEFAULT: f64 = 200.0;
+const SPEED_FACTOR: f64 = 1.5;
+const SPEED_RANGE: (f64, f64) = (1.0, 100_000.0);
+
+/// Motion of the free-fly camera. Key presses accelerate it, and it slows down on its own.
+/// Terminals only report key presses (repeated while held), not releases, so each press thrusts for [PRESS_TIME].
+pub struct Fly {
+    /// In world space, so that turning does not change the direction of travel.
+    velocity: VectorRow<f64, 3>,
+    /// About the camera's own axes (x: pitch, y: roll, z: yaw), in radians per second.
+    angular_velocity: [f64; 3],
+    /// Speed reached when holding a movement key, in units per second.
+    speed: f64,
+}
+
+impl Default for Fly {
+    fn default() -> Self {
+        Self {
+            velocity: VectorRow::from([0.0, 0.0, 0.0]),
+            angular_velocity: [0.0; 3],
+            speed: SPEED_DEFAULT,
+        }
+    }
+}
+
+impl Fly {
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * SPEED_FACTOR).min(SPEED_RANGE.1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / SPEED_FACTOR).max(SPEED_RANGE.0);
+    }
+
+    pub fn stop(&mut self) {
+        self.velocity = VectorRow::from([0.0, 0.0, 0.0]);
+        self.angular_velocity = [0.0; 3];
+    }
+
+    /// Accelerate in `direction` of camera space, e.g. [0.0, 1.0, 0.0] for forward.
+    pub fn thrust(&mut self, camera: &Camera, direction: [f64; 3]) {
+        let mut impulse = quaternion::rotate(
+            &VectorRow::from(direction),
+            &camera.rotation.0,
+            &camera.rotation.1,
+        );
+        impulse.0.scale(self.speed * PRESS_TIME / DAMPING_TIME);
+        self.velocity = (&self.velocity.0 + &impulse.0).into();
+    }
+
+    /// Accelerate the rotation about an axis of camera space (0: pitch, 1: roll, 2: yaw). `sign` is the direction.
+    pub fn spin(&mut self, axis: usize, sign: f64) {
+        self.angular_velocity[axis] += sign * ANGULAR_SPEED * PRESS_TIME / DAMPING_TIME;
+    }
+
+    /// Move and turn `camera` by `dt` seconds of motion.
+    pub fn step(&mut self, camera: &mut Camera, dt: f64) {
+        // Exact for exponential decay, so the motion does not depend on the size of the step.
+        let decay = (-dt / DAMPING_TIME).exp();
+        let travel = DAMPING_TIME * (1.0 - decay);
+
+        let mut distance = self.velocity.clone();
+        distance.0.scale(travel);
+        camera.position = (&camera.position.0 + &distance.0).into();
+        self.velocity.0.scale(decay);
+
+        camera.rotation = rotate_local(
+            &camera.rotation,
+            self.angular_velocity.map(|velocity| velocity * travel),
+        );
+        self.angular_velocity = self.angular_velocity.map(|velocity| velocity * decay);
+    }
+}
+
+/// Change of the orbit distance per dolly step.
+const DOLLY_FACTOR: f64 = 1.1;
+const DISTANCE_RANGE: (f64, f64) = (0.001, 1000.0);
+
+/// Where the orbit camera looks at, and turns around.
+///
+/// The distance is in multiples of the distance to the viewport, so that the view keeps its size when
+/// the resolution or fov changes. At 1.0 the viewport (the camera position) is at the target.
+#[derive(Clone)]
+pub struct Orbit {
+    pub target: VectorRow<f64, 3>,
+    pub distance: f64,
+}
+
+impl Default for Orbit {
+    fn default() -> Self {
+        Self {
+            target: VectorRow::from([0.0, 0.0, 0.0]),
+            distance: 1.0,
+        }
+    }
+}
+
+impl Orbit {
+    /// Orbit the point straight ahead of a first person `camera`, without changing the view.
+    pub fn ahead_of(camera: &Camera) -> Self {
+        let mut ahead = axis(camera, [0.0, 1.0, 0.0]);
+        ahead.0.scale(focal(camera));
+
+        Self {
+            target: (&camera.position.0 + &ahead.0).into(),
+            distance: 1.0,
+        }
+    }
+
+    /// Camera position for the orbit, given the camera's rotation.
+    pub fn position(&self, camera: &Camera) -> VectorRow<f64, 3> {
+        let mut back = axis(camera, [0.0, -1.0, 0.0]);
+        back.0.scale(focal(camera) * (self.distance - 1.0));
+        (&self.target.0 + &back.0).into()
+    }
+
+    /// Where the viewpoint is, i.e. the position of the first person camera with the same view.
+    pub fn viewpoint(&self, camera: &Camera) -> VectorRow<f64, 3> {
+        let mut back = axis(camera, [0.0, -1.0, 0.0]);
+        back.0.scale(focal(camera) * self.distance);
+        (&self.target.0 + &back.0).into()
+    }
+
+    /// Move closer (positive `steps`) or further away, by a factor per step.
+    pub fn dolly(&mut self, steps: f64) {
+        self.distance =
+            (self.distance * DOLLY_FACTOR.powf(-steps)).clamp(DISTANCE_RANGE.0, DISTANCE_RANGE.1);
+    }
+
+    /// Move the target in the plane of the viewport, so that it follows a drag of (`x`, `z`) pixels.
+    pub fn pan(&mut self, camera: &Camera, pixel_aspect: f64, x: f64, z: f64) {
+        let mut right = axis(camera, [1.0, 0.0, 0.0]);
+        let mut up = axis(camera, [0.0, 0.0, 1.0]);
+        right.0.scale(-x / pixel_aspect * self.distance);
+        up.0.scale(-z * self.distance);
+        self.target = (&(&self.target.0 + &right.0) + &up.0).into();
+    }
+
+    /// Look at the center of `bounds`, from where all of it fits the view.
+    pub fn frame(&mut self, camera: &Camera, pixel_aspect: f64, bounds: &Bounds) {
+        self.target = bounds.center.clone();
+        self.distance = (fit_distance(camera, pixel_aspect, bounds.radius) / focal(camera))
+            .clamp(DISTANCE_RANGE.0, DISTANCE_RANGE.1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forward(rotation: &(Quaternion<f64>, Quaternion<f64>)) -> VectorRow<f64, 3> {
+        quaternion::rotate(&VectorRow::from([0.0, 1.0, 0.0]), &rotation.0, &rotation.1)
+    }
+
+    fn close(a: &VectorRow<f64, 3>, b: &VectorRow<f64, 3>) -> bool {
+        VectorRow::from(&a.0 - &b.0).length() < 1e-9
+    }
+
+    #[test]
+    fn pitch_yaw_of_rotation() {
+        for (pitch, yaw) in [(0.0, 0.0), (0.5, 1.0), (-1.2, -2.5), (1.0, 3.0)] {
+            let (pitch_, yaw_) = pitch_yaw(&rotation(pitch, yaw));
+            assert!((pitch - pitch_).abs() < 1e-9 && (yaw - yaw_).abs() < 1e-9);
+        }
+
+        // Roll does not change where the camera looks.
+        let rolled = rotate_local(&rotation(0.5, 1.0), [0.0, 0.7, 0.0]);
+        assert!(close(&forward(&rolled), &forward(&rotation(0.5, 1.0))));
+        let (pitch, yaw) = pitch_yaw(&rolled);
+        assert!((pitch - 0.5).abs() < 1e-9 && (yaw - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn local_rotation() {
+        let level = rotation(0.0, 0.0);
+        assert!(close(
+            &forward(&rotate_local(&level, [0.0, 0.0, 0.3])),
+            &forward(&rotation(0.0, 0.3))
+        ));
+        assert!(close(
+            &forward(&rotate_local(&level, [0.3, 0.0, 0.0])),
+            &forward(&rotation(0.3, 0.0))
+        ));
+
+        // Pitching past straight up is not clamped.
+        let over = rotate_local(&level, [2.0, 0.0, 0.0]);
+        assert!(forward(&over)[1] < 0.0);
+    }
+
+    #[test]
+    fn motion_independent_of_step() {
+        let fly = |steps: u32| {
+            let mut fly = Fly::default();
+            let mut camera = Camera::default();
+            fly.thrust(&camera, [0.0, 1.0, 0.0]);
+            fly.spin(2, 1.0);
+
+            for _ in 0..steps {
+                fly.step(&mut camera, 1.0 / steps as f64);
+            }
+
+            camera
+        };
+
+        let slow = fly(30);
+        let fast = fly(144);
+        assert!(close(&slow.position, &fast.position));
+        assert!(close(&forward(&slow.rotation), &forward(&fast.rotation)));
+
+        // Forward, coasting to a stop.
+        let distance = SPEED_DEFAULT * PRESS_TIME * (1.0 - (-1.0 / DAMPING_TIME).exp());
+        assert!(close(
+            &slow.position,
+            &VectorRow::from([0.0, distance, 0.0])
+        ));
+    }
+
+    #[test]
+    fn orbit() {
+        let camera = Camera {
+            rotation: rotation(0.3, 1.2),
+            ..Default::default()
+        };
+        let focal = focal(&camera);
+
+        // Default orbits the origin, with the viewport at it.
+        let mut orbit = Orbit::default();
+        assert!(close(&orbit.position(&camera), &orbit.target));
+
+        // Further away, the target stays straight ahead of the viewpoint.
+        orbit.dolly(-1.0);
+        assert!((orbit.distance - DOLLY_FACTOR).abs() < 1e-9);
+        let mut ahead = forward(&camera.rotation);
+        ahead.0.scale(orbit.distance * focal);
+        assert!(close(
+            &(&orbit.viewpoint(&camera).0 + &ahead.0).into(),
+            &orbit.target
+        ));
+
+        // Switching from first person keeps the view.
+        let first_person = Camera {
+            position: orbit.viewpoint(&camera),
+            ..camera.clone()
+        };
+        let from_first_person = Orbit::ahead_of(&first_person);
+        assert!(close(
+            &from_first_person.viewpoint(&camera),
+            &first_person.position
+        ));
+
+        // Panning right moves the target left.
+        orbit.pan(&camera, 1.0, 10.0, 0.0);
+        let moved = orbit.target.clone();
+        assert!(moved.dot(&axis(&camera, [1.0, 0.0, 0.0])) < 0.0);
+        assert!(moved.dot(&axis(&camera, [0.0, 0.0, 1.0])).abs() < 1e-9);
+    }
+
+    #[test]
+    fn frame_bounds() {
+        let camera = Camera {
+            resolution: (200, 100),
+            ..Default::default()
+        };
+        let bounds = Bounds::from_vertices(&[
+            VectorRow::from([-10.0, 40.0, 0.0]),
+            VectorRow::from([10.0, 60.0, 0.0]),
+        ])
+        .unwrap();
+
+        let mut orbit = Orbit::default();
+        orbit.frame(&camera, 1.0, &bounds);
+        assert!(close(&orbit.target, &VectorRow::from([0.0, 50.0, 0.0])));
+
+        // Limited by the height. 90 degrees fov and a 2:1 view, so the viewport is at 100 pixels.
+        let half_height = f64::atan(50.0 / 100.0);
+        let expected = bounds.radius / half_height.sin() / 100.0;
+        assert!((orbit.distance - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn speed_limits() {
+        let mut fly = Fly::default();
+        (0..100).for_each(|_| fly.faster());
+        assert!(fly.speed() == SPEED_RANGE.1);
+        (0..100).for_each(|_| fly.slower());
+        assert!(fly.speed() == SPEED_RANGE.0);
+    }
+}
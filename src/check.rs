@@ -0,0 +1,398 @@
+/// Validation of a model's line draw order against its vertices, for "grpe check <model>".
+/// Polygons (line draw orders of three or more vertices) are checked as a mesh: that neighbours
+/// agree on winding, which backface culling relies on, that no edge is shared by more than two of
+/// them, and that they are flat.
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    fmt::Display,
+};
+
+use renderer::{Object, VectorRow};
+
+/// Relative to the size of a polygon, how small its area may be before it is degenerate.
+const AREA_TOLERANCE: f64 = 1e-9;
+
+/// Relative to the size of a polygon, how far a vertex may be off its plane.
+const PLANE_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    OutOfRange {
+        face: usize,
+        vertex: usize,
+    },
+    Degenerate {
+        face: usize,
+        reason: &'static str,
+    },
+    /// Vertices of the same object at the same position, so faces through them are not connected.
+    DuplicateVertices {
+        vertices: (usize, usize),
+    },
+    /// Polygons going the same way along a shared edge, so that one of them faces the other way.
+    InconsistentWinding {
+        faces: (usize, usize),
+        edge: (usize, usize),
+    },
+    /// Edge shared by more than two polygons.
+    NonManifoldEdge {
+        edge: (usize, usize),
+        faces: Vec<usize>,
+    },
+    NonPlanar {
+        face: usize,
+        distance: f64,
+    },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::OutOfRange { face, vertex } => {
+                write!(f, "Face {face}: Vertex {vertex} is out of range.")
+            }
+            Problem::Degenerate { face, reason } => write!(f, "Face {face}: Degenerate, {reason}."),
+            Problem::DuplicateVertices { vertices: (a, b) } => {
+                write!(f, "Vertices {a} and {b}: Same position.")
+            }
+            Problem::InconsistentWinding {
+                faces: (a, b),
+                edge: (from, to),
+            } => write!(
+                f,
+                "Faces {a} and {b}: Inconsistent winding, both go from vertex {from} to {to}."
+            ),
+            Problem::NonManifoldEdge {
+                edge: (a, b),
+                faces,
+            } => write!(
+                f,
+                "Faces {}: Non-manifold edge between vertices {a} and {b}.",
+                faces
+                    .iter()
+                    .map(|face| face.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Problem::NonPlanar { face, distance } => {
+                write!(
+                    f,
+                    "Face {face}: Not planar, a vertex is {distance:.3} off its plane."
+                )
+            }
+        }
+    }
+}
+
+/// Problems of a model, grouped by kind. `objects` scope the search for duplicate vertices.
+pub fn check(
+    vertices: &[VectorRow<f64, 3>],
+    line_draw_order: &[Vec<usize>],
+    objects: &[Object],
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    for (face, order) in line_draw_order.iter().enumerate() {
+        for &vertex in order.iter().filter(|&&vertex| vertex >= vertices.len()) {
+            problems.push(Problem::OutOfRange { face, vertex });
+        }
+    }
+
+    // Polygons that can be checked further, with their normal (not normalized) and size.
+    let mut polygons = Vec::new();
+
+    for (face, order) in line_draw_order.iter().enumerate() {
+        if order.iter().any(|&vertex| vertex >= vertices.len()) {
+            continue;
+        }
+
+        let repeated = order
+            .iter()
+            .enumerate()
+            .any(|(i, vertex)| order[i + 1..].contains(vertex));
+        let reason = match order.len() {
+            0 => Some("no vertices"),
+            1 => None,
+            2 if repeated => Some("line to its own start"),
+            2 => None,
+            _ if repeated => Some("repeated vertex"),
+            _ => {
+                let points = order
+                    .iter()
+                    .map(|&index| &vertices[index])
+                    .collect::<Vec<_>>();
+                let (normal, size) = normal_and_size(&points);
+
+                match normal.length() <= AREA_TOLERANCE * size * size {
+                    true => Some("no area"),
+                    false => {
+                        polygons.push((face, points, normal, size));
+                        None
+                    }
+                }
+            }
+        };
+
+        if let Some(reason) = reason {
+            problems.push(Problem::Degenerate { face, reason });
+        }
+    }
+
+    let whole = [Object {
+        vertices: 0..vertices.len(),
+        line_draw_order: 0..line_draw_order.len(),
+        levels: Vec::new(),
+    }];
+    let objects = match objects.is_empty() {
+        true => &whole[..],
+        false => objects,
+    };
+
+    for object in objects {
+        let mut seen = HashMap::new();
+        let range =
+            object.vertices.start.min(vertices.len())..object.vertices.end.min(vertices.len());
+
+        for index in range {
+            // "+ 0.0" so that -0 and 0 are the same.
+            let key = [0, 1, 2].map(|axis| (vertices[index][axis] + 0.0).to_bits());
+
+            match seen.entry(key) {
+                Entry::Occupied(first) => problems.push(Problem::DuplicateVertices {
+                    vertices: (*first.get(), index),
+                }),
+                Entry::Vacant(entry) => {
+                    entry.insert(index);
+                }
+            }
+        }
+    }
+
+    // Polygons along each edge (lower vertex index first), and whether they go from the lower to the higher.
+    let mut edges: BTreeMap<(usize, usize), Vec<(usize, bool)>> = BTreeMap::new();
+
+    for (face, _, _, _) in polygons.iter() {
+        let order = &line_draw_order[*face];
+
+        for (&from, &to) in order.iter().zip(order.iter().cycle().skip(1)) {
+            edges
+                .entry((from.min(to), from.max(to)))
+                .or_default()
+                .push((*face, from < to));
+        }
+    }
+
+    for (edge, uses) in edges {
+        match uses.as_slice() {
+            [(a, forward_a), (b, forward_b)] if forward_a == forward_b => {
+                problems.push(Problem::InconsistentWinding {
+                    faces: (*a, *b),
+                    edge: match forward_a {
+                        true => edge,
+                        false => (edge.1, edge.0),
+                    },
+                });
+            }
+            uses if uses.len() > 2 => problems.push(Problem::NonManifoldEdge {
+                edge,
+                faces: uses.iter().map(|(face, _)| *face).collect(),
+            }),
+            _ => (),
+        }
+    }
+
+    for (face, points, normal, size) in polygons.iter().filter(|polygon| polygon.1.len() > 3) {
+        let length = normal.length();
+        let unit = [0, 1, 2].map(|axis| normal[axis] / length);
+        let center = points.iter().fold([0.0; 3], |center, point| {
+            [0, 1, 2].map(|axis| center[axis] + point[axis] / points.len() as f64)
+        });
+        let distance = points
+            .iter()
+            .map(|point| {
+                (0..3)
+                    .map(|axis| (point[axis] - center[axis]) * unit[axis])
+                    .sum::<f64>()
+                    .abs()
+            })
+            .fold(0.0, f64::max);
+
+        if distance > PLANE_TOLERANCE * size {
+            problems.push(Problem::NonPlanar {
+                face: *face,
+                distance,
+            });
+        }
+    }
+
+    problems
+}
+
+/// Normal of a polygon (Newell's method), as long as twice its area, and its longest edge.
+fn normal_and_size(points: &[&VectorRow<f64, 3>]) -> (VectorRow<f64, 3>, f64) {
+    let mut normal = [0.0; 3];
+    let mut size: f64 = 0.0;
+
+    for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+        normal[0] += (a[1] - b[1]) * (a[2] + b[2]);
+        normal[1] += (a[2] - b[2]) * (a[0] + b[0]);
+        normal[2] += (a[0] - b[0]) * (a[1] + b[1]);
+        size = size.max(VectorRow::from([b[0] - a[0], b[1] - a[1], b[2] - a[2]]).length());
+    }
+
+    (VectorRow::from(normal), size)
+}
+
+/// Report of the problems of a model, as printed by "grpe check".
+pub fn report(
+    name: &str,
+    vertices: &[VectorRow<f64, 3>],
+    line_draw_order: &[Vec<usize>],
+    problems: &[Problem],
+) -> String {
+    let mut report = format!(
+        "{name}: {} vertices, {} faces.\n",
+        vertices.len(),
+        line_draw_order.len()
+    );
+
+    match problems.len() {
+        0 => report += "No problems found.\n",
+        count => {
+            report += &format!("{count} problem(s) found:\n");
+            for problem in problems {
+                report += &format!("{problem}\n");
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        primitive::{Primitive, Shape},
+        Model,
+    };
+
+    fn vertices(points: &[[f64; 3]]) -> Vec<VectorRow<f64, 3>> {
+        points.iter().map(VectorRow::from).collect()
+    }
+
+    #[test]
+    fn closed_mesh() {
+        // Tetrahedron, every face wound the same way seen from outside.
+        let vertices = vertices(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ]);
+        let mut line_draw_order = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        assert!(check(&vertices, &line_draw_order, &[]).is_empty());
+
+        line_draw_order[2].reverse();
+        let problems = check(&vertices, &line_draw_order, &[]);
+        assert!(problems.len() == 3);
+        assert!(problems.contains(&Problem::InconsistentWinding {
+            faces: (1, 2),
+            edge: (1, 3),
+        }));
+    }
+
+    #[test]
+    fn problems() {
+        let vertices = vertices(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.5],
+            [2.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, -1.0, 0.0],
+        ]);
+        let line_draw_order = vec![
+            vec![0, 1, 2, 3],
+            vec![0, 9],
+            vec![],
+            vec![2, 2],
+            vec![0, 1, 4],
+            vec![0, 1, 1],
+            vec![0, 6, 1],
+            vec![0, 6, 4],
+            vec![1, 0, 3],
+            vec![5],
+        ];
+
+        let problems = check(&vertices, &line_draw_order, &[]);
+        assert!(
+            problems[..problems.len() - 1]
+                == [
+                    Problem::OutOfRange { face: 1, vertex: 9 },
+                    Problem::Degenerate {
+                        face: 2,
+                        reason: "no vertices"
+                    },
+                    Problem::Degenerate {
+                        face: 3,
+                        reason: "line to its own start"
+                    },
+                    Problem::Degenerate {
+                        face: 4,
+                        reason: "no area"
+                    },
+                    Problem::Degenerate {
+                        face: 5,
+                        reason: "repeated vertex"
+                    },
+                    Problem::DuplicateVertices { vertices: (1, 5) },
+                    Problem::NonManifoldEdge {
+                        edge: (0, 1),
+                        faces: vec![0, 6, 8],
+                    },
+                    Problem::InconsistentWinding {
+                        faces: (6, 7),
+                        edge: (0, 6),
+                    },
+                ]
+        );
+        assert!(matches!(
+            problems.last(),
+            Some(Problem::NonPlanar { face: 0, distance }) if (distance - 0.25 / 4.5f64.sqrt()).abs() < 1e-9
+        ));
+
+        // Duplicates only count within an object.
+        let objects = [0..3, 3..7].map(|vertices| Object {
+            vertices,
+            line_draw_order: 0..0,
+            levels: Vec::new(),
+        });
+        let problems = check(&vertices, &line_draw_order, &objects);
+        assert!(!problems
+            .iter()
+            .any(|problem| matches!(problem, Problem::DuplicateVertices { .. })));
+    }
+
+    #[test]
+    fn primitives() {
+        for shape in Shape::ALL {
+            let primitive = Primitive::new(shape);
+            let (vertices, line_draw_order) = primitive.generate();
+            let problems = check(&vertices, &line_draw_order, &[]);
+            assert!(problems.is_empty(), "{primitive}: {problems:?}");
+        }
+    }
+
+    #[test]
+    fn plots() {
+        for plot in ["z = sin(x) * cos(y)", "z = 1 / x", "(cos(t), sin(t), t)"] {
+            let model: Model = plot.parse().unwrap();
+            let (vertices, line_draw_order) = model.generate();
+            let objects = model.get_objects(&vertices, &line_draw_order);
+            let problems = check(&vertices, &line_draw_order, &objects);
+            assert!(problems.is_empty(), "{plot}: {problems:?}");
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use std::path::PathBuf;
+
+use renderer::{Camera, ProjectionMode, RenderOption};
+
+use crate::{arg::Args, model::Model, toml};
+
+/// Directory for all configuration files. "$XDG_CONFIG_HOME/grpe", or "~/.config/grpe".
+pub fn dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("grpe"))
+}
+
+/// Defaults for a session. Read from "config.toml" in the configuration directory, where
+/// top level settings apply to every run, and named profiles override them, e.g:
+/// ```toml
+/// profile = "mate-terminal-fullscreen" # Used unless another is given with "--profile".
+/// model = "cube"
+///
+/// [profiles.mate-terminal-fullscreen]
+/// resolution = "auto" # Or [width, height].
+/// pixel_aspect = 1.5057
+/// fov = 90
+/// render_option = "polyfillandculling"
+/// colors = "inverted" # Or "normal".
+/// fps = 60
+/// info = true
+/// threads = 4
+/// lod = false
+/// gizmo = true # Axes in the corner.
+/// grid = true # Ground plane below the model.
+/// view_transition = 0.5 # Seconds to move to a saved view. 0 jumps.
+/// ```
+/// Command line options override both.
+pub struct Config {
+    /// [None] follows the terminal size.
+    pub resolution: Option<(u64, u64)>,
+    /// Width/height ratio of a terminal character cell's half.
+    pub pixel_aspect: f64,
+    pub fov: u64,
+    pub render_option: RenderOption,
+    pub invert_colors: bool,
+    pub model: Model,
+    /// [None] renders as fast as possible.
+    pub fps: Option<u64>,
+    pub info: bool,
+    /// [None] uses all available cores.
+    pub threads: Option<usize>,
+    /// Draw simplified versions of objects when they are small on screen.
+    pub lod: bool,
+    /// Show the world axes in a corner.
+    pub gizmo: bool,
+    /// Show a grid on the ground plane of the model.
+    pub grid: bool,
+    /// Seconds to move between saved views.
+    pub view_transition: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            resolution: None,
+            pixel_aspect: 65.5 / 43.5,
+            fov: 90,
+            render_option: RenderOption::default(),
+            invert_colors: false,
+            model: Model::Plane,
+            fps: None,
+            info: false,
+            threads: None,
+            lod: true,
+            gizmo: false,
+            grid: false,
+            view_transition: 0.5,
+        }
+    }
+}
+
+impl Config {
+    /// Load configuration file, using the given profile (if any). Defaults are used if there is no file.
+    pub fn load(profile: Option<&str>) -> Result<Self, String> {
+        let path = dir().map(|dir| dir.join("config.toml"));
+
+        let s = match path.as_ref().map(std::fs::read_to_string) {
+            Some(Ok(s)) => s,
+            Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("{}: {e}", path.unwrap().display()));
+            }
+            _ => String::new(),
+        };
+
+        Self::from_toml(&s, profile).map_err(|msg| match path {
+            Some(path) => format!("{}: {msg}", path.display()),
+            None => msg,
+        })
+    }
+
+    pub fn from_toml(s: &str, profile: Option<&str>) -> Result<Self, String> {
+        let mut table = toml::parse(s).map_err(|e| e.to_string())?;
+        let profiles = table.remove("profiles");
+        let profile_default = table.remove("profile");
+
+        let mut config = Self::default();
+        config.apply(&table)?;
+
+        let profile = match (profile, &profile_default) {
+            (Some(profile), _) => Some(profile),
+            (None, Some(profile)) => Some(profile.as_str().ok_or("\"profile\": Expected string.")?),
+            (None, None) => None,
+        };
+
+        if let Some(name) = profile {
+            let settings = profiles
+                .as_ref()
+                .and_then(|profiles| profiles.as_table()?.get(name)?.as_table())
+                .ok_or_else(|| format!("Unknown profile \"{name}\"."))?;
+            config.apply(settings)?;
+        }
+
+        Ok(config)
+    }
+
+    fn apply(&mut self, table: &toml::Table) -> Result<(), String> {
+        for (key, value) in table {
+            let error = |msg: &str| format!("\"{key}\": {msg}");
+
+            match key.as_str() {
+                "resolution" => {
+                    self.resolution = match (value.as_str(), value.as_array()) {
+                        (Some("auto"), _) => None,
+                        (_, Some([width, height])) => match (width.as_integer(), height.as_integer()) {
+                            (Some(width), Some(height)) if width > 0 && height > 1 => {
+                                Some((width as u64, height as u64))
+                            }
+                            _ => {
+                                return Err(error(
+                                    "Expected positive width, and height of at least 2.",
+                                ))
+                            }
+                        },
+                        _ => return Err(error("Expected \"auto\" or [width, height].")),
+                    };
+                }
+                "pixel_aspect" => {
+                    self.pixel_aspect = value
+                        .as_float()
+                        .filter(|aspect| *aspect > 0.0)
+                        .ok_or_else(|| error("Expected positive number."))?;
+                }
+                "fov" => {
+                    self.fov = value
+                        .as_integer()
+                        .filter(|fov| (1..=170).contains(fov))
+                        .ok_or_else(|| error("Expected integer in the range [1,170]."))?
+                        as u64;
+                }
+                "render_option" => {
+                    self.render_option = value
+                        .as_str()
+                        .and_then(|option| option.parse().ok())
+                        .ok_or_else(|| error("Unknown render option."))?;
+                }
+                "colors" => {
+                    self.invert_colors = match value.as_str() {
+                        Some("normal") => false,
+                        Some("inverted") => true,
+                        _ => return Err(error("Expected \"normal\" or \"inverted\".")),
+                    };
+                }
+                "model" => {
+                    self.model = value
+                        .as_str()
+                        .ok_or_else(|| error("Expected string."))?
+                        .parse()
+                        .map_err(error)?;
+                }
+                "fps" => {
+                    self.fps = Some(
+                        value
+                            .as_integer()
+                            .filter(|fps| *fps > 0)
+                            .ok_or_else(|| error("Expected positive integer."))?
+                            as u64,
+                    );
+                }
+                "info" => {
+                    self.info = value.as_bool().ok_or_else(|| error("Expected boolean."))?;
+                }
+                "threads" => {
+                    self.threads = Some(
+                        value
+                            .as_integer()
+                            .filter(|threads| *threads > 0)
+                            .ok_or_else(|| error("Expected positive integer."))?
+                            as usize,
+                    );
+                }
+                "lod" => {
+                    self.lod = value.as_bool().ok_or_else(|| error("Expected boolean."))?;
+                }
+                "gizmo" => {
+                    self.gizmo = value.as_bool().ok_or_else(|| error("Expected boolean."))?;
+                }
+                "grid" => {
+                    self.grid = value.as_bool().ok_or_else(|| error("Expected boolean."))?;
+                }
+                "view_transition" => {
+                    self.view_transition = value
+                        .as_float()
+                        .filter(|seconds| *seconds >= 0.0)
+                        .ok_or_else(|| error("Expected non-negative number."))?;
+                }
+                _ => return Err(error("Unknown setting.")),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Camera to start from, and to return to on reset.
+    pub fn camera(&self) -> Camera {
+        let mut camera = Camera {
+            projection_mode: ProjectionMode::Perspective { fov: self.fov },
+            ..Default::default()
+        };
+
+        if let Some(resolution) = self.resolution {
+            camera.resolution = resolution;
+        }
+
+        camera
+    }
+
+    /// Override with options given on the command line.
+    pub fn with_args(mut self, args: &Args) -> Self {
+        if let Some(resolution) = args.resolution {
+            self.resolution = Some(resolution);
+        }
+
+        if let Some(render_option) = &args.render_option {
+            self.render_option = render_option.clone();
+        }
+
+        if args.info.is_some() {
+            self.info = true;
+        }
+
+        if let Some(model) = &args.model {
+            self.model = model.clone();
+        }
+
+        if let Some(fps) = args.fps {
+            self.fps = Some(fps);
+        }
+
+        if let Some(threads) = args.threads {
+            self.threads = Some(threads);
+        }
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        profile = "small"
+        model = "cube"
+        fps = 30
+
+        [profiles.small]
+        resolution = [80, 40]
+        fov = 60
+
+        [profiles.mate-terminal-fullscreen]
+        resolution = "auto"
+        pixel_aspect = 1.5
+        render_option = "wireframe"
+        colors = "inverted"
+        info = true
+        threads = 2
+        lod = false
+        view_transition = 0
+        grid = true
+        "#;
+
+    #[test]
+    fn defaults() {
+        let config = Config::from_toml("", None).unwrap();
+        assert!(config.resolution.is_none());
+        assert!(config.fov == 90);
+        assert!(config.fps.is_none());
+        assert!(config.lod);
+        assert!(config.view_transition == 0.5);
+        assert!(!config.gizmo && !config.grid);
+    }
+
+    #[test]
+    fn profiles() {
+        let config = Config::from_toml(CONFIG, None).unwrap();
+        assert!(config.resolution == Some((80, 40)));
+        assert!(config.fov == 60);
+        assert!(config.fps == Some(30));
+        assert!(matches!(config.model, Model::Cube));
+        assert!(!config.invert_colors);
+
+        let config = Config::from_toml(CONFIG, Some("mate-terminal-fullscreen")).unwrap();
+        assert!(config.resolution.is_none());
+        assert!(config.fov == 90);
+        assert!(config.fps == Some(30));
+        assert!(config.pixel_aspect == 1.5);
+        assert!(matches!(config.render_option, RenderOption::WireFrame));
+        assert!(config.invert_colors);
+        assert!(config.info);
+        assert!(config.threads == Some(2));
+        assert!(!config.lod);
+        assert!(config.view_transition == 0.0);
+        assert!(config.grid);
+    }
+
+    #[test]
+    fn args_override() {
+        let args = Args {
+            resolution: Some((10, 20)),
+            model: Some(Model::Spiral),
+            ..Default::default()
+        };
+        let config = Config::from_toml(CONFIG, None).unwrap().with_args(&args);
+        assert!(config.resolution == Some((10, 20)));
+        assert!(matches!(config.model, Model::Spiral));
+        assert!(config.fps == Some(30));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Config::from_toml(CONFIG, Some("missing")).is_err());
+        assert!(Config::from_toml("", Some("missing")).is_err());
+        assert!(Config::from_toml("fov = 200", None).is_err());
+        assert!(Config::from_toml("resolution = [1]", None).is_err());
+        assert!(Config::from_toml("resolution = [80, 1]", None).is_err());
+        assert!(Config::from_toml("colour = \"normal\"", None).is_err());
+        assert!(Config::from_toml("model = \"teapot\"", None).is_err());
+        assert!(Config::from_toml("threads = 0", None).is_err());
+        assert!(Config::from_toml("view_transition = -1", None).is_err());
+    }
+}
@@ -0,0 +1,386 @@
+/// Editing of the model's vertices and faces in place, with undo and redo.
+use std::path::PathBuf;
+
+use renderer::{bounds::Bounds, overlay::Grid, Pick, Selection, VectorRow};
+
+use crate::obj;
+
+/// A change to the model, undone by its [Edit::inverse].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Edit {
+    /// Vertices moved, from and to the given positions.
+    Move {
+        vertices: Vec<usize>,
+        from: Vec<VectorRow<f64, 3>>,
+        to: Vec<VectorRow<f64, 3>>,
+    },
+    /// Face inserted at an index of the line draw order.
+    AddFace { index: usize, order: Vec<usize> },
+    /// Face removed from an index of the line draw order.
+    DeleteFace { index: usize, order: Vec<usize> },
+}
+
+impl Edit {
+    pub fn inverse(&self) -> Self {
+        match self.clone() {
+            Edit::Move { vertices, from, to } => Edit::Move {
+                vertices,
+                from: to,
+                to: from,
+            },
+            Edit::AddFace { index, order } => Edit::DeleteFace { index, order },
+            Edit::DeleteFace { index, order } => Edit::AddFace { index, order },
+        }
+    }
+
+    fn apply(&self, vertices: &mut [VectorRow<f64, 3>], line_draw_order: &mut Vec<Vec<usize>>) {
+        match self {
+            Edit::Move {
+                vertices: indices,
+                to,
+                ..
+            } => {
+                for (index, to) in indices.iter().zip(to) {
+                    vertices[*index] = to.clone();
+                }
+            }
+            Edit::AddFace { index, order } => line_draw_order.insert(*index, order.clone()),
+            Edit::DeleteFace { index, .. } => {
+                line_draw_order.remove(*index);
+            }
+        }
+    }
+}
+
+/// Selection and history of an editing session, and where to save the result.
+pub struct Editor {
+    /// Selected vertices, in the order they were picked. A new face goes through them in this order.
+    pub vertices: Vec<usize>,
+    /// Selected face, if any.
+    pub face: Option<usize>,
+    /// Distance selected vertices are moved at a time.
+    pub step: f64,
+    pub path: PathBuf,
+    done: Vec<Edit>,
+    undone: Vec<Edit>,
+    /// Number of [Self::done] edits when last saved, if the history still leads there.
+    saved: Option<usize>,
+}
+
+impl Editor {
+    /// Editor of the given vertices, moving them a tenth of a ground grid cell at a time.
+    pub fn new(path: PathBuf, vertices: &[VectorRow<f64, 3>]) -> Self {
+        Self {
+            vertices: Vec::new(),
+            face: None,
+            step: Bounds::from_vertices(vertices)
+                .map_or(1.0, |bounds| Grid::for_bounds(&bounds).spacing / 10.0),
+            path,
+            done: Vec::new(),
+            undone: Vec::new(),
+            saved: Some(0),
+        }
+    }
+
+    /// Toggle the picked vertex in the selection, and select its face. Nothing picked clears the selection.
+    pub fn select(&mut self, pick: Option<&Pick>) {
+        match pick {
+            Some(pick) => {
+                match self
+                    .vertices
+                    .iter()
+                    .position(|vertex| *vertex == pick.vertex)
+                {
+                    Some(position) => {
+                        self.vertices.remove(position);
+                    }
+                    None => self.vertices.push(pick.vertex),
+                }
+                self.face = pick.face;
+            }
+            None => {
+                self.vertices.clear();
+                self.face = None;
+            }
+        }
+    }
+
+    pub fn selection(&self) -> Selection {
+        Selection {
+            face: self.face,
+            vertices: self.vertices.clone(),
+        }
+    }
+
+    /// Apply an edit, and record it to be undone.
+    fn apply(
+        &mut self,
+        edit: Edit,
+        vertices: &mut [VectorRow<f64, 3>],
+        line_draw_order: &mut Vec<Vec<usize>>,
+    ) {
+        edit.apply(vertices, line_draw_order);
+        self.after(&edit);
+
+        if self.saved > Some(self.done.len()) {
+            // The saved state can no longer be reached.
+            self.saved = None;
+        }
+        self.done.push(edit);
+        self.undone.clear();
+    }
+
+    /// Move the selected vertices, or those of the selected face, by `offset`.
+    pub fn move_selected(
+        &mut self,
+        offset: &VectorRow<f64, 3>,
+        vertices: &mut [VectorRow<f64, 3>],
+        line_draw_order: &mut Vec<Vec<usize>>,
+    ) {
+        let indices = match (self.vertices.is_empty(), self.face) {
+            (false, _) => self.vertices.clone(),
+            (true, Some(face)) => line_draw_order[face].clone(),
+            (true, None) => return,
+        };
+        let from = indices
+            .iter()
+            .map(|&index| vertices[index].clone())
+            .collect::<Vec<_>>();
+        let to = from
+            .iter()
+            .map(|vertex| {
+                VectorRow::from([
+                    vertex[0] + offset[0],
+                    vertex[1] + offset[1],
+                    vertex[2] + offset[2],
+                ])
+            })
+            .collect();
+
+        self.apply(
+            Edit::Move {
+                vertices: indices,
+                from,
+                to,
+            },
+            vertices,
+            line_draw_order,
+        );
+    }
+
+    /// Add a face through the selected vertices, and select it instead.
+    pub fn add_face(
+        &mut self,
+        vertices: &mut [VectorRow<f64, 3>],
+        line_draw_order: &mut Vec<Vec<usize>>,
+    ) -> Result<(), &'static str> {
+        if self.vertices.is_empty() {
+            return Err("Select the vertices of the face first.");
+        }
+
+        let index = line_draw_order.len();
+        let order = std::mem::take(&mut self.vertices);
+        self.apply(Edit::AddFace { index, order }, vertices, line_draw_order);
+        self.face = Some(index);
+        Ok(())
+    }
+
+    pub fn delete_face(
+        &mut self,
+        vertices: &mut [VectorRow<f64, 3>],
+        line_draw_order: &mut Vec<Vec<usize>>,
+    ) -> Result<(), &'static str> {
+        let index = self.face.ok_or("Select a face first.")?;
+        let order = line_draw_order[index].clone();
+        self.apply(Edit::DeleteFace { index, order }, vertices, line_draw_order);
+        Ok(())
+    }
+
+    /// Undo the last edit, if any. Returns true if there was one.
+    pub fn undo(
+        &mut self,
+        vertices: &mut [VectorRow<f64, 3>],
+        line_draw_order: &mut Vec<Vec<usize>>,
+    ) -> bool {
+        let Some(edit) = self.done.pop() else {
+            return false;
+        };
+
+        let inverse = edit.inverse();
+        inverse.apply(vertices, line_draw_order);
+        self.after(&inverse);
+        self.undone.push(edit);
+        true
+    }
+
+    /// Redo the last undone edit, if any. Returns true if there was one.
+    pub fn redo(
+        &mut self,
+        vertices: &mut [VectorRow<f64, 3>],
+        line_draw_order: &mut Vec<Vec<usize>>,
+    ) -> bool {
+        let Some(edit) = self.undone.pop() else {
+            return false;
+        };
+
+        edit.apply(vertices, line_draw_order);
+        self.after(&edit);
+        self.done.push(edit);
+        true
+    }
+
+    /// Keep the selection valid after an edit was applied.
+    fn after(&mut self, edit: &Edit) {
+        match edit {
+            Edit::Move { .. } => (),
+            // Faces after the index moved.
+            Edit::AddFace { .. } | Edit::DeleteFace { .. } => self.face = None,
+        }
+    }
+
+    /// Edits that can be undone, and redone.
+    pub fn history(&self) -> (usize, usize) {
+        (self.done.len(), self.undone.len())
+    }
+
+    /// Whether there are edits since the model was loaded, or last saved.
+    pub fn unsaved(&self) -> bool {
+        self.saved != Some(self.done.len())
+    }
+
+    /// Write the model to [Self::path], as OBJ.
+    pub fn save(
+        &mut self,
+        vertices: &[VectorRow<f64, 3>],
+        line_draw_order: &[Vec<usize>],
+    ) -> Result<(), String> {
+        obj::save(&self.path, vertices, line_draw_order)?;
+        self.saved = Some(self.done.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>) {
+        (
+            vec![
+                VectorRow::from([0.0, 0.0, 0.0]),
+                VectorRow::from([1.0, 0.0, 0.0]),
+                VectorRow::from([1.0, 1.0, 0.0]),
+                VectorRow::from([0.0, 1.0, 0.0]),
+            ],
+            vec![vec![0, 1, 2, 3], vec![0]],
+        )
+    }
+
+    fn pick(face: Option<usize>, vertex: usize) -> Pick {
+        Pick { face, vertex }
+    }
+
+    #[test]
+    fn select() {
+        let (vertices, _) = square();
+        let mut editor = Editor::new(PathBuf::from("square.obj"), &vertices);
+
+        editor.select(Some(&pick(Some(0), 2)));
+        editor.select(Some(&pick(Some(0), 0)));
+        editor.select(Some(&pick(None, 3)));
+        assert!(editor.vertices == [2, 0, 3] && editor.face.is_none());
+
+        // Picked again, deselected.
+        editor.select(Some(&pick(Some(0), 0)));
+        assert!(editor.vertices == [2, 3] && editor.face == Some(0));
+
+        editor.select(None);
+        assert!(editor.selection() == Selection::default());
+    }
+
+    #[test]
+    fn undo_redo() {
+        let (mut vertices, mut line_draw_order) = square();
+        let original = square();
+        let mut editor = Editor::new(PathBuf::from("square.obj"), &vertices);
+        let offset = VectorRow::from([0.25, 0.5, -1.0]);
+
+        // Nothing selected, nothing to do.
+        editor.move_selected(&offset, &mut vertices, &mut line_draw_order);
+        assert!(editor
+            .delete_face(&mut vertices, &mut line_draw_order)
+            .is_err());
+        assert!(editor
+            .add_face(&mut vertices, &mut line_draw_order)
+            .is_err());
+        assert!(editor.history() == (0, 0) && !editor.unsaved());
+
+        editor.select(Some(&pick(Some(0), 1)));
+        editor.move_selected(&offset, &mut vertices, &mut line_draw_order);
+        editor.move_selected(&offset, &mut vertices, &mut line_draw_order);
+        assert!(vertices[1] == VectorRow::from([1.5, 1.0, -2.0]));
+        assert!(vertices[0] == original.0[0]);
+
+        editor.select(Some(&pick(Some(0), 3)));
+        editor.select(Some(&pick(Some(0), 2)));
+        editor
+            .add_face(&mut vertices, &mut line_draw_order)
+            .unwrap();
+        assert!(line_draw_order[2] == [1, 3, 2]);
+        assert!(editor.face == Some(2) && editor.vertices.is_empty());
+
+        editor.face = Some(0);
+        editor
+            .delete_face(&mut vertices, &mut line_draw_order)
+            .unwrap();
+        assert!(line_draw_order == [vec![0], vec![1, 3, 2]]);
+        assert!(editor.history() == (4, 0) && editor.unsaved());
+
+        while editor.undo(&mut vertices, &mut line_draw_order) {}
+        assert!(vertices == original.0 && line_draw_order == original.1);
+        assert!(editor.history() == (0, 4) && !editor.unsaved());
+
+        editor.redo(&mut vertices, &mut line_draw_order);
+        assert!(vertices[1] == VectorRow::from([1.25, 0.5, -1.0]));
+
+        // A new edit drops what was undone.
+        editor.select(None);
+        editor.select(Some(&pick(None, 0)));
+        editor
+            .add_face(&mut vertices, &mut line_draw_order)
+            .unwrap();
+        assert!(editor.history() == (2, 0));
+        assert!(!editor.redo(&mut vertices, &mut line_draw_order));
+    }
+
+    #[test]
+    fn unsaved() {
+        let path = std::env::temp_dir().join(format!("grpe-edit-{}.obj", std::process::id()));
+        let (mut vertices, mut line_draw_order) = square();
+        let mut editor = Editor::new(path.clone(), &vertices);
+
+        editor.select(Some(&pick(None, 0)));
+        editor
+            .add_face(&mut vertices, &mut line_draw_order)
+            .unwrap();
+        editor.save(&vertices, &line_draw_order).unwrap();
+        assert!(!editor.unsaved());
+        assert!(std::fs::read_to_string(&path).unwrap() == obj::write(&vertices, &line_draw_order));
+
+        editor.undo(&mut vertices, &mut line_draw_order);
+        assert!(editor.unsaved());
+        editor.redo(&mut vertices, &mut line_draw_order);
+        assert!(!editor.unsaved());
+
+        // Undone, then replaced by another edit: the saved state is gone for good.
+        editor.undo(&mut vertices, &mut line_draw_order);
+        editor.face = Some(0);
+        editor
+            .delete_face(&mut vertices, &mut line_draw_order)
+            .unwrap();
+        editor.undo(&mut vertices, &mut line_draw_order);
+        assert!(editor.unsaved());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
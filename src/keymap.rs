@@ -0,0 +1,321 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use crate::{config, toml};
+
+/// Everything that can be triggered from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    ToggleAuto,
+    Reset,
+    CycleRenderOption,
+    CycleRenderOptionBack,
+    ToggleViewMode,
+    RotateUp,
+    RotateDown,
+    RotateLeft,
+    RotateRight,
+    FovIncrease,
+    FovDecrease,
+    ZoomIn,
+    ZoomOut,
+    InvertColors,
+    ToggleProfiler,
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    RollLeft,
+    RollRight,
+    SpeedIncrease,
+    SpeedDecrease,
+    FrameAll,
+    SaveView,
+    ToggleGizmo,
+    ToggleGrid,
+    MoveSelectionLeft,
+    MoveSelectionRight,
+    MoveSelectionUp,
+    MoveSelectionDown,
+    MoveSelectionAway,
+    MoveSelectionToward,
+    AddFace,
+    DeleteFace,
+    Undo,
+    Redo,
+    SaveModel,
+    Console,
+}
+
+impl Action {
+    pub const ALL: [Action; 41] = [
+        Action::ToggleAuto,
+        Action::Reset,
+        Action::CycleRenderOption,
+        Action::CycleRenderOptionBack,
+        Action::ToggleViewMode,
+        Action::RotateUp,
+        Action::RotateDown,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::FovIncrease,
+        Action::FovDecrease,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::InvertColors,
+        Action::ToggleProfiler,
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::RollLeft,
+        Action::RollRight,
+        Action::SpeedIncrease,
+        Action::SpeedDecrease,
+        Action::FrameAll,
+        Action::SaveView,
+        Action::ToggleGizmo,
+        Action::ToggleGrid,
+        Action::MoveSelectionLeft,
+        Action::MoveSelectionRight,
+        Action::MoveSelectionUp,
+        Action::MoveSelectionDown,
+        Action::MoveSelectionAway,
+        Action::MoveSelectionToward,
+        Action::AddFace,
+        Action::DeleteFace,
+        Action::Undo,
+        Action::Redo,
+        Action::SaveModel,
+        Action::Console,
+    ];
+
+    /// Whether the automatic rotation keeps going when the action is triggered.
+    pub fn keeps_auto(&self) -> bool {
+        matches!(
+            self,
+            Action::CycleRenderOption
+                | Action::CycleRenderOptionBack
+                | Action::ToggleViewMode
+                | Action::FovIncrease
+                | Action::FovDecrease
+                | Action::InvertColors
+                | Action::ToggleProfiler
+                | Action::SpeedIncrease
+                | Action::SpeedDecrease
+                | Action::FrameAll
+                | Action::SaveView
+                | Action::ToggleGizmo
+                | Action::ToggleGrid
+                | Action::Console
+        )
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::ToggleAuto => write!(f, "toggle_auto"),
+            Action::Reset => write!(f, "reset"),
+            Action::CycleRenderOption => write!(f, "cycle_render_option"),
+            Action::CycleRenderOptionBack => write!(f, "cycle_render_option_back"),
+            Action::ToggleViewMode => write!(f, "toggle_view_mode"),
+            Action::RotateUp => write!(f, "rotate_up"),
+            Action::RotateDown => write!(f, "rotate_down"),
+            Action::RotateLeft => write!(f, "rotate_left"),
+            Action::RotateRight => write!(f, "rotate_right"),
+            Action::FovIncrease => write!(f, "fov_increase"),
+            Action::FovDecrease => write!(f, "fov_decrease"),
+            Action::ZoomIn => write!(f, "zoom_in"),
+            Action::ZoomOut => write!(f, "zoom_out"),
+            Action::InvertColors => write!(f, "invert_colors"),
+            Action::ToggleProfiler => write!(f, "toggle_profiler"),
+            Action::MoveForward => write!(f, "move_forward"),
+            Action::MoveBack => write!(f, "move_back"),
+            Action::MoveLeft => write!(f, "move_left"),
+            Action::MoveRight => write!(f, "move_right"),
+            Action::MoveUp => write!(f, "move_up"),
+            Action::MoveDown => write!(f, "move_down"),
+            Action::RollLeft => write!(f, "roll_left"),
+            Action::RollRight => write!(f, "roll_right"),
+            Action::SpeedIncrease => write!(f, "speed_increase"),
+            Action::SpeedDecrease => write!(f, "speed_decrease"),
+            Action::FrameAll => write!(f, "frame_all"),
+            Action::SaveView => write!(f, "save_view"),
+            Action::ToggleGizmo => write!(f, "toggle_gizmo"),
+            Action::ToggleGrid => write!(f, "toggle_grid"),
+            Action::MoveSelectionLeft => write!(f, "move_selection_left"),
+            Action::MoveSelectionRight => write!(f, "move_selection_right"),
+            Action::MoveSelectionUp => write!(f, "move_selection_up"),
+            Action::MoveSelectionDown => write!(f, "move_selection_down"),
+            Action::MoveSelectionAway => write!(f, "move_selection_away"),
+            Action::MoveSelectionToward => write!(f, "move_selection_toward"),
+            Action::AddFace => write!(f, "add_face"),
+            Action::DeleteFace => write!(f, "delete_face"),
+            Action::Undo => write!(f, "undo"),
+            Action::Redo => write!(f, "redo"),
+            Action::SaveModel => write!(f, "save_model"),
+            Action::Console => write!(f, "console"),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.to_string() == s)
+            .ok_or("Unknown action.")
+    }
+}
+
+/// Maps keys to [Action]s. Loaded from "keys.toml" in the configuration directory, where each
+/// line binds an action to one or more keys, e.g:
+/// ```toml
+/// rotate_up = "w"
+/// zoom_in = ["+", "="]
+/// ```
+/// Actions not mentioned keep their default bindings.
+pub struct Keymap {
+    bindings: HashMap<char, Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::from([
+                ('a', Action::ToggleAuto),
+                ('r', Action::Reset),
+                ('o', Action::CycleRenderOption),
+                ('O', Action::CycleRenderOptionBack),
+                ('v', Action::ToggleViewMode),
+                ('i', Action::RotateUp),
+                ('k', Action::RotateDown),
+                ('j', Action::RotateLeft),
+                ('l', Action::RotateRight),
+                ('F', Action::FovIncrease),
+                ('f', Action::FovDecrease),
+                ('+', Action::ZoomIn),
+                ('-', Action::ZoomOut),
+                ('c', Action::InvertColors),
+                ('p', Action::ToggleProfiler),
+                ('w', Action::MoveForward),
+                ('s', Action::MoveBack),
+                // "a" toggles the automatic rotation, as it always has.
+                ('A', Action::MoveLeft),
+                ('d', Action::MoveRight),
+                ('e', Action::MoveUp),
+                ('q', Action::MoveDown),
+                ('z', Action::RollLeft),
+                ('x', Action::RollRight),
+                (']', Action::SpeedIncrease),
+                ('[', Action::SpeedDecrease),
+                ('h', Action::FrameAll),
+                ('b', Action::SaveView),
+                ('g', Action::ToggleGizmo),
+                ('G', Action::ToggleGrid),
+                ('J', Action::MoveSelectionLeft),
+                ('L', Action::MoveSelectionRight),
+                ('I', Action::MoveSelectionUp),
+                ('K', Action::MoveSelectionDown),
+                ('Y', Action::MoveSelectionAway),
+                ('H', Action::MoveSelectionToward),
+                ('n', Action::AddFace),
+                ('X', Action::DeleteFace),
+                ('u', Action::Undo),
+                ('U', Action::Redo),
+                ('W', Action::SaveModel),
+                (':', Action::Console),
+            ]),
+        }
+    }
+}
+
+impl Keymap {
+    pub fn action(&self, key: char) -> Option<Action> {
+        self.bindings.get(&key).copied()
+    }
+
+    /// Load keymap from file. Defaults are used if there is no file.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = config::dir().map(|dir| dir.join("keys.toml")) else {
+            return Ok(Self::default());
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(s) => Self::from_toml(&s).map_err(|msg| format!("{}: {msg}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {e}", path.display())),
+        }
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, String> {
+        let mut keymap = Self::default();
+
+        for (name, value) in toml::parse(s).map_err(|e| e.to_string())? {
+            let action: Action = name
+                .parse()
+                .map_err(|msg| format!("\"{name}\": {msg}"))?;
+            let keys = value.as_array().unwrap_or(std::slice::from_ref(&value));
+
+            keymap.bindings.retain(|_, bound| *bound != action);
+
+            for key in keys {
+                let mut chars = key.as_str().unwrap_or_default().chars();
+
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => {
+                        keymap.bindings.insert(c, action);
+                    }
+                    _ => return Err(format!("\"{name}\": Keys must be single characters.")),
+                }
+            }
+        }
+
+        Ok(keymap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_names() {
+        for action in Action::ALL {
+            assert!(action.to_string().parse::<Action>() == Ok(action));
+        }
+    }
+
+    #[test]
+    fn rebind() {
+        let keymap = Keymap::from_toml(
+            r#"
+            rotate_up = "w"
+            zoom_in = ["+", "="]
+            reset = []
+            "#,
+        )
+        .unwrap();
+
+        assert!(keymap.action('w') == Some(Action::RotateUp));
+        assert!(keymap.action('i').is_none());
+        assert!(keymap.action('+') == Some(Action::ZoomIn));
+        assert!(keymap.action('=') == Some(Action::ZoomIn));
+        assert!(keymap.action('r').is_none());
+        assert!(keymap.action('k') == Some(Action::RotateDown));
+        assert!(keymap.action('a') == Some(Action::ToggleAuto));
+    }
+
+    #[test]
+    fn bad_bindings() {
+        assert!(Keymap::from_toml("fly = \"x\"").is_err());
+        assert!(Keymap::from_toml("reset = \"xy\"").is_err());
+        assert!(Keymap::from_toml("reset = 1").is_err());
+    }
+}
@@ -1,22 +1,69 @@
 mod arg;
+mod camera;
+mod check;
+mod config;
+mod edit;
+mod keymap;
 mod model;
+mod obj;
+mod scheduler;
 mod state;
+mod toml;
+mod view;
 
-use std::{
-    cell::RefCell,
-    io::{stdout, BufWriter, Write},
-    rc::Rc,
-};
+use std::{cell::RefCell, fmt::Write, rc::Rc};
 
 use io::{platform::unix::EventHandler, EventHandlerTrait};
 use renderer::{
-    renderer::terminal::TerminalBuilder, Camera, ProjectionMode,
-    RendererBuilderTrait, RendererTrait,
+    lod, profile, renderer::terminal::TerminalBuilder, Object, ProjectionMode,
+    RendererBuilderTrait, RendererTrait, VectorRow,
 };
 use state::StateHandler;
 
 fn main() {
-    let args = arg::parse_args();
+    let args = match arg::parse_args() {
+        arg::Command::Run(args) => args,
+        arg::Command::Help => {
+            println!("{}", arg::help());
+            std::process::exit(0);
+        }
+        arg::Command::Version => {
+            println!("grpe {}", env!("CARGO_PKG_VERSION"));
+            std::process::exit(0);
+        }
+        arg::Command::Check(model) => std::process::exit(check_model(&model)),
+    };
+    let mut config = config::Config::load(args.profile.as_deref())
+        .unwrap_or_else(|msg| {
+            println!("Failed to load configuration. {msg}");
+            std::process::exit(1);
+        })
+        .with_args(&args);
+    let keymap = keymap::Keymap::load().unwrap_or_else(|msg| {
+        println!("Failed to load keymap. {msg}");
+        std::process::exit(1);
+    });
+    let views = view::Views::load().unwrap_or_else(|msg| {
+        println!("Failed to load views. {msg}");
+        std::process::exit(1);
+    });
+    let view = args.view.as_deref().map(|name| {
+        views.get(name).cloned().unwrap_or_else(|| {
+            println!("No saved view named \"{name}\".");
+            std::process::exit(1);
+        })
+    });
+    // An existing file to edit is the model, so that saving does not overwrite it with another.
+    let edited = args
+        .edit
+        .as_deref()
+        .filter(|path| path.exists())
+        .map(|path| {
+            obj::load(path).unwrap_or_else(|msg| {
+                println!("Failed to load model. {msg}");
+                std::process::exit(1);
+            })
+        });
 
     // 1. Instantiate IO handler.
     let event_handler = EventHandler::init().expect("Failed to initialize event handler.");
@@ -33,86 +80,154 @@ fn main() {
     // let rotation_prim = rotation.inverse();
 
     // 2. Instantiate renderer.
-    let camera_default = Camera {
-        resolution: args.resolution.unwrap_or((64, 64)),
-        ..Default::default()
-    };
     let mut renderer = TerminalBuilder::default()
-        .with_camera(camera_default.clone())
+        .with_camera(config.camera())
+        .expect("Bad option config.")
+        .with_option(config.render_option.clone())
         .expect("Bad option config.")
         .build()
         .unwrap();
 
     let mut extras = renderer.extras().clone();
-    extras.pixel_width_scaling = 65.5 / 43.5;
+    extras.pixel_width_scaling = config.pixel_aspect;
+    if let Some(threads) = config.threads {
+        extras.threads = threads;
+    }
     renderer.set_extras(extras);
 
-    // 3. Create vertices.
-    let vertices = Rc::new(RefCell::new(
-        args.model
-            .as_ref()
-            .unwrap_or(&model::Model::Plane)
-            .get_vertices(),
-    ));
+    if args.trace.is_some() {
+        // Keep every frame for the trace.
+        renderer.profiler_mut().set_history(usize::MAX);
+    }
+
+    // 3. Create vertices, line order, and the objects they make up (with their levels of detail).
+    // When editing, point clouds are not subsampled, so that all of their points are saved.
+    let mut resolution = renderer.config().camera.resolution;
+    if args.edit.is_none() {
+        config
+            .model
+            .subsample((resolution.0 * resolution.1) as usize);
+    }
+
+    let (vertices, line_draw_order, objects) = match edited {
+        Some((vertices, line_draw_order)) => (vertices, line_draw_order, Vec::new()),
+        None => geometry(&config, args.edit.is_some()),
+    };
+
+    let vertices = Rc::new(RefCell::new(vertices));
     renderer.set_vertices(Rc::clone(&vertices));
 
     // 4. Define line order.
-    let line_draw_order = Rc::new(RefCell::new(
-        args.model
-            .as_ref()
-            .unwrap_or(&model::Model::Plane)
-            .get_line_draw_order(),
-    ));
+    let line_draw_order = Rc::new(RefCell::new(line_draw_order));
     renderer.set_vertices_line_draw_order(Rc::clone(&line_draw_order));
+    renderer.set_objects(objects);
 
     // 5. Create a state handler.
-    let mut state = StateHandler::new(args, event_handler, vertices, line_draw_order);
+    let mut state = StateHandler::new(
+        config,
+        keymap,
+        views,
+        event_handler,
+        vertices,
+        line_draw_order,
+    );
+
+    if let Some(path) = args.edit.clone() {
+        let editor = edit::Editor::new(path, &state.vertices.borrow());
+        state.edit(editor);
+    }
+
+    if let Some(view) = view.as_ref() {
+        let mut config = renderer.config().clone();
+        state.show_view(view, &mut config.camera);
+        renderer = renderer.set_config(config).expect("Bad configuration.");
+    }
 
     // 6. Engine loop
+    let mut profiler_shown = false;
+
     while state.event_handler.running() {
         let updated_config = state.update(renderer.config().clone());
-        let mut writer = BufWriter::new(stdout().lock());
+
+        // The resolution follows the terminal, whose size is only known once it reports it. The
+        // model may also be changed from the console.
+        let model_changed = state.take_model_changed();
+        if updated_config.camera.resolution != resolution || model_changed {
+            resolution = updated_config.camera.resolution;
+
+            let subsampled = args.edit.is_none()
+                && state
+                    .config
+                    .model
+                    .subsample((resolution.0 * resolution.1) as usize);
+            if subsampled || model_changed {
+                let (vertices, line_draw_order, objects) = geometry(&state.config, false);
+                state.set_geometry(vertices, line_draw_order);
+                renderer.set_vertices(Rc::clone(&state.vertices));
+                renderer.set_objects(objects);
+            }
+        }
+
+        if let Some((x, y)) = state.take_click() {
+            // Picked from the previous frame, which is the one that was clicked.
+            state.select(renderer.pick(x, y));
+        }
+        let mut hud = String::new();
 
         let banner_text = "GRPE";
         let banner_fill_width =
             (updated_config.camera.resolution.0 as usize - banner_text.len()) / 2 - 1; // Note: "-1" for extra space(s).
         let banner_char = "=";
         let banner = banner_char.repeat(banner_fill_width);
-        write!(writer, "\x1B[H\x1B[2K").unwrap();
+        write!(hud, "\x1B[H\x1B[2K").unwrap();
         write!(
-            writer,
+            hud,
             "\x1B[1;38;2;0;0;0;48;2;255;255;0m{banner} {banner_text} {banner}\x1B[0m"
         )
         .unwrap();
         if updated_config.camera.resolution.0 % 2 != 0 {
             // Just make it nice even if odd.
+            write!(hud, "\x1B[1;38;2;0;0;0;48;2;255;255;0m{banner_char}\x1B[0m").unwrap();
+        }
+
+        // The console, when open, takes the place of the info line.
+        write!(
+            hud,
+            "\x1B[{};H\x1B[2K",
+            (updated_config.camera.resolution.1 + 4) / 2
+        )
+        .unwrap();
+        if let Some(console) = state.console() {
+            let line = match console.error {
+                Some(msg) => format!(":{} | {msg}", console.line),
+                None => format!(":{}", console.line),
+            };
             write!(
-                writer,
-                "\x1B[1;38;2;0;0;0;48;2;255;255;0m{banner_char}\x1B[0m"
+                hud,
+                "{}",
+                line.chars()
+                    .take(updated_config.camera.resolution.0 as usize)
+                    .collect::<String>()
             )
             .unwrap();
-        }
-
-        if let ProjectionMode::Perspective { fov } = updated_config.camera.projection_mode {
-            if state.args.info.is_some() {
-                write!(
-                    writer,
-                    "\x1B[{};H\x1B[2K",
-                    (updated_config.camera.resolution.1 + 4) / 2
-                )
-                .unwrap();
-                let info  = format!("FPS: {:0>4} | Events handled: {:0>10} | Resolution: ({},{}) | FOV: {:0>3} | Camera Rotation: (Pitch: {:.2}, Yaw: {:.2}) | Camera Position: ({:.2},{:.2},{:.2}) | ViewMode: {} | RenderOption: {}",
+        } else if let ProjectionMode::Perspective { fov } = updated_config.camera.projection_mode {
+            if state.config.info {
+                let info  = format!("FPS: {:0>4} | Dropped: {:0>6} | Events handled: {:0>10} | Resolution: ({},{}) | FOV: {:0>3} | Camera Rotation: (Pitch: {:.2}, Yaw: {:.2}) | Camera Position: ({:.2},{:.2},{:.2}) | Speed: {:.0} | ViewMode: {} | RenderOption: {}",
                     state.info().fps_smoothened,
+                    state.info().dropped_frames,
                     state.info().event_count,
                     updated_config.camera.resolution.0, updated_config.camera.resolution.1, fov,
                     state.info().rotation.0, state.info().rotation.1,
                     updated_config.camera.position[0],  updated_config.camera.position[1], updated_config.camera.position[2],
+                    state.info().fly_speed,
                     state.info().view_mode, state.info().render_option,
                 );
                 write!(
-                    writer,
+                    hud,
                     "{}",
                     info.chars()
+                        .chain(selection_info(state.info().selection.as_ref()).chars())
+                        .chain(edit_info(state.editor(), state.info().edit_error).chars())
                         .take(updated_config.camera.resolution.0 as usize)
                         .collect::<String>()
                 )
@@ -120,20 +235,167 @@ fn main() {
             }
         }
 
-        writer.flush().unwrap();
-        drop(writer);
-
-        if state.info().invert_colors {
-            println!("\x1B[H\x1B[7m"); // Invert colors. (Move to first row before printing/receiving, because it will be cleared anyway.)
+        if state.info().profiler {
+            write_profiler_overlay(
+                &mut hud,
+                renderer.profiler(),
+                updated_config.camera.resolution.0 as usize,
+            );
+        } else if profiler_shown {
+            // Clear the overlay from the canvas.
+            renderer.redraw();
         }
+        profiler_shown = state.info().profiler;
+
+        // Query the terminal for its size, answered with an event (see `state.update`). Part of the
+        // frame, rather than written on its own between synchronized updates.
+        write!(hud, "\x1B[18t").unwrap();
+
+        let mut extras = renderer.extras().clone();
+        extras.invert_colors = state.info().invert_colors;
+        extras.synchronized_output = state.info().synchronized_output;
+        extras.gizmo = state.info().gizmo;
+        extras.grid = state.info().grid.clone();
+        extras.selection = state.selection();
+        renderer.set_extras(extras);
+        renderer.set_hud(hud);
 
         renderer = renderer
             .set_config(updated_config)
             .expect("Bad configuration.");
         renderer.render();
+    }
+
+    // Written before leaving the alternate screen, but reported after, to be seen.
+    let trace = args.trace.as_ref().map(|path| {
+        let format = profile::Format::from_path(path).expect("Checked when parsing arguments.");
+        std::fs::File::create(path)
+            .map(std::io::BufWriter::new)
+            .and_then(|mut file| renderer.profiler().write(format, &mut file))
+            .map_err(|e| format!("{}: {e}", path.display()))
+    });
+    drop(renderer);
+
+    if let Some(editor) = state.editor().filter(|editor| editor.unsaved()) {
+        println!("Edits were not saved to {}.", editor.path.display());
+    }
 
-        println!("\x1B[H\x1B[0m"); // Restore style . (Move to first row before printing/receiving, because it will be cleared anyway.)
+    for msg in state.errors.iter() {
+        println!("{msg}");
+    }
+
+    if let Some(Err(msg)) = trace {
+        println!("Failed to write trace. {msg}");
+        std::process::exit(1);
     }
 
     std::thread::sleep(std::time::Duration::from_millis(100));
 }
+
+/// Mean and worst time of each render stage over the last second or so, in the top left corner.
+fn write_profiler_overlay(hud: &mut String, profiler: &profile::Profiler, width: usize) {
+    const FRAMES: usize = 60;
+
+    let lines = std::iter::once(format!(
+        "{:<30} {:>9} {:>9}",
+        format!("Stage (last {FRAMES} frames)"),
+        "mean",
+        "max"
+    ))
+    .chain(profiler.summary(FRAMES).into_iter().map(|summary| {
+        format!(
+            "{:<30} {:>6.2} ms {:>6.2} ms",
+            summary.name,
+            summary.mean.as_secs_f64() * 1000.0,
+            summary.max.as_secs_f64() * 1000.0
+        )
+    }));
+
+    for (row, line) in lines.enumerate() {
+        let line = line.chars().take(width).collect::<String>();
+        write!(
+            hud,
+            "\x1B[{};1H\x1B[38;2;0;0;0;48;2;255;255;0m{line}\x1B[0m",
+            row + 2
+        )
+        .unwrap();
+    }
+}
+
+/// The selected face, vertex and its position, to follow the rest of the info line.
+fn selection_info(selection: Option<&(renderer::Pick, renderer::VectorRow<f64, 3>)>) -> String {
+    match selection {
+        Some((pick, position)) => format!(
+            " | Selected: (Face: {}, Vertex: {}, Position: ({:.2},{:.2},{:.2}))",
+            pick.face.map_or("-".to_string(), |face| face.to_string()),
+            pick.vertex,
+            position[0],
+            position[1],
+            position[2]
+        ),
+        None => String::new(),
+    }
+}
+
+/// Report the problems of a model. Returns the exit code, 1 if there are any.
+fn check_model(model: &model::Model) -> i32 {
+    let (vertices, line_draw_order) = model.generate();
+    let objects = model.get_objects(&vertices, &line_draw_order);
+    let problems = check::check(&vertices, &line_draw_order, &objects);
+    print!(
+        "{}",
+        check::report(&model.to_string(), &vertices, &line_draw_order, &problems)
+    );
+
+    match problems.is_empty() {
+        true => 0,
+        false => 1,
+    }
+}
+
+/// Vertices, line draw order and the objects they make up (with their levels of detail), of the
+/// configured model.
+fn geometry(
+    config: &config::Config,
+    edit: bool,
+) -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>, Vec<Object>) {
+    let (mut vertices, mut line_draw_order) = config.model.generate();
+    let mut objects = config.model.get_objects(&vertices, &line_draw_order);
+
+    // When editing, the model is neither simplified nor culled, as both depend on where its vertices
+    // were when loaded.
+    if edit {
+        objects.clear();
+    }
+
+    if config.lod {
+        for object in objects.iter_mut() {
+            lod::add_levels(
+                object,
+                &mut vertices,
+                &mut line_draw_order,
+                &lod::DETAILS_DEFAULT,
+            );
+        }
+    }
+
+    (vertices, line_draw_order, objects)
+}
+
+/// Selection, history and whether there are unsaved edits, when editing.
+fn edit_info(editor: Option<&edit::Editor>, error: Option<&str>) -> String {
+    let Some(editor) = editor else {
+        return String::new();
+    };
+    let (undo, redo) = editor.history();
+
+    format!(
+        " | Edit: (Selected: {}, Undo: {undo}, Redo: {redo}{}){}",
+        editor.vertices.len(),
+        match editor.unsaved() {
+            true => ", Unsaved",
+            false => "",
+        },
+        error.map_or(String::new(), |msg| format!(" {msg}"))
+    )
+}
@@ -0,0 +1,534 @@
+/// Point clouds, and meshes, read from files: PLY (ASCII or binary little-endian), "x y z" text
+/// (".xyz"), and CSV with the columns to use, e.g. "scan.csv:x=lon,y=lat,z=height". Colours and
+/// other properties of PLY vertices are read past, as the renderer draws without colour.
+use std::{fmt::Display, path::Path, str::FromStr};
+
+use renderer::VectorRow;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cloud {
+    vertices: Vec<VectorRow<f64, 3>>,
+    /// Faces of a PLY file, if any. Without, each vertex is drawn as a point.
+    faces: Vec<Vec<usize>>,
+    /// Most points to draw, see [Cloud::subsample].
+    limit: usize,
+    /// As given, to be shown.
+    source: String,
+}
+
+/// Vertices, and faces.
+type Mesh = (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>);
+
+/// A column of a CSV file, by number (from 1) or by name in the header.
+#[derive(Clone, Debug, PartialEq)]
+enum Column {
+    Number(usize),
+    Name(String),
+}
+
+impl Cloud {
+    /// Whether a model is to be read from a file, by its extension.
+    pub fn is_cloud(s: &str) -> bool {
+        let path = match s.rsplit_once(':') {
+            Some((path, _)) if Self::extension(path).as_deref() == Some("csv") => path,
+            _ => s,
+        };
+
+        matches!(
+            Self::extension(path).as_deref(),
+            Some("ply" | "xyz" | "csv")
+        )
+    }
+
+    fn extension(path: &str) -> Option<String> {
+        Path::new(path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+    }
+
+    pub fn generate(&self) -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>) {
+        if !self.faces.is_empty() {
+            return (self.vertices.clone(), self.faces.clone());
+        }
+
+        let (count, points) = (self.vertices.len(), self.points());
+        // Evenly spread through the file.
+        let vertices = (0..points)
+            .map(|point| self.vertices[point * count / points].clone())
+            .collect();
+
+        (vertices, (0..points).map(|index| vec![index]).collect())
+    }
+
+    /// Points drawn of a point cloud.
+    fn points(&self) -> usize {
+        self.vertices.len().min(self.limit)
+    }
+
+    /// Draw at most `points` of a point cloud, as there is little use in more points than pixels.
+    /// Meshes are drawn whole. Returns true if the number of points drawn changed.
+    pub fn subsample(&mut self, points: usize) -> bool {
+        let before = self.points();
+        self.limit = points.max(1);
+        self.faces.is_empty() && self.points() != before
+    }
+}
+
+impl Display for Cloud {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for Cloud {
+    type Err = &'static str;
+
+    /// `<file>`, or `<file>.csv:x=<column>,y=<column>,z=<column>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, columns) = match s.rsplit_once(':') {
+            Some((path, columns)) if Self::extension(path).as_deref() == Some("csv") => {
+                (path, Some(columns))
+            }
+            _ => (s, None),
+        };
+        let bytes = std::fs::read(path).map_err(|_| "Could not read the file.")?;
+
+        let (vertices, faces) = match Self::extension(path).as_deref() {
+            Some("ply") => ply(&bytes)?,
+            Some("xyz") => (xyz(text(&bytes)?)?, Vec::new()),
+            Some("csv") => {
+                let mut selected = [1, 2, 3].map(Column::Number);
+
+                for column in columns
+                    .unwrap_or_default()
+                    .split(',')
+                    .filter(|c| !c.is_empty())
+                {
+                    let (axis, column) = column
+                        .split_once('=')
+                        .ok_or("Expected columns as x=<column>,y=<column>,z=<column>.")?;
+                    let axis = ["x", "y", "z"]
+                        .iter()
+                        .position(|name| *name == axis)
+                        .ok_or("Expected columns of x, y or z.")?;
+                    selected[axis] = match column.parse() {
+                        Ok(0) => return Err("Columns are numbered from 1."),
+                        Ok(number) => Column::Number(number),
+                        Err(_) => Column::Name(column.to_string()),
+                    };
+                }
+
+                (csv(text(&bytes)?, &selected)?, Vec::new())
+            }
+            _ => return Err("Expected a \".ply\", \".xyz\" or \".csv\" file."),
+        };
+
+        if vertices.is_empty() {
+            return Err("No points in the file.");
+        }
+
+        Ok(Self {
+            vertices,
+            faces,
+            limit: usize::MAX,
+            source: s.to_string(),
+        })
+    }
+}
+
+fn text(bytes: &[u8]) -> Result<&str, &'static str> {
+    std::str::from_utf8(bytes).map_err(|_| "Expected a text file.")
+}
+
+/// Lines of "x y z", separated by whitespace. Further columns (e.g. colour, intensity) are ignored,
+/// as are empty lines and comments ("#").
+fn xyz(s: &str) -> Result<Vec<VectorRow<f64, 3>>, &'static str> {
+    s.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut values = line.split_whitespace().map(|value| value.parse::<f64>());
+            match [values.next(), values.next(), values.next()] {
+                [Some(Ok(x)), Some(Ok(y)), Some(Ok(z))] => Ok(VectorRow::from([x, y, z])),
+                _ => Err("Expected \"x y z\" on each line."),
+            }
+        })
+        .collect()
+}
+
+/// Comma separated values, with an optional header naming the columns. A first line which is not
+/// all numbers is taken to be the header.
+fn csv(s: &str, columns: &[Column; 3]) -> Result<Vec<VectorRow<f64, 3>>, &'static str> {
+    let split = |line: &str| {
+        line.split(',')
+            .map(|field| field.trim().trim_matches('"').to_string())
+            .collect::<Vec<_>>()
+    };
+    let mut lines = s.lines().filter(|line| !line.trim().is_empty()).peekable();
+    let header = lines
+        .next_if(|line| {
+            split(line)
+                .iter()
+                .any(|field| field.parse::<f64>().is_err())
+        })
+        .map(split);
+
+    let indices = columns
+        .iter()
+        .map(|column| match column {
+            Column::Number(number) => Ok(number - 1),
+            Column::Name(name) => header
+                .as_ref()
+                .ok_or("Columns may only be named if the file has a header.")?
+                .iter()
+                .position(|field| field == name)
+                .ok_or("No column of that name in the header."),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    lines
+        .map(|line| {
+            let fields = split(line);
+            let value = |index: usize| fields.get(index)?.parse::<f64>().ok();
+            match [value(indices[0]), value(indices[1]), value(indices[2])] {
+                [Some(x), Some(y), Some(z)] => Ok(VectorRow::from([x, y, z])),
+                _ => Err("Expected a number in each selected column."),
+            }
+        })
+        .collect()
+}
+
+/// Type of a PLY property, by its size in bytes and whether it is a float, signed or unsigned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Scalar {
+    Int(usize),
+    Uint(usize),
+    Float(usize),
+}
+
+impl FromStr for Scalar {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "char" | "int8" => Ok(Scalar::Int(1)),
+            "uchar" | "uint8" => Ok(Scalar::Uint(1)),
+            "short" | "int16" => Ok(Scalar::Int(2)),
+            "ushort" | "uint16" => Ok(Scalar::Uint(2)),
+            "int" | "int32" => Ok(Scalar::Int(4)),
+            "uint" | "uint32" => Ok(Scalar::Uint(4)),
+            "float" | "float32" => Ok(Scalar::Float(4)),
+            "double" | "float64" => Ok(Scalar::Float(8)),
+            _ => Err("Unknown PLY property type."),
+        }
+    }
+}
+
+enum Property {
+    Scalar(String, Scalar),
+    /// Type of the length, and of the items.
+    List(String, Scalar, Scalar),
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Values of the body of a PLY file, one after another.
+struct Body<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    binary: bool,
+}
+
+impl Body<'_> {
+    fn read(&mut self, scalar: Scalar) -> Result<f64, &'static str> {
+        if !self.binary {
+            let rest = &self.bytes[self.position..];
+            let start = rest
+                .iter()
+                .position(|byte| !byte.is_ascii_whitespace())
+                .ok_or("Unexpected end of PLY data.")?;
+            let length = rest[start..]
+                .iter()
+                .position(|byte| byte.is_ascii_whitespace())
+                .unwrap_or(rest.len() - start);
+            self.position += start + length;
+
+            return text(&rest[start..start + length])?
+                .parse()
+                .map_err(|_| "Expected a number in the PLY data.");
+        }
+
+        let size = match scalar {
+            Scalar::Int(size) | Scalar::Uint(size) | Scalar::Float(size) => size,
+        };
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + size)
+            .ok_or("Unexpected end of PLY data.")?;
+        self.position += size;
+
+        // Little-endian: widen to 8 bytes, sign extending integers.
+        let mut wide = [0; 8];
+        wide[..size].copy_from_slice(bytes);
+        if let Scalar::Int(_) = scalar {
+            if bytes[size - 1] & 0x80 != 0 {
+                wide[size..].fill(0xFF);
+            }
+        }
+
+        Ok(match scalar {
+            Scalar::Int(_) => i64::from_le_bytes(wide) as f64,
+            Scalar::Uint(_) => u64::from_le_bytes(wide) as f64,
+            Scalar::Float(4) => f32::from_le_bytes([wide[0], wide[1], wide[2], wide[3]]) as f64,
+            Scalar::Float(_) => f64::from_le_bytes(wide),
+        })
+    }
+}
+
+/// Length of a PLY list, or a vertex index in one.
+fn whole(value: f64) -> Result<usize, &'static str> {
+    match value >= 0.0 && value.fract() == 0.0 {
+        true => Ok(value as usize),
+        false => Err("Negative or fractional index in the PLY file."),
+    }
+}
+
+/// Vertices, and faces if any, of a PLY file.
+fn ply(bytes: &[u8]) -> Result<Mesh, &'static str> {
+    const END: &[u8] = b"end_header";
+    let invalid = "Invalid PLY header.";
+    let end = bytes
+        .windows(END.len())
+        .position(|window| window == END)
+        .ok_or(invalid)?;
+    let header = text(&bytes[..end]).map_err(|_| invalid)?;
+    // The body starts on the line after "end_header".
+    let start = bytes[end..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(bytes.len(), |newline| end + newline + 1);
+
+    let mut lines = header.lines().map(str::split_whitespace);
+    if lines.next().and_then(|mut words| words.next()) != Some("ply") {
+        return Err(invalid);
+    }
+
+    let mut binary = None;
+    let mut elements: Vec<Element> = Vec::new();
+
+    for words in lines {
+        match words.collect::<Vec<_>>().as_slice() {
+            ["format", "ascii", _] => binary = Some(false),
+            ["format", "binary_little_endian", _] => binary = Some(true),
+            ["format", ..] => {
+                return Err("Unsupported PLY format, expected ASCII or binary little-endian.")
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| invalid)?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", length, item, name] => elements
+                .last_mut()
+                .ok_or(invalid)?
+                .properties
+                .push(Property::List(
+                    name.to_string(),
+                    length.parse()?,
+                    item.parse()?,
+                )),
+            ["property", scalar, name] => elements
+                .last_mut()
+                .ok_or(invalid)?
+                .properties
+                .push(Property::Scalar(name.to_string(), scalar.parse()?)),
+            ["comment" | "obj_info", ..] | [] => (),
+            _ => return Err(invalid),
+        }
+    }
+
+    let mut body = Body {
+        bytes: &bytes[start..],
+        position: 0,
+        binary: binary.ok_or(invalid)?,
+    };
+    let mut vertices = Vec::new();
+    let mut faces = Vec::new();
+
+    for element in elements.iter() {
+        for _ in 0..element.count {
+            let mut point = [0.0; 3];
+            let mut indices = Vec::new();
+
+            for property in element.properties.iter() {
+                match property {
+                    Property::Scalar(name, scalar) => {
+                        let value = body.read(*scalar)?;
+                        if let Some(axis) = ["x", "y", "z"].iter().position(|axis| axis == name) {
+                            point[axis] = value;
+                        }
+                    }
+                    Property::List(name, length, item) => {
+                        let length = whole(body.read(*length)?)?;
+                        let values = (0..length)
+                            .map(|_| body.read(*item))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        if name == "vertex_indices" || name == "vertex_index" {
+                            indices = values.into_iter().map(whole).collect::<Result<_, _>>()?;
+                        }
+                    }
+                }
+            }
+
+            match element.name.as_str() {
+                "vertex" => vertices.push(VectorRow::from(point)),
+                "face" => faces.push(indices),
+                _ => (),
+            }
+        }
+    }
+
+    if faces.iter().flatten().any(|index| *index >= vertices.len()) {
+        return Err("Face of a vertex not in the PLY file.");
+    }
+
+    Ok((vertices, faces))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(vertices: &[VectorRow<f64, 3>]) -> Vec<[f64; 3]> {
+        vertices
+            .iter()
+            .map(|vertex| [vertex[0], vertex[1], vertex[2]])
+            .collect()
+    }
+
+    #[test]
+    fn xyz_and_csv() {
+        let vertices = xyz("# Scan\n1 2 3\n\n-1.5 0 2e3 255 0 0\n").unwrap();
+        assert!(points(&vertices) == [[1.0, 2.0, 3.0], [-1.5, 0.0, 2000.0]]);
+        assert!(xyz("1 2\n").is_err());
+
+        let s = "id,lat,lon,\"height\"\n7,1,2,3\n8,4,5,6\n";
+        let columns = [
+            Column::Name("lon".into()),
+            Column::Number(2),
+            Column::Name("height".into()),
+        ];
+        assert!(points(&csv(s, &columns).unwrap()) == [[2.0, 1.0, 3.0], [5.0, 4.0, 6.0]]);
+
+        // Without a header, columns are numbered.
+        let columns = [1, 2, 3].map(Column::Number);
+        assert!(points(&csv("1,2,3\n4,5,6", &columns).unwrap()).len() == 2);
+        assert!(csv(
+            "1,2,3\n4,5,6",
+            &[
+                Column::Name("x".into()),
+                Column::Number(2),
+                Column::Number(3)
+            ]
+        )
+        .is_err());
+        assert!(csv("x,y\n1,2", &columns).is_err());
+    }
+
+    #[test]
+    fn ply_ascii() {
+        let s = "ply\n\
+                 format ascii 1.0\n\
+                 comment Coloured triangle.\n\
+                 element vertex 3\n\
+                 property float x\n\
+                 property float y\n\
+                 property float z\n\
+                 property uchar red\n\
+                 property uchar green\n\
+                 property uchar blue\n\
+                 element face 1\n\
+                 property list uchar int vertex_indices\n\
+                 end_header\n\
+                 0 0 0 255 0 0\n\
+                 1 0 0 0 255 0\n\
+                 0 1 0.5 0 0 255\n\
+                 3 0 1 2\n";
+        let (vertices, faces) = ply(s.as_bytes()).unwrap();
+        assert!(points(&vertices) == [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.5]]);
+        assert!(faces == [vec![0, 1, 2]]);
+
+        assert!(ply(s.replace("0 1 2", "0 1 3").as_bytes()).is_err());
+        let error = "Negative or fractional index in the PLY file.";
+        assert!(ply(s.replace("0 1 2", "0 -1 2").as_bytes()) == Err(error));
+        assert!(ply(s.replace("0 1 2", "0 1.5 2").as_bytes()) == Err(error));
+        assert!(ply(s.replace("ascii", "binary_big_endian").as_bytes()).is_err());
+        assert!(ply(s.replace("0 1 0.5 0 0 255\n", "").as_bytes()).is_err());
+    }
+
+    #[test]
+    fn ply_binary() {
+        let mut bytes = b"ply\n\
+                          format binary_little_endian 1.0\n\
+                          element vertex 2\n\
+                          property double x\n\
+                          property float y\n\
+                          property short z\n\
+                          element face 1\n\
+                          property list uchar uint vertex_indices\n\
+                          end_header\n"
+            .to_vec();
+        for (x, y, z) in [(1.5f64, -2.0f32, -3i16), (4.0, 5.0, 6)] {
+            bytes.extend(x.to_le_bytes());
+            bytes.extend(y.to_le_bytes());
+            bytes.extend(z.to_le_bytes());
+        }
+        bytes.push(2);
+        bytes.extend([1u32, 0].map(u32::to_le_bytes).concat());
+
+        let (vertices, faces) = ply(&bytes).unwrap();
+        assert!(points(&vertices) == [[1.5, -2.0, -3.0], [4.0, 5.0, 6.0]]);
+        assert!(faces == [vec![1, 0]]);
+
+        bytes.pop();
+        assert!(ply(&bytes).is_err());
+    }
+
+    #[test]
+    fn subsample() {
+        let path = std::env::temp_dir().join(format!("grpe-cloud-{}.csv", std::process::id()));
+        let s = (0..10)
+            .map(|i| format!("{i},0,{}\n", i * 2))
+            .collect::<String>();
+        std::fs::write(&path, s).unwrap();
+
+        let source = format!("{}:x=3,z=1", path.display());
+        assert!(Cloud::is_cloud(&source));
+        let mut cloud: Cloud = source.parse().unwrap();
+        assert!(cloud.to_string() == source);
+        assert!(cloud.vertices[1] == VectorRow::from([2.0, 0.0, 1.0]));
+
+        assert!(!cloud.subsample(20));
+        assert!(cloud.generate().0.len() == 10);
+        assert!(cloud.subsample(4));
+        let (vertices, line_draw_order) = cloud.generate();
+        assert!(points(&vertices)
+            .iter()
+            .map(|point| point[2])
+            .eq([0.0, 2.0, 5.0, 7.0]));
+        assert!(line_draw_order == [vec![0], vec![1], vec![2], vec![3]]);
+
+        // All points again, given room.
+        assert!(!cloud.subsample(4) && cloud.subsample(30));
+        assert!(cloud.generate().0.len() == 10);
+
+        assert!(format!("{}:x=0", path.display()).parse::<Cloud>().is_err());
+        assert!(format!("{}:w=1", path.display()).parse::<Cloud>().is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(source.parse::<Cloud>().is_err());
+    }
+}
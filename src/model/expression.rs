@@ -0,0 +1,300 @@
+//! Arithmetic expressions of a few named variables, e.g. "sin(x) * cos(y) ^ 2". Supports numbers
+//! (also as "1.5e-3"), the constants pi and e, "+", "-", "*", "/", "^" (right associative, before a leading "-"),
+//! parentheses, and the functions in [FUNCTIONS].
+
+/// Function of one argument, and its name.
+type Function = (&'static str, fn(f64) -> f64);
+
+const FUNCTIONS: [Function; 16] = [
+    ("sin", f64::sin),
+    ("cos", f64::cos),
+    ("tan", f64::tan),
+    ("asin", f64::asin),
+    ("acos", f64::acos),
+    ("atan", f64::atan),
+    ("sinh", f64::sinh),
+    ("cosh", f64::cosh),
+    ("tanh", f64::tanh),
+    ("sqrt", f64::sqrt),
+    ("abs", f64::abs),
+    ("exp", f64::exp),
+    ("ln", f64::ln),
+    ("log", f64::log10),
+    ("floor", f64::floor),
+    ("ceil", f64::ceil),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    Number(f64),
+    /// Index into the variables the expression was parsed with.
+    Variable(usize),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    /// Index into [FUNCTIONS], and the argument.
+    Call(usize, Box<Expression>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+impl Expression {
+    /// Parse an expression, where each of `variables` stands for the value at the same index given
+    /// to [Self::evaluate].
+    pub fn parse(s: &str, variables: &[&str]) -> Result<Self, &'static str> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+            variables,
+        };
+        let expression = parser.sum()?;
+
+        match parser.peek() {
+            None => Ok(expression),
+            Some(Token::Symbol(')')) => Err("Unbalanced parentheses."),
+            Some(_) => Err("Expected an operator."),
+        }
+    }
+
+    pub fn evaluate(&self, values: &[f64]) -> f64 {
+        match self {
+            Expression::Number(number) => *number,
+            Expression::Variable(index) => values[*index],
+            Expression::Negate(expression) => -expression.evaluate(values),
+            Expression::Binary(operator, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(values), rhs.evaluate(values));
+                match operator {
+                    Operator::Add => lhs + rhs,
+                    Operator::Subtract => lhs - rhs,
+                    Operator::Multiply => lhs * rhs,
+                    Operator::Divide => lhs / rhs,
+                    Operator::Power => lhs.powf(rhs),
+                }
+            }
+            Expression::Call(function, argument) => {
+                FUNCTIONS[*function].1(argument.evaluate(values))
+            }
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                number.push(c);
+            }
+            // An exponent only if digits follow, so that "2e" is still the number and the constant.
+            let mut exponent = chars.clone();
+            if exponent.next_if(|c| matches!(c, 'e' | 'E')).is_some() {
+                let sign = exponent.next_if(|c| matches!(c, '+' | '-'));
+                if exponent.peek().is_some_and(char::is_ascii_digit) {
+                    number.push('e');
+                    number.extend(sign);
+                    while let Some(c) = exponent.next_if(char::is_ascii_digit) {
+                        number.push(c);
+                    }
+                    chars = exponent;
+                }
+            }
+            tokens.push(Token::Number(
+                number.parse().map_err(|_| "Expected a number.")?,
+            ));
+        } else if c.is_alphabetic() {
+            let mut name = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                name.push(c);
+            }
+            tokens.push(Token::Name(name));
+        } else if "+-*/^()".contains(c) {
+            chars.next();
+            tokens.push(Token::Symbol(c));
+        } else {
+            return Err("Unexpected character.");
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent, one method per level of precedence.
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    variables: &'a [&'a str],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Consume the next token if it is one of the symbols.
+    fn symbol(&mut self, symbols: &str) -> Option<char> {
+        match self.peek() {
+            Some(Token::Symbol(symbol)) if symbols.contains(*symbol) => {
+                let symbol = *symbol;
+                self.position += 1;
+                Some(symbol)
+            }
+            _ => None,
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expression, &'static str> {
+        let mut expression = self.product()?;
+
+        while let Some(symbol) = self.symbol("+-") {
+            let operator = match symbol {
+                '+' => Operator::Add,
+                _ => Operator::Subtract,
+            };
+            expression =
+                Expression::Binary(operator, Box::new(expression), Box::new(self.product()?));
+        }
+
+        Ok(expression)
+    }
+
+    fn product(&mut self) -> Result<Expression, &'static str> {
+        let mut expression = self.negation()?;
+
+        while let Some(symbol) = self.symbol("*/") {
+            let operator = match symbol {
+                '*' => Operator::Multiply,
+                _ => Operator::Divide,
+            };
+            expression =
+                Expression::Binary(operator, Box::new(expression), Box::new(self.negation()?));
+        }
+
+        Ok(expression)
+    }
+
+    fn negation(&mut self) -> Result<Expression, &'static str> {
+        match self.symbol("-") {
+            Some(_) => Ok(Expression::Negate(Box::new(self.negation()?))),
+            None => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Expression, &'static str> {
+        let base = self.atom()?;
+
+        match self.symbol("^") {
+            // The exponent may be negated, e.g. "2^-x".
+            Some(_) => Ok(Expression::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(self.negation()?),
+            )),
+            None => Ok(base),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expression, &'static str> {
+        let token = self.peek().cloned().ok_or("Expected an expression.")?;
+        self.position += 1;
+
+        match token {
+            Token::Number(number) => Ok(Expression::Number(number)),
+            Token::Symbol('(') => self.parenthesized(),
+            Token::Name(name) => {
+                if let Some(index) = self.variables.iter().position(|variable| *variable == name) {
+                    return Ok(Expression::Variable(index));
+                }
+
+                match name.as_str() {
+                    "pi" => Ok(Expression::Number(std::f64::consts::PI)),
+                    "e" => Ok(Expression::Number(std::f64::consts::E)),
+                    _ => {
+                        let function = FUNCTIONS
+                            .iter()
+                            .position(|(function, _)| *function == name)
+                            .ok_or("Unknown variable or function.")?;
+                        self.symbol("(").ok_or("Expected \"(\" after function.")?;
+                        Ok(Expression::Call(function, Box::new(self.parenthesized()?)))
+                    }
+                }
+            }
+            Token::Symbol(')') => Err("Unbalanced parentheses."),
+            Token::Symbol(_) => Err("Expected an expression."),
+        }
+    }
+
+    /// The rest of a parenthesized expression, after "(".
+    fn parenthesized(&mut self) -> Result<Expression, &'static str> {
+        let expression = self.sum()?;
+        self.symbol(")").ok_or("Unbalanced parentheses.")?;
+        Ok(expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(s: &str, values: &[f64]) -> f64 {
+        Expression::parse(s, &["x", "y"]).unwrap().evaluate(values)
+    }
+
+    #[test]
+    fn precedence() {
+        assert!(evaluate("1 + 2 * 3 - 4 / 2", &[0.0, 0.0]) == 5.0);
+        assert!(evaluate("2 ^ 3 ^ 2", &[0.0, 0.0]) == 512.0);
+        assert!(evaluate("-x^2", &[3.0, 0.0]) == -9.0);
+        assert!(evaluate("2^-1", &[0.0, 0.0]) == 0.5);
+        assert!(evaluate("(x + y) * -(x - y)", &[3.0, 1.0]) == -8.0);
+        assert!(evaluate("8 / 2 / 2", &[0.0, 0.0]) == 2.0);
+    }
+
+    #[test]
+    fn exponents() {
+        assert!(evaluate("1e-3", &[0.0, 0.0]) == 0.001);
+        assert!(evaluate("2E+2 + 1.5e1", &[0.0, 0.0]) == 215.0);
+        assert!(evaluate("x*1e2", &[3.0, 0.0]) == 300.0);
+        assert!(evaluate("e-1e0", &[0.0, 0.0]) == std::f64::consts::E - 1.0);
+        assert!(Expression::parse("2e", &[]) == Err("Expected an operator."));
+    }
+
+    #[test]
+    fn names() {
+        assert!(evaluate("sqrt(x) + abs(y)", &[16.0, -2.0]) == 6.0);
+        assert!(evaluate("cos(pi) + ln(e)", &[0.0, 0.0]) == 0.0);
+        assert!(evaluate("log(1000)", &[0.0, 0.0]) == 3.0);
+        assert!(evaluate("sqrt(x)", &[-1.0, 0.0]).is_nan());
+    }
+
+    #[test]
+    fn errors() {
+        let error = |s| Expression::parse(s, &["x"]).unwrap_err();
+        assert!(error("") == "Expected an expression.");
+        assert!(error("x +") == "Expected an expression.");
+        assert!(error("(x") == "Unbalanced parentheses.");
+        assert!(error("x)") == "Unbalanced parentheses.");
+        assert!(error("2x") == "Expected an operator.");
+        assert!(error("y") == "Unknown variable or function.");
+        assert!(error("sin x") == "Expected \"(\" after function.");
+        assert!(error("x % 2") == "Unexpected character.");
+        assert!(error("1.2.3") == "Expected a number.");
+    }
+}
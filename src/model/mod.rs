@@ -1,37 +1,75 @@
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
-use renderer::VectorRow;
+use renderer::{Object, VectorRow};
 
+use cloud::Cloud;
+use plot::Plot;
+use primitive::Primitive;
+
+mod cloud;
 mod cube;
+mod expression;
 mod plane;
+mod plot;
+pub mod primitive;
 mod spiral;
 mod star;
 mod test;
 
+#[derive(Clone)]
 pub enum Model {
     Plane,
     Spiral,
     Cube,
     Star,
     Test,
+    /// Generated from parameters, e.g. "torus:radius=32,tube=12".
+    Primitive(Primitive),
+    /// Surface or curve of expressions, e.g. "z = sin(x) * cos(y); x = -pi..pi".
+    Plot(Plot),
+    /// Read from a file, e.g. "scan.ply".
+    Cloud(Cloud),
+}
+
+impl Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Model::Plane => write!(f, "plane"),
+            Model::Spiral => write!(f, "spiral"),
+            Model::Cube => write!(f, "cube"),
+            Model::Star => write!(f, "star"),
+            Model::Test => write!(f, "test"),
+            Model::Primitive(primitive) => write!(f, "{primitive}"),
+            Model::Plot(plot) => write!(f, "{plot}"),
+            Model::Cloud(cloud) => write!(f, "{cloud}"),
+        }
+    }
 }
 
 impl FromStr for Model {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Before the name is lowercased, as paths are case sensitive.
+        if Cloud::is_cloud(s) {
+            return s.parse().map(Model::Cloud);
+        }
+
         match s.to_lowercase().as_str() {
             "plane" => Ok(Model::Plane),
             "spiral" => Ok(Model::Spiral),
             "cube" => Ok(Model::Cube),
             "star" => Ok(Model::Star),
             "test" => Ok(Model::Test),
-            _ => Err("Could not convert to string."),
+            s if Plot::is_plot(s) => s.parse().map(Model::Plot),
+            s => s.parse().map(Model::Primitive),
         }
     }
 }
 
 impl Model {
+    pub const ALL: [Model; 5] = [Model::Plane, Model::Spiral, Model::Cube, Model::Star, Model::Test];
+
     pub fn get_vertices(&self) -> Vec<VectorRow<f64, 3>> {
         let mut vertices = Vec::new();
 
@@ -51,6 +89,15 @@ impl Model {
             Model::Test => {
                 vertices.append(&mut test::get_vertices());
             }
+            Model::Primitive(primitive) => {
+                vertices.append(&mut primitive.generate().0);
+            }
+            Model::Plot(plot) => {
+                vertices.append(&mut plot.generate().0);
+            }
+            Model::Cloud(cloud) => {
+                vertices.append(&mut cloud.generate().0);
+            }
         }
 
         vertices
@@ -75,10 +122,99 @@ impl Model {
             Model::Test => {
                 lines.append(&mut test::get_line_draw_order());
             }
+            Model::Primitive(primitive) => {
+                lines.append(&mut primitive.generate().1);
+            }
+            Model::Plot(plot) => {
+                lines.append(&mut plot.generate().1);
+            }
+            Model::Cloud(cloud) => {
+                lines.append(&mut cloud.generate().1);
+            }
         }
 
         lines
     }
+
+    /// Vertices and line draw order, generated once for both.
+    pub fn generate(&self) -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>) {
+        match self {
+            Model::Primitive(primitive) => primitive.generate(),
+            Model::Plot(plot) => plot.generate(),
+            Model::Cloud(cloud) => cloud.generate(),
+            _ => (self.get_vertices(), self.get_line_draw_order()),
+        }
+    }
+
+    /// Draw at most `points` of a point cloud, see [Cloud::subsample]. Returns true if the
+    /// vertices changed.
+    pub fn subsample(&mut self, points: usize) -> bool {
+        match self {
+            Model::Cloud(cloud) => cloud.subsample(points),
+            _ => false,
+        }
+    }
+
+    /// Parts of the model to cull separately, given the vertices and line draw order it generated.
+    /// Models not split up are a single object.
+    pub fn get_objects(
+        &self,
+        vertices: &[VectorRow<f64, 3>],
+        line_draw_order: &[Vec<usize>],
+    ) -> Vec<Object> {
+        match self {
+            Model::Spiral => spiral::get_objects(),
+            _ => vec![Object {
+                vertices: 0..vertices.len(),
+                line_draw_order: 0..line_draw_order.len(),
+                levels: Vec::new(),
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn objects_only_draw_own_vertices() {
+        for model in Model::ALL {
+            let mut vertices = model.get_vertices();
+            let mut lines = model.get_line_draw_order();
+            let mut objects = model.get_objects(&vertices, &lines);
+
+            for object in objects.iter_mut() {
+                renderer::lod::add_levels(
+                    object,
+                    &mut vertices,
+                    &mut lines,
+                    &renderer::lod::DETAILS_DEFAULT,
+                );
+            }
+
+            for object in objects {
+                let parts = std::iter::once((object.vertices, object.line_draw_order)).chain(
+                    object
+                        .levels
+                        .into_iter()
+                        .map(|level| (level.vertices, level.line_draw_order)),
+                );
+
+                for (object_vertices, object_lines) in parts {
+                    assert!(object_vertices.end <= vertices.len(), "{model}");
+                    assert!(object_lines.end <= lines.len(), "{model}");
+
+                    for order in &lines[object_lines] {
+                        assert!(
+                            order.iter().all(|index| object_vertices.contains(index)),
+                            "{model}"
+                        );
+                    }
+                }
+            }
+        }
+    }
 }
 
 // mod object {
@@ -0,0 +1,298 @@
+/// Plots of expressions: surfaces "z = f(x, y)" sampled over a grid, and parametric curves
+/// "(x(t), y(t), z(t))". Settings follow the expression, separated by ";", e.g.
+/// "z = sin(x) * cos(y); x = -pi..pi; y = -pi..pi; resolution = 48". Plots are scaled to fit a box
+/// of [SIZE] around the origin, keeping their proportions.
+use std::{fmt::Display, str::FromStr};
+
+use renderer::VectorRow;
+
+use super::expression::Expression;
+
+/// Side of the box plots are scaled to fit, the size of the primitives.
+const SIZE: f64 = 64.0;
+
+/// Most samples along each axis of a surface, which has their square.
+const RESOLUTION_MAX: usize = 512;
+
+/// Most samples along a curve.
+const STEPS_MAX: usize = 4096;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Kind {
+    /// Height over the x and y ranges, sampled `resolution` times along each.
+    Surface {
+        z: Expression,
+        x: (f64, f64),
+        y: (f64, f64),
+        resolution: usize,
+    },
+    /// Point at t over its range, sampled in `steps`.
+    Curve {
+        point: [Expression; 3],
+        t: (f64, f64),
+        steps: usize,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plot {
+    kind: Kind,
+    /// As given, to be shown.
+    source: String,
+}
+
+impl Plot {
+    /// Whether a model is to be parsed as a plot: "z = ..." or "(...".
+    pub fn is_plot(s: &str) -> bool {
+        s.trim_start().starts_with('(')
+            || s.split_once('=').is_some_and(|(lhs, _)| lhs.trim() == "z")
+    }
+
+    pub fn generate(&self) -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>) {
+        let lerp = |(start, end): (f64, f64), step: usize, steps: usize| {
+            start + (end - start) * step as f64 / steps as f64
+        };
+        let mut vertices = Vec::new();
+        let mut line_draw_order = Vec::new();
+        // Points where the expressions are not finite (e.g. "1 / x" at 0) are left out, with
+        // whatever goes through them.
+        let mut add = |point: [f64; 3]| {
+            point.iter().all(|value| value.is_finite()).then(|| {
+                vertices.push(VectorRow::from(point));
+                vertices.len() - 1
+            })
+        };
+
+        match &self.kind {
+            Kind::Surface {
+                z,
+                x,
+                y,
+                resolution,
+            } => {
+                let side = resolution + 1;
+                let indices = (0..side * side)
+                    .map(|sample| {
+                        let x = lerp(*x, sample % side, *resolution);
+                        let y = lerp(*y, sample / side, *resolution);
+                        add([x, y, z.evaluate(&[x, y])])
+                    })
+                    .collect::<Vec<_>>();
+
+                // Two triangles a cell, so that faces are flat. Counter-clockwise seen from above.
+                for cell in 0..resolution * resolution {
+                    let corner = cell % resolution + cell / resolution * side;
+                    let [a, b, c, d] = [corner, corner + 1, corner + side + 1, corner + side];
+
+                    for triangle in [[a, b, c], [a, c, d]] {
+                        if let [Some(a), Some(b), Some(c)] = triangle.map(|index| indices[index]) {
+                            line_draw_order.push(vec![a, b, c]);
+                        }
+                    }
+                }
+            }
+            Kind::Curve { point, t, steps } => {
+                let mut previous = None;
+
+                for step in 0..=*steps {
+                    let t = lerp(*t, step, *steps);
+                    let index = add(point.each_ref().map(|expression| expression.evaluate(&[t])));
+
+                    if let (Some(previous), Some(index)) = (previous, index) {
+                        line_draw_order.push(vec![previous, index]);
+                    }
+                    previous = index;
+                }
+            }
+        }
+
+        let largest = vertices
+            .iter()
+            .flat_map(|vertex| [vertex[0], vertex[1], vertex[2]])
+            .fold(0.0, |largest: f64, value| largest.max(value.abs()));
+
+        if largest > 0.0 {
+            let scale = SIZE / 2.0 / largest;
+            for vertex in vertices.iter_mut() {
+                *vertex =
+                    VectorRow::from([vertex[0] * scale, vertex[1] * scale, vertex[2] * scale]);
+            }
+        }
+
+        (vertices, line_draw_order)
+    }
+}
+
+impl Display for Plot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for Plot {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(';');
+        let equation = parts.next().unwrap_or_default().trim();
+        let settings = parts
+            .filter(|part| !part.trim().is_empty())
+            .map(|part| {
+                part.split_once('=')
+                    .map(|(name, value)| (name.trim(), value.trim()))
+                    .ok_or("Expected settings as name = value.")
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let kind = match equation.split_once('=').map(|(lhs, rhs)| (lhs.trim(), rhs)) {
+            Some(("z", z)) => {
+                let z = Expression::parse(z, &["x", "y"])?;
+                let (mut x, mut y, mut resolution) = ((-1.0, 1.0), (-1.0, 1.0), 32);
+
+                for (name, value) in settings {
+                    match name {
+                        "x" => x = range(value)?,
+                        "y" => y = range(value)?,
+                        "resolution" => {
+                            resolution = samples(value, RESOLUTION_MAX)
+                                .ok_or("Expected a whole number from 1 to 512.")?
+                        }
+                        _ => return Err("Unknown setting."),
+                    }
+                }
+
+                Kind::Surface {
+                    z,
+                    x,
+                    y,
+                    resolution,
+                }
+            }
+            Some(_) => return Err("Expected a surface as \"z = f(x, y)\"."),
+            None => {
+                let components = equation
+                    .strip_prefix('(')
+                    .and_then(|inner| inner.strip_suffix(')'))
+                    .map(split_arguments)
+                    .filter(|components| components.len() == 3)
+                    .ok_or("Expected a curve as \"(x(t), y(t), z(t))\".")?;
+                let mut point = Vec::new();
+                for component in components {
+                    point.push(Expression::parse(component, &["t"])?);
+                }
+                let (mut t, mut steps) = ((0.0, std::f64::consts::TAU), 256);
+
+                for (name, value) in settings {
+                    match name {
+                        "t" => t = range(value)?,
+                        "steps" => {
+                            steps = samples(value, STEPS_MAX)
+                                .ok_or("Expected a whole number from 1 to 4096.")?
+                        }
+                        _ => return Err("Unknown setting."),
+                    }
+                }
+
+                Kind::Curve {
+                    point: point.try_into().expect("Three components."),
+                    t,
+                    steps,
+                }
+            }
+        };
+
+        Ok(Self {
+            kind,
+            source: s.trim().to_string(),
+        })
+    }
+}
+
+/// "start..end", where either may be a constant expression, e.g. "-pi..2*pi".
+fn range(s: &str) -> Result<(f64, f64), &'static str> {
+    let error = "Expected a range as \"start..end\".";
+    let (start, end) = s.split_once("..").ok_or(error)?;
+    let start = Expression::parse(start, &[])?.evaluate(&[]);
+    let end = Expression::parse(end, &[])?.evaluate(&[]);
+
+    match start.is_finite() && end.is_finite() && start < end {
+        true => Ok((start, end)),
+        false => Err(error),
+    }
+}
+
+fn samples(s: &str, max: usize) -> Option<usize> {
+    s.parse().ok().filter(|samples| (1..=max).contains(samples))
+}
+
+/// Split on the commas outside of parentheses.
+fn split_arguments(s: &str) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+
+    for (index, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                arguments.push(&s[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+    arguments.push(&s[start..]);
+
+    arguments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surface() {
+        let plot: Plot = "z = x * y; x = 0..2; y = -1..1; resolution = 2"
+            .parse()
+            .unwrap();
+        let (vertices, faces) = plot.generate();
+        assert!(vertices.len() == 9 && faces.len() == 8);
+        // Scaled so that the farthest coordinate, x = 2, is at half the size.
+        assert!(vertices[8] == VectorRow::from([32.0, 16.0, 32.0]));
+        assert!(faces[0] == [0, 1, 4]);
+        assert!(plot.to_string() == "z = x * y; x = 0..2; y = -1..1; resolution = 2");
+
+        // No point at the origin, nor the triangles through it.
+        let plot: Plot = "z = 1 / sqrt(x^2 + y^2); resolution = 2".parse().unwrap();
+        let (vertices, faces) = plot.generate();
+        assert!(vertices.len() == 8 && faces.len() == 2);
+    }
+
+    #[test]
+    fn curve() {
+        let plot: Plot = "(cos(t), sin(t), t / (2 * pi)); steps = 4".parse().unwrap();
+        let (vertices, lines) = plot.generate();
+        assert!(vertices.len() == 5 && lines.len() == 4);
+        assert!(lines
+            .iter()
+            .enumerate()
+            .all(|(i, line)| *line == [i, i + 1]));
+        assert!((vertices[1][1] - 32.0).abs() < 1e-9 && (vertices[4][2] - 32.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |s: &str| s.parse::<Plot>().unwrap_err();
+        assert!(error("z = x * w") == "Unknown variable or function.");
+        assert!(error("z = x; x = 1..0") == "Expected a range as \"start..end\".");
+        assert!(error("z = x; x = 1") == "Expected a range as \"start..end\".");
+        assert!(error("z = x; t = 0..1") == "Unknown setting.");
+        assert!(error("z = x; resolution = 0") == "Expected a whole number from 1 to 512.");
+        assert!(error("z = x; resolution = 513") == "Expected a whole number from 1 to 512.");
+        assert!(error("(t, t, t); steps = 4097") == "Expected a whole number from 1 to 4096.");
+        assert!(error("z = x; resolution") == "Expected settings as name = value.");
+        assert!(error("y = x") == "Expected a surface as \"z = f(x, y)\".");
+        assert!(error("(t, t)") == "Expected a curve as \"(x(t), y(t), z(t))\".");
+        assert!(error("(t, sin(t, t), t)") == "Unexpected character.");
+    }
+}
@@ -0,0 +1,562 @@
+/// Meshes generated from a few parameters, e.g. "sphere:segments=24,rings=12". Faces are wound
+/// counter-clockwise seen from outside, as those of the cube, so that backface culling keeps the outside.
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use renderer::VectorRow;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Box,
+    Sphere,
+    Icosphere,
+    Cylinder,
+    Cone,
+    Torus,
+    Grid,
+    Revolution,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 8] = [
+        Shape::Box,
+        Shape::Sphere,
+        Shape::Icosphere,
+        Shape::Cylinder,
+        Shape::Cone,
+        Shape::Torus,
+        Shape::Grid,
+        Shape::Revolution,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Shape::Box => "box",
+            Shape::Sphere => "sphere",
+            Shape::Icosphere => "icosphere",
+            Shape::Cylinder => "cylinder",
+            Shape::Cone => "cone",
+            Shape::Torus => "torus",
+            Shape::Grid => "grid",
+            Shape::Revolution => "revolution",
+        }
+    }
+
+    /// Numeric parameters, in the order they are written.
+    fn parameters(&self) -> Vec<Parameter> {
+        match self {
+            Shape::Box => vec![
+                Parameter::size("width", 64.0),
+                Parameter::size("depth", 64.0),
+                Parameter::size("height", 64.0),
+            ],
+            Shape::Sphere => vec![
+                Parameter::size("radius", 32.0),
+                Parameter::count("segments", 24, 3),
+                Parameter::count("rings", 12, 2),
+            ],
+            Shape::Icosphere => vec![
+                Parameter::size("radius", 32.0),
+                Parameter {
+                    name: "subdivisions",
+                    default: 2.0,
+                    range: (0.0, 6.0),
+                    count: true,
+                },
+            ],
+            Shape::Cylinder | Shape::Cone => vec![
+                Parameter::size("radius", 32.0),
+                Parameter::size("height", 64.0),
+                Parameter::count("segments", 24, 3),
+            ],
+            Shape::Torus => vec![
+                Parameter::size("radius", 32.0),
+                Parameter::size("tube", 12.0),
+                Parameter::count("segments", 24, 3),
+                Parameter::count("rings", 12, 3),
+            ],
+            Shape::Grid => vec![
+                Parameter::size("width", 128.0),
+                Parameter::size("depth", 128.0),
+                Parameter::count("segments", 8, 1),
+            ],
+            Shape::Revolution => vec![Parameter::count("segments", 24, 3)],
+        }
+    }
+}
+
+struct Parameter {
+    name: &'static str,
+    default: f64,
+    /// Inclusive.
+    range: (f64, f64),
+    /// Whole numbers only.
+    count: bool,
+}
+
+impl Parameter {
+    fn size(name: &'static str, default: f64) -> Self {
+        Self {
+            name,
+            default,
+            range: (f64::MIN_POSITIVE, f64::MAX),
+            count: false,
+        }
+    }
+
+    fn count(name: &'static str, default: u32, min: u32) -> Self {
+        Self {
+            name,
+            default: default as f64,
+            range: (min as f64, 1024.0),
+            count: true,
+        }
+    }
+}
+
+/// Profile of [Shape::Revolution] unless given: a vase.
+const PROFILE_DEFAULT: [[f64; 2]; 6] = [
+    [0.0, -32.0],
+    [16.0, -32.0],
+    [28.0, -16.0],
+    [12.0, 12.0],
+    [10.0, 24.0],
+    [16.0, 32.0],
+];
+
+/// A [Shape], and the values of its parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Primitive {
+    pub shape: Shape,
+    /// Values of [Shape::parameters], in the same order.
+    values: Vec<f64>,
+    /// Of [Shape::Revolution]: points (radius, height), from the bottom up. Ends of radius 0 are closed.
+    profile: Vec<[f64; 2]>,
+}
+
+impl Primitive {
+    /// The shape with default parameters.
+    pub fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            values: shape
+                .parameters()
+                .iter()
+                .map(|parameter| parameter.default)
+                .collect(),
+            profile: match shape {
+                Shape::Revolution => PROFILE_DEFAULT.to_vec(),
+                _ => Vec::new(),
+            },
+        }
+    }
+
+    fn value(&self, name: &str) -> f64 {
+        let index = self
+            .shape
+            .parameters()
+            .iter()
+            .position(|parameter| parameter.name == name)
+            .expect("Parameter of the shape.");
+        self.values[index]
+    }
+
+    fn count(&self, name: &str) -> usize {
+        self.value(name) as usize
+    }
+
+    pub fn generate(&self) -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>) {
+        let circle = |radius: f64, height: f64, steps: usize, start: f64| {
+            (0..steps)
+                .map(|step| {
+                    let angle = start - std::f64::consts::TAU * step as f64 / steps as f64;
+                    [radius + height * angle.cos(), height * angle.sin()]
+                })
+                .collect::<Vec<_>>()
+        };
+
+        match self.shape {
+            Shape::Box => cuboid([
+                self.value("width"),
+                self.value("depth"),
+                self.value("height"),
+            ]),
+            Shape::Sphere => {
+                let (radius, rings) = (self.value("radius"), self.count("rings"));
+                let profile = (0..=rings)
+                    .map(|ring| {
+                        let angle = std::f64::consts::PI * ring as f64 / rings as f64;
+                        // Exactly 0 at the poles.
+                        let r = match ring == 0 || ring == rings {
+                            true => 0.0,
+                            false => radius * angle.sin(),
+                        };
+                        [r, radius * angle.cos()]
+                    })
+                    .collect::<Vec<_>>();
+                revolve(&profile, self.count("segments"), false)
+            }
+            Shape::Icosphere => icosphere(self.value("radius"), self.count("subdivisions")),
+            Shape::Cylinder => {
+                let (radius, half) = (self.value("radius"), self.value("height") / 2.0);
+                let profile = [[0.0, half], [radius, half], [radius, -half], [0.0, -half]];
+                revolve(&profile, self.count("segments"), false)
+            }
+            Shape::Cone => {
+                let (radius, half) = (self.value("radius"), self.value("height") / 2.0);
+                let profile = [[0.0, half], [radius, -half], [0.0, -half]];
+                revolve(&profile, self.count("segments"), false)
+            }
+            Shape::Torus => {
+                // Around the tube from the top, down its outside.
+                let profile = circle(
+                    self.value("radius"),
+                    self.value("tube"),
+                    self.count("rings"),
+                    std::f64::consts::FRAC_PI_2,
+                );
+                revolve(&profile, self.count("segments"), true)
+            }
+            Shape::Grid => grid(
+                self.value("width"),
+                self.value("depth"),
+                self.count("segments"),
+            ),
+            Shape::Revolution => {
+                let profile = self.profile.iter().rev().copied().collect::<Vec<_>>();
+                revolve(&profile, self.count("segments"), false)
+            }
+        }
+    }
+}
+
+impl Display for Primitive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parameters = self
+            .shape
+            .parameters()
+            .iter()
+            .zip(self.values.iter())
+            .map(|(parameter, value)| format!("{}={value}", parameter.name))
+            .collect::<Vec<_>>();
+
+        if let Shape::Revolution = self.shape {
+            let points = self
+                .profile
+                .iter()
+                .map(|[radius, height]| format!("{radius}:{height}"))
+                .collect::<Vec<_>>();
+            parameters.push(format!("profile={}", points.join("/")));
+        }
+
+        write!(f, "{}:{}", self.shape.name(), parameters.join(","))
+    }
+}
+
+impl FromStr for Primitive {
+    type Err = &'static str;
+
+    /// `<shape>[:<name>=<value>,...]`. The profile of a revolution is given as `profile=r:h/r:h/...`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, given) = s.split_once(':').unwrap_or((s, ""));
+        let shape = *Shape::ALL
+            .iter()
+            .find(|shape| shape.name() == name)
+            .ok_or("Unknown model.")?;
+        let mut primitive = Self::new(shape);
+        let parameters = shape.parameters();
+
+        for parameter in given.split(',').filter(|parameter| !parameter.is_empty()) {
+            let (name, value) = parameter
+                .split_once('=')
+                .ok_or("Expected parameters as name=value.")?;
+
+            if let ("profile", Shape::Revolution) = (name, shape) {
+                primitive.profile = value
+                    .split('/')
+                    .map(|point| {
+                        let (radius, height) = point.split_once(':')?;
+                        let point = [radius.parse().ok()?, height.parse().ok()?];
+                        (point[0] >= 0.0).then_some(point)
+                    })
+                    .collect::<Option<Vec<[f64; 2]>>>()
+                    .filter(|profile| profile.len() >= 2)
+                    .ok_or("Expected a profile of two or more radius:height points, radii not negative.")?;
+                continue;
+            }
+
+            let index = parameters
+                .iter()
+                .position(|parameter| parameter.name == name)
+                .ok_or("Unknown parameter.")?;
+            let parameter = &parameters[index];
+            let value: f64 = value.parse().map_err(|_| "Expected a number.")?;
+
+            if !(parameter.range.0..=parameter.range.1).contains(&value)
+                || (parameter.count && value.fract() != 0.0)
+            {
+                return Err(match parameter.count {
+                    true => "Parameter out of range.",
+                    false => "Sizes must be positive.",
+                });
+            }
+
+            primitive.values[index] = value;
+        }
+
+        Ok(primitive)
+    }
+}
+
+/// Box centered on the origin.
+fn cuboid(size: [f64; 3]) -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>) {
+    let vertices = (0..8)
+        .map(|corner| {
+            // Bit 0 for x, 1 for y, 2 for z.
+            VectorRow::from([0, 1, 2].map(|axis| match corner >> axis & 1 {
+                0 => -size[axis] / 2.0,
+                _ => size[axis] / 2.0,
+            }))
+        })
+        .collect();
+    let faces = vec![
+        vec![0, 1, 5, 4], // Front (-y)
+        vec![2, 6, 7, 3], // Back (+y)
+        vec![0, 4, 6, 2], // Left (-x)
+        vec![1, 3, 7, 5], // Right (+x)
+        vec![4, 5, 7, 6], // Top (+z)
+        vec![0, 2, 3, 1], // Bottom (-z)
+    ];
+
+    (vertices, faces)
+}
+
+/// Sweep a profile of points (radius, height) around the z axis, in `segments` steps. Points of radius
+/// 0 become a single vertex, closing the surface there. Faces are outward, if the profile runs down
+/// the outside of the surface (e.g. top to bottom of a sphere).
+fn revolve(
+    profile: &[[f64; 2]],
+    segments: usize,
+    closed: bool,
+) -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>) {
+    let mut vertices = Vec::new();
+    // First vertex of each profile point, and whether it is a single one.
+    let mut rings = Vec::new();
+
+    for &[radius, height] in profile {
+        rings.push((vertices.len(), radius == 0.0));
+
+        match radius == 0.0 {
+            true => vertices.push(VectorRow::from([0.0, 0.0, height])),
+            false => vertices.extend((0..segments).map(|segment| {
+                let angle = std::f64::consts::TAU * segment as f64 / segments as f64;
+                VectorRow::from([radius * angle.cos(), radius * angle.sin(), height])
+            })),
+        }
+    }
+
+    let pairs = rings.len() - !closed as usize;
+    let mut faces = Vec::new();
+
+    for pair in 0..pairs {
+        let (upper, upper_single) = rings[pair];
+        let (lower, lower_single) = rings[(pair + 1) % rings.len()];
+        let vertex = |(start, single): (usize, bool), segment: usize| match single {
+            true => start,
+            false => start + segment % segments,
+        };
+
+        for segment in 0..segments {
+            let face = [
+                vertex((upper, upper_single), segment),
+                vertex((lower, lower_single), segment),
+                vertex((lower, lower_single), segment + 1),
+                vertex((upper, upper_single), segment + 1),
+            ];
+
+            match (upper_single, lower_single) {
+                (false, false) => faces.push(face.to_vec()),
+                (true, false) => faces.push(face[..3].to_vec()),
+                (false, true) => faces.push(vec![face[0], face[1], face[3]]),
+                // Nothing between two points on the axis.
+                (true, true) => break,
+            }
+        }
+    }
+
+    (vertices, faces)
+}
+
+/// Icosahedron with each triangle split into four `subdivisions` times, pushed out onto a sphere.
+fn icosphere(radius: f64, subdivisions: usize) -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>) {
+    let t = (1.0 + 5f64.sqrt()) / 2.0;
+    let mut points = vec![
+        [-1.0, t, 0.0],
+        [1.0, t, 0.0],
+        [-1.0, -t, 0.0],
+        [1.0, -t, 0.0],
+        [0.0, -1.0, t],
+        [0.0, 1.0, t],
+        [0.0, -1.0, -t],
+        [0.0, 1.0, -t],
+        [t, 0.0, -1.0],
+        [t, 0.0, 1.0],
+        [-t, 0.0, -1.0],
+        [-t, 0.0, 1.0],
+    ];
+    let mut faces: Vec<[usize; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Shared by the two triangles along an edge.
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |a: usize, b: usize| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let (a, b): ([f64; 3], [f64; 3]) = (points[a], points[b]);
+                points.push([0, 1, 2].map(|axis| (a[axis] + b[axis]) / 2.0));
+                points.len() - 1
+            })
+        };
+
+        faces = faces
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let vertices = points
+        .iter()
+        .map(|point| {
+            let length = point.iter().map(|x| x * x).sum::<f64>().sqrt();
+            VectorRow::from(point.map(|x| x / length * radius))
+        })
+        .collect();
+
+    (vertices, faces.into_iter().map(Vec::from).collect())
+}
+
+/// Flat grid of `segments` by `segments` cells, facing up.
+fn grid(width: f64, depth: f64, segments: usize) -> (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>) {
+    let side = segments + 1;
+    let vertices = (0..side * side)
+        .map(|index| {
+            let (x, y) = (index % side, index / side);
+            VectorRow::from([
+                width * (x as f64 / segments as f64 - 0.5),
+                depth * (y as f64 / segments as f64 - 0.5),
+                0.0,
+            ])
+        })
+        .collect();
+    let faces = (0..segments * segments)
+        .map(|cell| {
+            let corner = cell % segments + cell / segments * side;
+            vec![corner, corner + 1, corner + side + 1, corner + side]
+        })
+        .collect();
+
+    (vertices, faces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Volume enclosed by the faces, negative if they face inward.
+    fn volume(vertices: &[VectorRow<f64, 3>], faces: &[Vec<usize>]) -> f64 {
+        faces
+            .iter()
+            .flat_map(|face| (1..face.len() - 1).map(move |i| [face[0], face[i], face[i + 1]]))
+            .map(|[a, b, c]| {
+                let (a, b, c) = (&vertices[a], &vertices[b], &vertices[c]);
+                a.dot(&b.cross(c)) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn parse() {
+        let sphere: Primitive = "sphere:segments=8,rings=4".parse().unwrap();
+        assert!(sphere.shape == Shape::Sphere && sphere.count("segments") == 8);
+        assert!(sphere.value("radius") == 32.0);
+        assert!(sphere.to_string() == "sphere:radius=32,segments=8,rings=4");
+
+        for shape in Shape::ALL {
+            let primitive = Primitive::new(shape);
+            assert!(primitive.to_string().parse::<Primitive>() == Ok(primitive));
+        }
+
+        let revolution: Primitive = "revolution:profile=0:0/1:2.5".parse().unwrap();
+        assert!(revolution.profile == [[0.0, 0.0], [1.0, 2.5]]);
+
+        assert!("teapot".parse::<Primitive>().is_err());
+        assert!("sphere:colour=red".parse::<Primitive>().is_err());
+        assert!("sphere:segments".parse::<Primitive>().is_err());
+        assert!("sphere:segments=2".parse::<Primitive>().is_err());
+        assert!("sphere:segments=2.5".parse::<Primitive>().is_err());
+        assert!("box:width=-1".parse::<Primitive>().is_err());
+        assert!("revolution:profile=1:2".parse::<Primitive>().is_err());
+        assert!("revolution:profile=-1:2/1:3".parse::<Primitive>().is_err());
+    }
+
+    #[test]
+    fn closed_shapes_face_outward() {
+        let pi = std::f64::consts::PI;
+
+        for (model, expected) in [
+            ("box:width=1,depth=2,height=3", 6.0),
+            ("cylinder:radius=1,height=2,segments=256", 2.0 * pi),
+            ("cone:radius=1,height=3,segments=256", pi),
+            ("sphere:radius=1,segments=128,rings=64", 4.0 / 3.0 * pi),
+            ("icosphere:radius=1,subdivisions=4", 4.0 / 3.0 * pi),
+            (
+                "torus:radius=2,tube=1,segments=128,rings=128",
+                2.0 * pi * pi * 2.0,
+            ),
+            ("revolution:profile=0:0/1:0/1:1/0:1,segments=256", pi),
+        ] {
+            let (vertices, faces) = model.parse::<Primitive>().unwrap().generate();
+            let volume = volume(&vertices, &faces);
+            assert!(
+                (volume - expected).abs() < expected * 0.01,
+                "{model}: {volume}"
+            );
+        }
+    }
+
+    #[test]
+    fn counts() {
+        let count = |model: &str| {
+            let (vertices, faces) = model.parse::<Primitive>().unwrap().generate();
+            (vertices.len(), faces.len())
+        };
+
+        assert!(count("sphere:segments=8,rings=4") == (2 + 8 * 3, 8 * 4));
+        assert!(count("icosphere:subdivisions=1") == (42, 80));
+        assert!(count("cylinder:segments=5") == (2 + 5 * 2, 5 * 3));
+        assert!(count("torus:segments=5,rings=3") == (15, 15));
+        assert!(count("grid:segments=3") == (16, 9));
+    }
+}
@@ -1,10 +1,13 @@
 use std::f64::consts;
 
-use renderer::VectorRow;
+use renderer::{Object, VectorRow};
 
 const MAX_DEPTH: i32 = 1000;
 const GRID_SIZE: i32 = 200;
 const GRID_SPACING: i32 = 100;
+/// The grid is split in tiles of this size, to be culled separately.
+const GRID_TILE_SIZE: i32 = 20;
+const GRID_TILES: i32 = GRID_SIZE / GRID_TILE_SIZE;
 
 pub fn get_vertices() -> Vec<VectorRow<f64, 3>> {
     let mut vertices = vec![];
@@ -18,13 +21,18 @@ pub fn get_vertices() -> Vec<VectorRow<f64, 3>> {
         ]));
     }
 
-    for i in 0..GRID_SIZE {
-        for j in 0..GRID_SIZE {
-            vertices.push(VectorRow::from([
-                (-GRID_SIZE / 2 * GRID_SPACING) as f64 + (i * GRID_SPACING) as f64,
-                MAX_DEPTH as f64,
-                (-GRID_SIZE / 2 * GRID_SPACING) as f64 + (j * GRID_SPACING) as f64,
-            ]));
+    // Grid, tile by tile.
+    for tile_i in 0..GRID_TILES {
+        for tile_j in 0..GRID_TILES {
+            for i in tile_i * GRID_TILE_SIZE..(tile_i + 1) * GRID_TILE_SIZE {
+                for j in tile_j * GRID_TILE_SIZE..(tile_j + 1) * GRID_TILE_SIZE {
+                    vertices.push(VectorRow::from([
+                        (-GRID_SIZE / 2 * GRID_SPACING) as f64 + (i * GRID_SPACING) as f64,
+                        MAX_DEPTH as f64,
+                        (-GRID_SIZE / 2 * GRID_SPACING) as f64 + (j * GRID_SPACING) as f64,
+                    ]));
+                }
+            }
         }
     }
 
@@ -35,8 +43,8 @@ pub fn get_line_draw_order() -> Vec<Vec<usize>> {
     let mut lines = vec![];
 
     // Particles (points).
-    for i in 1..(GRID_SIZE * GRID_SIZE) {
-        lines.push(vec![i as usize]);
+    for i in 0..(GRID_SIZE * GRID_SIZE) {
+        lines.push(vec![(MAX_DEPTH + i) as usize]);
     }
 
     // Particles (lines).
@@ -45,4 +53,28 @@ pub fn get_line_draw_order() -> Vec<Vec<usize>> {
     }
 
     lines
-}
\ No newline at end of file
+}
+
+/// The spiral, and each tile of the grid.
+pub fn get_objects() -> Vec<Object> {
+    let spiral_len = MAX_DEPTH as usize;
+    let tile_len = (GRID_TILE_SIZE * GRID_TILE_SIZE) as usize;
+    let grid_len = (GRID_SIZE * GRID_SIZE) as usize;
+
+    let mut objects = vec![Object {
+        vertices: 0..spiral_len,
+        line_draw_order: grid_len..grid_len + spiral_len - 1,
+        levels: Vec::new(),
+    }];
+
+    for tile in 0..(GRID_TILES * GRID_TILES) as usize {
+        let start = tile * tile_len;
+        objects.push(Object {
+            vertices: spiral_len + start..spiral_len + start + tile_len,
+            line_draw_order: start..start + tile_len,
+            levels: Vec::new(),
+        });
+    }
+
+    objects
+}
@@ -0,0 +1,156 @@
+/// Reader and writer for Wavefront OBJ, as far as the renderer's geometry goes: vertices, and the
+/// faces, lines and points between them. Line draw orders of one vertex are written as points
+/// ("p"), of two as lines ("l"), and of more as faces ("f").
+use std::{fmt::Write, path::Path};
+
+use renderer::VectorRow;
+
+/// Vertices, and line draw order.
+type Mesh = (Vec<VectorRow<f64, 3>>, Vec<Vec<usize>>);
+
+/// Read what [write()] writes. Texture coordinates, normals, groups and materials are ignored.
+pub fn read(s: &str) -> Result<Mesh, &'static str> {
+    let mut vertices = Vec::new();
+    let mut line_draw_order = Vec::new();
+
+    for line in s.lines() {
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("v") => {
+                let coordinates = words
+                    .take(3)
+                    .map(|word| word.parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .ok()
+                    .filter(|coordinates| coordinates.len() == 3)
+                    .ok_or("Expected a vertex as \"v x y z\".")?;
+                vertices.push(VectorRow::from([
+                    coordinates[0],
+                    coordinates[1],
+                    coordinates[2],
+                ]));
+            }
+            Some("f" | "l" | "p") => {
+                let order = words
+                    .map(|word| {
+                        // Faces may refer to texture coordinates and normals too, e.g. "1/2/3".
+                        let index = word.split('/').next().unwrap_or_default();
+                        match index.parse::<isize>() {
+                            // Counted from 1, or back from the last vertex so far.
+                            Ok(index) if index > 0 => Ok(index as usize - 1),
+                            Ok(index) if index < 0 && index.unsigned_abs() <= vertices.len() => {
+                                Ok(vertices.len() - index.unsigned_abs())
+                            }
+                            _ => Err("Expected a vertex index."),
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                line_draw_order.push(order);
+            }
+            _ => (),
+        }
+    }
+
+    match line_draw_order
+        .iter()
+        .flatten()
+        .all(|index| *index < vertices.len())
+    {
+        true => Ok((vertices, line_draw_order)),
+        false => Err("Element of a vertex not in the file."),
+    }
+}
+
+pub fn load(path: &Path) -> Result<Mesh, String> {
+    let s = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    read(&s).map_err(|msg| format!("{}: {msg}", path.display()))
+}
+
+pub fn write(vertices: &[VectorRow<f64, 3>], line_draw_order: &[Vec<usize>]) -> String {
+    let mut s = String::from("# Written by grpe.\n");
+
+    for vertex in vertices {
+        writeln!(s, "v {} {} {}", vertex[0], vertex[1], vertex[2]).unwrap();
+    }
+
+    for order in line_draw_order {
+        let element = match order.len() {
+            0 => continue,
+            1 => "p",
+            2 => "l",
+            _ => "f",
+        };
+
+        s += element;
+        for index in order {
+            // OBJ indices start at 1.
+            write!(s, " {}", index + 1).unwrap();
+        }
+        s += "\n";
+    }
+
+    s
+}
+
+pub fn save(
+    path: &Path,
+    vertices: &[VectorRow<f64, 3>],
+    line_draw_order: &[Vec<usize>],
+) -> Result<(), String> {
+    std::fs::write(path, write(vertices, line_draw_order))
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elements() {
+        let vertices = [
+            VectorRow::from([0.0, 0.0, 0.0]),
+            VectorRow::from([1.5, 0.0, -2.0]),
+            VectorRow::from([0.0, 1.0, 0.1]),
+        ];
+        let line_draw_order = [vec![0, 1, 2], vec![], vec![2, 0], vec![1]];
+
+        assert!(
+            write(&vertices, &line_draw_order)
+                == "# Written by grpe.\n\
+                    v 0 0 0\n\
+                    v 1.5 0 -2\n\
+                    v 0 1 0.1\n\
+                    f 1 2 3\n\
+                    l 3 1\n\
+                    p 2\n"
+        );
+        assert!(
+            read(&write(&vertices, &line_draw_order))
+                == Ok((vertices.to_vec(), vec![vec![0, 1, 2], vec![2, 0], vec![1]]))
+        );
+    }
+
+    #[test]
+    fn other_writers() {
+        let (vertices, line_draw_order) = read(
+            "o cube\n\
+             v 0 0 0 1\n\
+             v 1 0 0\n\
+             vt 0.5 0.5\n\
+             v 1 1 0\n\
+             f 1/1/1 2/1/1 -1/1/1\n",
+        )
+        .unwrap();
+        assert!(vertices.len() == 3 && vertices[1] == VectorRow::from([1.0, 0.0, 0.0]));
+        assert!(line_draw_order == [vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(read("v 0 0") == Err("Expected a vertex as \"v x y z\"."));
+        assert!(read("v 0 0 0\nf 1 0") == Err("Expected a vertex index."));
+        assert!(read("v 0 0 0\nl -2 1") == Err("Expected a vertex index."));
+        assert!(read("v 0 0 0\np 2") == Err("Element of a vertex not in the file."));
+    }
+}
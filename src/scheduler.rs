@@ -0,0 +1,162 @@
+use std::time::{Duration, Instant};
+
+/// Time simulated by each update of animations (e.g. auto rotation), independent of the frame rate.
+pub const STEP: Duration = Duration::from_micros(1_000_000 / 60);
+
+/// Most steps simulated for a single frame. After a stall (e.g. a suspended process) the simulation is
+/// slowed down rather than jumping ahead.
+const STEPS_MAX: u32 = 15;
+
+/// Remaining time to a deadline that is spun (yielding) rather than slept, as sleep tends to oversleep.
+const SPIN: Duration = Duration::from_micros(500);
+
+/// Paces frames to a target frame rate, and tells how many fixed [STEP]s to simulate for each.
+///
+/// Deadlines are kept on a fixed grid (`start + n * interval`), so that a slow frame does not shift
+/// every later one. Deadlines that pass while a frame is still being made are counted as dropped.
+pub struct FrameScheduler {
+    /// [None] renders as fast as possible.
+    interval: Option<Duration>,
+    deadline: Instant,
+    time_prev: Instant,
+    /// Time not yet simulated.
+    accumulator: Duration,
+    frame_time: Duration,
+    dropped: u64,
+}
+
+impl FrameScheduler {
+    pub fn new(fps: Option<u64>) -> Self {
+        let now = Instant::now();
+
+        Self {
+            interval: Self::interval(fps),
+            deadline: now,
+            time_prev: now,
+            accumulator: Duration::ZERO,
+            frame_time: Duration::ZERO,
+            dropped: 0,
+        }
+    }
+
+    fn interval(fps: Option<u64>) -> Option<Duration> {
+        fps.filter(|fps| *fps > 0)
+            .map(|fps| Duration::from_nanos(1_000_000_000 / fps))
+    }
+
+    /// Change the target frame rate. The next deadline is counted from the previous frame.
+    pub fn set_fps(&mut self, fps: Option<u64>) {
+        let interval = Self::interval(fps);
+
+        if interval != self.interval {
+            self.interval = interval;
+            self.deadline = self.time_prev + interval.unwrap_or_default();
+        }
+    }
+
+    /// Sleep until the deadline of the next frame. Returns the number of [STEP]s to simulate.
+    pub fn wait(&mut self) -> u32 {
+        if self.interval.is_some() {
+            loop {
+                let now = Instant::now();
+                if now >= self.deadline {
+                    break;
+                }
+
+                let left = self.deadline - now;
+                if left > SPIN {
+                    std::thread::sleep(left - SPIN);
+                } else {
+                    std::thread::yield_now();
+                }
+            }
+        }
+
+        self.begin(Instant::now())
+    }
+
+    /// Start a frame at `now`: move the deadline on, and advance the simulation clock.
+    fn begin(&mut self, now: Instant) -> u32 {
+        if let Some(interval) = self.interval {
+            let late = now.saturating_duration_since(self.deadline);
+            let missed = (late.as_nanos() / interval.as_nanos()) as u32;
+            self.dropped += missed as u64;
+            self.deadline += interval * (missed + 1);
+        }
+
+        self.frame_time = now.saturating_duration_since(self.time_prev);
+        self.time_prev = now;
+        self.accumulator += self.frame_time;
+
+        let steps = (self.accumulator.as_nanos() / STEP.as_nanos()) as u32;
+        if steps > STEPS_MAX {
+            self.accumulator = Duration::ZERO;
+            return STEPS_MAX;
+        }
+
+        self.accumulator -= STEP * steps;
+        steps
+    }
+
+    /// Frame rate, from the time since the previous frame.
+    pub fn fps(&self) -> u64 {
+        1_000_000_u128
+            .checked_div(self.frame_time.as_micros())
+            .unwrap_or(99999) as u64
+    }
+
+    /// Frames that missed their deadline so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn fixed_steps() {
+        let mut scheduler = FrameScheduler::new(None);
+        let start = scheduler.time_prev;
+
+        // 120 fps: a step every other frame.
+        let steps = (1..=12)
+            .map(|frame| scheduler.begin(start + STEP * frame / 2))
+            .sum::<u32>();
+        assert!(steps == 6);
+        assert!(scheduler.fps() == 120);
+        assert!(scheduler.dropped() == 0);
+
+        // A stall is not caught up on.
+        assert!(scheduler.begin(start + ms(10_000)) == STEPS_MAX);
+        assert!(scheduler.accumulator == Duration::ZERO);
+    }
+
+    #[test]
+    fn dropped_frames() {
+        let mut scheduler = FrameScheduler::new(Some(100));
+        let start = scheduler.deadline;
+
+        // On time, and slightly late.
+        scheduler.begin(start);
+        scheduler.begin(start + ms(12));
+        assert!(scheduler.dropped() == 0);
+        assert!(scheduler.deadline == start + ms(20));
+
+        // Missed the deadlines at 20 and 30 ms. The grid is kept.
+        scheduler.begin(start + ms(41));
+        assert!(scheduler.dropped() == 2);
+        assert!(scheduler.deadline == start + ms(50));
+
+        // Slower target, counted from the previous frame.
+        scheduler.set_fps(Some(10));
+        assert!(scheduler.deadline == start + ms(141));
+        scheduler.begin(start + ms(141));
+        assert!(scheduler.dropped() == 2);
+    }
+}
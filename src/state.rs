@@ -1,10 +1,21 @@
-use std::{cell::RefCell, rc::Rc, time::Instant};
+use std::{cell::RefCell, rc::Rc};
 
 use io::{platform::unix::EventHandler, Event, EventHandlerTrait, Modifier};
-use linear_algebra::quaternion::{self, Quaternion};
-use renderer::{Camera, ProjectionMode, RenderOption, RendererConfiguration, VectorRow, ViewMode};
-
-use crate::arg::Args;
+use linear_algebra::quaternion;
+use renderer::{
+    bounds::Bounds, overlay::Grid, Camera, Pick, ProjectionMode, RendererConfiguration, Selection,
+    VectorRow, ViewMode,
+};
+
+use crate::{
+    camera::{self, Fly, Orbit},
+    config::Config,
+    edit::Editor,
+    keymap::{Action, Keymap},
+    model::Model,
+    scheduler::{FrameScheduler, STEP},
+    view::{Transition, View, Views},
+};
 
 mod input {
     pub mod mouse {
@@ -17,32 +28,40 @@ mod input {
         #[derive(Default)]
         pub struct State {
             pub left: Option<Event>,
+            pub middle: Option<Event>,
             pub right: Option<Event>,
+            /// Scroll ticks, up is positive.
             pub scroll: Option<i32>,
             pub modifier: Option<io::Modifier>,
+            /// Where the left button went down, as reported (1-indexed cell).
+            pub pressed_at: Option<(u32, u32)>,
+            /// Left button released where it went down, without a modifier.
+            pub click: Option<(u32, u32)>,
         }
     }
 
     pub mod keyboard {
-        #![allow(non_snake_case)]
-        /// This is ugly :)
+        use std::collections::HashSet;
+
+        use crate::keymap::Action;
+
+        /// [Action]s triggered since last update.
         #[derive(Default)]
         pub struct State {
-            pub a: Option<()>,
-            pub r: Option<()>,
-            pub o: Option<()>,
-            pub O: Option<()>,
-            pub w: Option<()>,
-            pub v: Option<()>,
-            pub i: Option<()>,
-            pub j: Option<()>,
-            pub k: Option<()>,
-            pub l: Option<()>,
-            pub f: Option<()>,
-            pub F: Option<()>,
-            pub c: Option<()>,
-            pub plus: Option<()>,
-            pub minus: Option<()>,
+            pressed: HashSet<Action>,
+            /// Last digit 1-9 pressed, when not bound to an [Action]. Names a saved view.
+            pub digit: Option<u32>,
+        }
+
+        impl State {
+            pub fn press(&mut self, action: Action) {
+                self.pressed.insert(action);
+            }
+
+            /// Consume the [Action]. Returns true if it was triggered.
+            pub fn take(&mut self, action: Action) -> bool {
+                self.pressed.remove(&action)
+            }
         }
     }
 
@@ -62,6 +81,7 @@ mod input {
 
     pub mod auto {
         pub struct State {
+            /// Rotation per simulated step (see [crate::scheduler::STEP]).
             pub rot_diff: (f64, f64),
         }
 
@@ -94,18 +114,32 @@ mod input {
 }
 
 mod info {
-    use std::time::Instant;
-
-    use renderer::{RenderOption, VectorRow, ViewMode};
+    use renderer::{overlay::Grid, Pick, RenderOption, VectorRow, ViewMode};
 
     pub struct State {
         pub event_count: u64,
         pub position: VectorRow<f64, 3>,
+        /// Pitch and yaw. Roll of the first person camera is not included.
         pub rotation: (f64, f64),
+        /// Speed of the first person camera.
+        pub fly_speed: f64,
         pub invert_colors: bool,
-        pub time_prev: Instant,
+        /// Show the timings of the render stages.
+        pub profiler: bool,
+        pub gizmo: bool,
+        /// Ground grid to show, if any.
+        pub grid: Option<Grid>,
+        /// Picked element, and the position of its vertex.
+        pub selection: Option<(Pick, VectorRow<f64, 3>)>,
+        /// Why the last edit could not be made, if it could not. Kept until the next edit.
+        pub edit_error: Option<&'static str>,
+        pub focused: bool,
+        /// Terminal supports synchronized output (mode 2026).
+        pub synchronized_output: bool,
         pub fps: u64,
         pub fps_smoothened: u64,
+        /// Frames that missed their deadline, when the frame rate is capped.
+        pub dropped_frames: u64,
         pub view_mode: ViewMode,
         pub render_option: RenderOption,
     }
@@ -116,10 +150,18 @@ mod info {
                 event_count: 0,
                 position: VectorRow::from([0.0, 0.0, 0.0]),
                 rotation: Default::default(),
+                fly_speed: 0.0,
                 invert_colors: false,
-                time_prev: Instant::now(),
+                profiler: false,
+                gizmo: false,
+                grid: None,
+                selection: None,
+                edit_error: None,
+                focused: true,
+                synchronized_output: false,
                 fps: 0,
                 fps_smoothened: 0,
+                dropped_frames: 0,
                 view_mode: Default::default(),
                 render_option: Default::default(),
             }
@@ -127,70 +169,150 @@ mod info {
     }
 }
 
+/// Command line, opened with [Action::Console] or by pasting. Runs the name of an [Action], as in
+/// the keymap, or loads a model, as given to "--model".
+#[derive(Default)]
+pub struct Console {
+    pub line: String,
+    /// Why the line could not be run. Cleared when it is edited.
+    pub error: Option<&'static str>,
+}
+
+/// Frame rate used while the terminal is not focused, to not burn a core in the background.
+const UNFOCUSED_FPS: u64 = 4;
+
 /// The one thing that truly does a little bit too much.
 pub struct StateHandler {
-    pub args: Args,
+    pub config: Config,
+    pub keymap: Keymap,
     pub event_handler: EventHandler,
     pub vertices: Rc<RefCell<Vec<VectorRow<f64, 3>>>>,
     pub line_draw_order: Rc<RefCell<Vec<Vec<usize>>>>,
     input: input::State,
     info: info::State,
+    scheduler: FrameScheduler,
+    fly: Fly,
+    orbit: Orbit,
+    views: Views,
+    /// Next digit saves the view, rather than recalling it.
+    save_view: bool,
+    transition: Option<Transition>,
+    /// Set when editing the model, rather than only viewing it.
+    editor: Option<Editor>,
+    console: Option<Console>,
+    /// The model was changed from the console, and is yet to be shown.
+    model_changed: bool,
+    /// Failures that should not interrupt the session, reported on exit.
+    pub errors: Vec<String>,
 }
 
 impl StateHandler {
     pub fn new(
-        args: Args,
+        config: Config,
+        keymap: Keymap,
+        views: Views,
         event_handler: EventHandler,
         vertices: Rc<RefCell<Vec<VectorRow<f64, 3>>>>,
         line_draw_order: Rc<RefCell<Vec<Vec<usize>>>>,
     ) -> Self {
+        let info = info::State {
+            invert_colors: config.invert_colors,
+            gizmo: config.gizmo,
+            grid: match config.grid {
+                true => Self::grid(&vertices.borrow()),
+                false => None,
+            },
+            ..Default::default()
+        };
+
+        println!("\x1B[H\x1B[?2026$p"); // Query terminal for synchronized output support. (Move to first row before printing/receiving, because it will be cleared anyway.)
+
         Self {
-            args,
+            scheduler: FrameScheduler::new(config.fps),
+            config,
+            keymap,
             event_handler,
             vertices,
             line_draw_order,
             input: Default::default(),
-            info: Default::default(),
+            info,
+            fly: Fly::default(),
+            orbit: Orbit::default(),
+            views,
+            save_view: false,
+            transition: None,
+            editor: None,
+            console: None,
+            model_changed: false,
+            errors: Vec::new(),
         }
     }
 
-    pub fn update(&mut self, config: RendererConfiguration) -> RendererConfiguration {
-        loop {
-            let now = Instant::now();
-            self.info.fps = (1000_000_u128
-                .checked_div(now.duration_since(self.info.time_prev).as_micros()))
-            .unwrap_or(99999) as u64;
-
-            let target_fps = match self.args.fps {
-                Some(fps) => fps,
-                None => self.info.fps,
-            };
+    /// Grid on the ground plane below the vertices.
+    fn grid(vertices: &[VectorRow<f64, 3>]) -> Option<Grid> {
+        Bounds::from_vertices(vertices).map(|bounds| Grid::for_bounds(&bounds))
+    }
 
-            if self.info.fps <= target_fps {
-                self.info.time_prev = now;
-                break;
-            }
+    /// Start from `view`, without a transition.
+    pub fn show_view(&mut self, view: &View, camera: &mut renderer::Camera) {
+        self.orbit = view.apply(camera);
+        self.info.rotation = camera::pitch_yaw(&camera.rotation);
+    }
 
-            if let Some(fps) = self.args.fps {
-                if fps < 200 {
-                    // Use a mix of spin lock and actual sleep. This, 4 ms, is enough for sub 240 fps.
-                    std::thread::sleep(std::time::Duration::from_millis(4));
-                }
-            }
+    /// Edit the model, rather than only view it.
+    pub fn edit(&mut self, editor: Editor) {
+        self.editor = Some(editor);
+    }
+
+    pub fn editor(&self) -> Option<&Editor> {
+        self.editor.as_ref()
+    }
+
+    pub fn console(&self) -> Option<&Console> {
+        self.console.as_ref()
+    }
+
+    /// Whether the model was changed since last asked, so that its geometry is to be replaced.
+    pub fn take_model_changed(&mut self) -> bool {
+        std::mem::take(&mut self.model_changed)
+    }
+
+    /// Replace the geometry of the model, e.g. after it changed. Clears the selection, which may
+    /// no longer exist.
+    pub fn set_geometry(
+        &mut self,
+        vertices: Vec<VectorRow<f64, 3>>,
+        line_draw_order: Vec<Vec<usize>>,
+    ) {
+        *self.vertices.borrow_mut() = vertices;
+        *self.line_draw_order.borrow_mut() = line_draw_order;
+        self.info.selection = None;
+
+        if self.info.grid.is_some() {
+            self.info.grid = Self::grid(&self.vertices.borrow());
         }
+    }
+
+    /// Wait for the next frame, then apply the input and simulation steps since the previous one.
+    pub fn update(&mut self, config: RendererConfiguration) -> RendererConfiguration {
+        self.scheduler.set_fps(match self.config.fps {
+            _ if !self.info.focused => Some(UNFOCUSED_FPS),
+            fps => fps,
+        });
+        let steps = self.scheduler.wait();
+        self.info.fps = self.scheduler.fps();
+        self.info.dropped_frames = self.scheduler.dropped();
 
         let smooth_factor = 0.25;
         self.info.fps_smoothened = (smooth_factor * self.info.fps as f64
             + (1.0 - smooth_factor) * self.info.fps_smoothened as f64)
             as u64;
 
-        println!("\x1B[H\x1B[18t"); // Query terminal for size. (Move to first row before printing/receiving, because it will be cleared anyway.)
-
         while let Ok(event) = self.event_handler.latest_event() {
             // Batch handling - Read all inputs up until this point.
             self.handle_event(event);
         }
-        let config = self.update_config(config);
+        let config = self.update_config(config, steps);
 
         config
     }
@@ -198,11 +320,17 @@ impl StateHandler {
     fn handle_event(&mut self, event: Event) {
         self.info.event_count += 1;
 
+        // Keys go to the console while it is open.
+        if let (Some(_), Event::Character(c)) = (self.console.as_ref(), &event) {
+            self.type_console(*c);
+            return;
+        }
+
         if let Some(_) = self.input.auto.as_ref() {
             match event {
-                Event::Misc(_) => (),
-                Event::Character(c) => match c.to_ascii_lowercase() {
-                    'o' | 'v' | 'f' | 'c' => (),
+                Event::Misc(_) | Event::Focus(_) => (),
+                Event::Character(c) => match self.keymap.action(c) {
+                    Some(action) if action.keeps_auto() => (),
                     _ => self.input.auto = None,
                 },
                 Event::Mouse(ref _modifier, ref event) => match event {
@@ -238,15 +366,44 @@ impl StateHandler {
                                 }
                             } else {
                                 self.input.mouse.left =
-                                    Some(input::mouse::Event::Down(x as f64, -(y as f64)))
+                                    Some(input::mouse::Event::Down(x as f64, -(y as f64)));
+                                self.input.mouse.pressed_at = Some((x, y));
+                            }
+                        }
+                        io::mouse::Motion::Up => {
+                            if self.input.mouse.pressed_at.take() == Some((x, y))
+                                && self.input.mouse.modifier == Some(Modifier::None)
+                            {
+                                self.input.mouse.click = Some((x, y));
                             }
+                            self.input.mouse.left = None;
                         }
-                        io::mouse::Motion::Up => self.input.mouse.left = None,
                     },
 
                     io::mouse::Event::Middle(motion, x, y) => match motion {
-                        io::mouse::Motion::Down => (),
-                        io::mouse::Motion::Up => (),
+                        io::mouse::Motion::Down => {
+                            if let Some(event) = self.input.mouse.middle.as_ref() {
+                                match event {
+                                    input::mouse::Event::Down(x_, y_) => {
+                                        self.input.mouse.middle = Some(input::mouse::Event::Hold {
+                                            from: (*x_, *y_),
+                                            to: (x as f64, -(y as f64)),
+                                        });
+                                    }
+                                    input::mouse::Event::Hold { from, to: _ } => {
+                                        self.input.mouse.middle = Some(input::mouse::Event::Hold {
+                                            from: (from.0, from.1),
+                                            to: (x as f64, -(y as f64)),
+                                        });
+                                    }
+                                    input::mouse::Event::Up(_x, _y) => unreachable!(),
+                                }
+                            } else {
+                                self.input.mouse.middle =
+                                    Some(input::mouse::Event::Down(x as f64, -(y as f64)))
+                            }
+                        }
+                        io::mouse::Motion::Up => self.input.mouse.middle = None,
                     },
                     io::mouse::Event::Right(motion, x, y) => match motion {
                         io::mouse::Motion::Down => {
@@ -276,54 +433,107 @@ impl StateHandler {
                     io::mouse::Event::Scroll(direction) => match direction {
                         io::mouse::Direction::Down => match self.input.mouse.scroll.as_mut() {
                             Some(val) => *val -= 1,
-                            None => self.input.mouse.scroll = Some(-10),
+                            None => self.input.mouse.scroll = Some(-1),
                         },
                         io::mouse::Direction::Up => match self.input.mouse.scroll.as_mut() {
                             Some(val) => *val += 1,
-                            None => self.input.mouse.scroll = Some(10),
+                            None => self.input.mouse.scroll = Some(1),
                         },
                     },
                     _ => (),
                 }
             }
-            Event::Character(c) => match c {
-                'a' => self.input.keyboard.a = Some(()),
-                'r' => self.input.keyboard.r = Some(()),
-                'o' => self.input.keyboard.o = Some(()),
-                'O' => self.input.keyboard.O = Some(()),
-                'w' => self.input.keyboard.w = Some(()),
-                'v' => self.input.keyboard.v = Some(()),
-                'i' => self.input.keyboard.i = Some(()),
-                'j' => self.input.keyboard.j = Some(()),
-                'k' => self.input.keyboard.k = Some(()),
-                'l' => self.input.keyboard.l = Some(()),
-                'f' => self.input.keyboard.f = Some(()),
-                'F' => self.input.keyboard.F = Some(()),
-                'c' => self.input.keyboard.c = Some(()),
-                '+' => self.input.keyboard.plus = Some(()),
-                '-' => self.input.keyboard.minus = Some(()),
-                _ => (),
-            },
+            Event::Character(c) => {
+                // Opened right away, so that the keys that follow in the same batch go to it.
+                if let Some(Action::Console) = self.keymap.action(c) {
+                    self.console = Some(Default::default());
+                } else if let Some(action) = self.keymap.action(c) {
+                    self.input.keyboard.press(action);
+                } else if let Some(digit) = c.to_digit(10).filter(|digit| *digit > 0) {
+                    self.input.keyboard.digit = Some(digit);
+                }
+            }
+            Event::Paste(text) => {
+                // Line breaks do not run the line, so that nothing runs before it is seen.
+                let console = self.console.get_or_insert_with(Default::default);
+                console.line.extend(text.chars().map(|c| if c.is_control() { ' ' } else { c }));
+                console.error = None;
+            }
+            Event::Focus(focused) => self.info.focused = focused,
             Event::Misc(event) => match event {
                 io::misc::Event::CurrentSize(current_size) => {
                     self.input.misc.resize = Some((current_size.0, current_size.1))
                 }
+                io::misc::Event::ModeReport(report) => {
+                    if report.mode == 2026 {
+                        self.info.synchronized_output = report.supported;
+                    }
+                }
+            },
+        }
+    }
+
+    /// Edit the console line. Enter runs it, closing the console unless it fails, and Escape
+    /// closes it.
+    fn type_console(&mut self, c: char) {
+        let Some(mut console) = self.console.take() else {
+            return;
+        };
+        console.error = None;
+
+        match c {
+            '\r' | '\n' => match self.run(console.line.trim()) {
+                Ok(()) => return,
+                Err(msg) => console.error = Some(msg),
             },
+            '\x1b' => return,
+            '\x7f' | '\x08' => {
+                console.line.pop();
+            }
+            c if !c.is_control() => console.line.push(c),
+            _ => (),
+        }
+
+        self.console = Some(console);
+    }
+
+    /// Run a line of the console.
+    fn run(&mut self, line: &str) -> Result<(), &'static str> {
+        if line.is_empty() {
+            return Ok(());
         }
+
+        if let Ok(action) = line.parse::<Action>() {
+            self.input.keyboard.press(action);
+            return Ok(());
+        }
+
+        if self.editor.is_some() {
+            return Err("The model can not be changed while editing.");
+        }
+        self.config.model = line.parse::<Model>()?;
+        self.model_changed = true;
+
+        Ok(())
     }
 
-    fn update_config(&mut self, mut config: RendererConfiguration) -> RendererConfiguration {
-        if let Some(_) = self.input.keyboard.r.take() {
+    /// Apply input, and `steps` of simulation (see [crate::scheduler::STEP]).
+    fn update_config(
+        &mut self,
+        mut config: RendererConfiguration,
+        steps: u32,
+    ) -> RendererConfiguration {
+        if self.input.keyboard.take(Action::Reset) {
             // Reset
             self.info.rotation = Default::default();
-            config.camera = Camera::default();
-            if let Some(res) = self.args.resolution {
-                config.camera.resolution = res;
-            }
-            config.option = RenderOption::default();
+            self.fly.stop();
+            self.orbit = Orbit::default();
+            self.transition = None;
+            config.camera = self.config.camera();
+            config.option = self.config.render_option.clone();
         }
 
-        if let Some(_) = self.input.keyboard.o.take() {
+        if self.input.keyboard.take(Action::CycleRenderOption) {
             // Toggle render option
             config.option = match config.option {
                 renderer::RenderOption::Vertices => renderer::RenderOption::WireFrame,
@@ -342,7 +552,7 @@ impl StateHandler {
             };
         }
 
-        if let Some(_) = self.input.keyboard.O.take() {
+        if self.input.keyboard.take(Action::CycleRenderOptionBack) {
             // Toggle render option
             config.option = match config.option {
                 renderer::RenderOption::Vertices => {
@@ -361,7 +571,7 @@ impl StateHandler {
             };
         }
 
-        if let Some(_) = self.input.keyboard.F.take() {
+        if self.input.keyboard.take(Action::FovIncrease) {
             if let ProjectionMode::Perspective { fov } = config.camera.projection_mode {
                 // Increase fov
                 config.camera.projection_mode = ProjectionMode::Perspective {
@@ -370,7 +580,7 @@ impl StateHandler {
             }
         }
 
-        if let Some(_) = self.input.keyboard.f.take() {
+        if self.input.keyboard.take(Action::FovDecrease) {
             if let ProjectionMode::Perspective { fov } = config.camera.projection_mode {
                 // Decrease fov
                 config.camera.projection_mode = ProjectionMode::Perspective {
@@ -379,7 +589,7 @@ impl StateHandler {
             }
         }
 
-        if let None = self.args.resolution {
+        if let None = self.config.resolution {
             if let Some(new_size) = self.input.misc.resize {
                 // Resize
                 config.camera.resolution.0 = new_size.0;
@@ -398,20 +608,64 @@ impl StateHandler {
             }
         }
 
-        if let Some(_) = self.input.keyboard.i.take() {
-            rot_diff.0 -= std::f64::consts::FRAC_PI_8;
+        let first_person = matches!(config.camera.view_mode, ViewMode::FirstPerson);
+
+        if self.input.keyboard.take(Action::RotateUp) {
+            match first_person {
+                true => self.fly.spin(0, -1.0),
+                false => rot_diff.0 -= std::f64::consts::FRAC_PI_8,
+            }
+        }
+
+        if self.input.keyboard.take(Action::RotateDown) {
+            match first_person {
+                true => self.fly.spin(0, 1.0),
+                false => rot_diff.0 += std::f64::consts::FRAC_PI_8,
+            }
+        }
+
+        if self.input.keyboard.take(Action::RotateLeft) {
+            match first_person {
+                true => self.fly.spin(2, -1.0),
+                false => rot_diff.1 -= std::f64::consts::FRAC_PI_8,
+            }
         }
 
-        if let Some(_) = self.input.keyboard.k.take() {
-            rot_diff.0 += std::f64::consts::FRAC_PI_8;
+        if self.input.keyboard.take(Action::RotateRight) {
+            match first_person {
+                true => self.fly.spin(2, 1.0),
+                false => rot_diff.1 += std::f64::consts::FRAC_PI_8,
+            }
         }
 
-        if let Some(_) = self.input.keyboard.j.take() {
-            rot_diff.1 -= std::f64::consts::FRAC_PI_8;
+        // Free-fly movement, in first person view only.
+        for (action, direction) in [
+            (Action::MoveForward, [0.0, 1.0, 0.0]),
+            (Action::MoveBack, [0.0, -1.0, 0.0]),
+            (Action::MoveLeft, [-1.0, 0.0, 0.0]),
+            (Action::MoveRight, [1.0, 0.0, 0.0]),
+            (Action::MoveUp, [0.0, 0.0, 1.0]),
+            (Action::MoveDown, [0.0, 0.0, -1.0]),
+        ] {
+            if self.input.keyboard.take(action) && first_person {
+                self.fly.thrust(&config.camera, direction);
+            }
         }
 
-        if let Some(_) = self.input.keyboard.l.take() {
-            rot_diff.1 += std::f64::consts::FRAC_PI_8;
+        if self.input.keyboard.take(Action::RollLeft) && first_person {
+            self.fly.spin(1, -1.0);
+        }
+
+        if self.input.keyboard.take(Action::RollRight) && first_person {
+            self.fly.spin(1, 1.0);
+        }
+
+        if self.input.keyboard.take(Action::SpeedIncrease) {
+            self.fly.faster();
+        }
+
+        if self.input.keyboard.take(Action::SpeedDecrease) {
+            self.fly.slower();
         }
 
         // Calculate positional change(s) based on input.
@@ -427,49 +681,92 @@ impl StateHandler {
             }
         }
 
-        if let Some(val) = self.input.mouse.scroll.take() {
-            pos_diff[1] += (val * 10) as f64;
+        // Dolly steps, in (positive) or out.
+        let mut dolly = self.input.mouse.scroll.take().unwrap_or(0) as f64;
+
+        if self.input.keyboard.take(Action::ZoomOut) {
+            dolly -= 1.0;
         }
 
-        if let Some(_) = self.input.keyboard.minus.take() {
-            pos_diff[1] -= 10 as f64;
+        if self.input.keyboard.take(Action::ZoomIn) {
+            dolly += 1.0;
         }
 
-        if let Some(_) = self.input.keyboard.plus.take() {
-            pos_diff[1] += 10 as f64;
+        if let ViewMode::FirstPerson = config.camera.view_mode {
+            pos_diff[1] += dolly * 10.0;
+        } else {
+            self.orbit.dolly(dolly);
         }
 
-        if let Some(_) = self.input.keyboard.v.take() {
-            // Toggle view mode and adjust position.
-            if let ProjectionMode::Perspective { fov } = config.camera.projection_mode {
-                // Undo any current rotation.
-                let mut pos = quaternion::rotate(
-                    &config.camera.position,
-                    &config.camera.rotation.1,
-                    &config.camera.rotation.0,
-                );
+        // Pan the orbit target, in pixels.
+        let mut pan = (0.0, 0.0);
+
+        if let Some(input::mouse::Event::Hold { from, to }) = self.input.mouse.middle.as_mut() {
+            pan = (to.0 - from.0, (to.1 - from.1) * 2.0); // "* 2" because a character is two pixels high.
+            *from = *to;
+        }
+
+        if self.input.keyboard.take(Action::FrameAll) {
+            if let Some(bounds) = Bounds::from_vertices(&self.vertices.borrow()) {
+                self.orbit
+                    .frame(&config.camera, self.config.pixel_aspect, &bounds);
+
+                if let ViewMode::FirstPerson = config.camera.view_mode {
+                    config.camera.position = self.orbit.viewpoint(&config.camera);
+                }
+            }
+        }
 
+        if self.input.keyboard.take(Action::ToggleViewMode) {
+            // Toggle view mode and adjust position, keeping the view.
+            if let ProjectionMode::Perspective { .. } = config.camera.projection_mode {
                 config.camera.view_mode = match config.camera.view_mode {
                     renderer::ViewMode::FirstPerson => {
-                        pos[1] += (config.camera.resolution.0 as f64 / 2.0)
-                            / f64::tan((fov as f64 / 2.0) * (std::f64::consts::PI / 180.0));
+                        self.orbit = Orbit::ahead_of(&config.camera);
                         renderer::ViewMode::Orbital
                     }
                     renderer::ViewMode::Orbital => {
-                        pos[1] -= (config.camera.resolution.0 as f64 / 2.0)
-                            / f64::tan((fov as f64 / 2.0) * (std::f64::consts::PI / 180.0));
+                        config.camera.position = self.orbit.viewpoint(&config.camera);
                         renderer::ViewMode::FirstPerson
                     }
                 };
+            }
+
+            self.fly.stop();
+        }
 
-                // Re-apply rotation.
-                config.camera.position =
-                    quaternion::rotate(&pos, &config.camera.rotation.0, &config.camera.rotation.1);
+        // Saved views, named by digit.
+        if self.input.keyboard.take(Action::SaveView) {
+            self.save_view = !self.save_view;
+        }
+
+        if let Some(digit) = self.input.keyboard.digit.take() {
+            let name = digit.to_string();
+
+            if self.save_view {
+                self.save_view = false;
+                self.views.set(name, View::of(&config.camera, &self.orbit));
+
+                if let Err(msg) = self.views.save() {
+                    self.errors.push(format!("Failed to save views. {msg}"));
+                }
+            } else if let Some(view) = self.views.get(&name).cloned() {
+                let steps = (self.config.view_transition / STEP.as_secs_f64()).round() as u32;
+
+                match steps {
+                    0 => self.show_view(&view, &mut config.camera),
+                    _ => {
+                        self.transition =
+                            Some(Transition::new(&config.camera, &self.orbit, view, steps))
+                    }
+                }
+
+                self.fly.stop();
             }
         }
 
         // Automatic mode?
-        if let Some(()) = self.input.keyboard.a.take() {
+        if self.input.keyboard.take(Action::ToggleAuto) {
             if let None = self.input.auto {
                 self.input.auto = Some(input::auto::State::default());
             } else {
@@ -479,11 +776,19 @@ impl StateHandler {
 
         if let Some(auto) = self.input.auto.as_ref() {
             self.info.rotation.0 = -std::f64::consts::FRAC_PI_6;
-            rot_diff = auto.rot_diff;
-        }
+            rot_diff = (auto.rot_diff.0 * steps as f64, auto.rot_diff.1 * steps as f64);
+        } else if let ViewMode::FirstPerson = config.camera.view_mode {
+            // Turn about the camera's own axes, without limits.
+            config.camera.rotation =
+                camera::rotate_local(&config.camera.rotation, [rot_diff.0, 0.0, rot_diff.1]);
+            rot_diff = (0.0, 0.0);
+
+            for _ in 0..steps {
+                self.fly.step(&mut config.camera, STEP.as_secs_f64());
+            }
 
-        // Apply updated rotation on positional change.
-        let old_rotation = config.camera.rotation.clone();
+            self.info.rotation = camera::pitch_yaw(&config.camera.rotation);
+        }
 
         if !(rot_diff.0 == 0.0 && rot_diff.1 == 0.0) {
             self.info.rotation = (
@@ -492,18 +797,7 @@ impl StateHandler {
                     .max(-std::f64::consts::FRAC_PI_2),
                 (self.info.rotation.1 + rot_diff.1) % (std::f64::consts::PI * 2.0),
             );
-
-            let rotation = (self.info.rotation.0 / 2.0, self.info.rotation.1 / 2.0); // Half angles for quaternions.
-            let pitch = Quaternion(
-                rotation.0.cos(),
-                rotation.0.sin() * (rotation.1 * 2.0).cos(),
-                rotation.0.sin() * (rotation.1 * 2.0).sin(),
-                0.0,
-            );
-            let yaw = Quaternion(rotation.1.cos(), 0.0, 0.0, rotation.1.sin());
-            let rotation = &pitch * &yaw;
-            let rotation_prim = rotation.inverse();
-            config.camera.rotation = (rotation, rotation_prim);
+            config.camera.rotation = camera::rotation(self.info.rotation.0, self.info.rotation.1);
         }
 
         match config.camera.view_mode {
@@ -516,27 +810,156 @@ impl StateHandler {
                 config.camera.position = (&config.camera.position.0 + &pos_diff.0).into();
             }
             ViewMode::Orbital => {
-                let mut pos =
-                    quaternion::rotate(&config.camera.position, &old_rotation.1, &old_rotation.0);
-                pos = (&pos.0 + &pos_diff.0).into();
-                config.camera.position =
-                    quaternion::rotate(&pos, &config.camera.rotation.0, &config.camera.rotation.1)
+                self.orbit
+                    .pan(&config.camera, self.config.pixel_aspect, pan.0, pan.1);
+                config.camera.position = self.orbit.position(&config.camera);
             }
         }
 
+        if let Some(transition) = self.transition.as_mut() {
+            // Overrides other movement while it lasts.
+            let view = transition.advance(steps);
+            let done = transition.done();
+            self.show_view(&view, &mut config.camera);
+            self.fly.stop();
+
+            if done {
+                self.transition = None;
+            }
+        }
+
+        self.update_editor(&config.camera);
+
         // Store info
         self.info.position = config.camera.position.clone();
         // self.info.rotation = Already done.
+        self.info.fly_speed = self.fly.speed();
         self.info.render_option = config.option.clone();
         self.info.view_mode = config.camera.view_mode.clone();
 
-        if let Some(_) = self.input.keyboard.c.take() {
+        if self.input.keyboard.take(Action::InvertColors) {
             self.info.invert_colors = !self.info.invert_colors;
         }
 
+        if self.input.keyboard.take(Action::ToggleProfiler) {
+            self.info.profiler = !self.info.profiler;
+        }
+
+        if self.input.keyboard.take(Action::ToggleGizmo) {
+            self.info.gizmo = !self.info.gizmo;
+        }
+
+        if self.input.keyboard.take(Action::ToggleGrid) {
+            self.info.grid = match self.info.grid {
+                Some(_) => None,
+                None => Self::grid(&self.vertices.borrow()),
+            };
+        }
+
         config
     }
 
+    /// Apply edit actions to the model, if editing. Vertices move along the axes of the camera.
+    fn update_editor(&mut self, camera: &Camera) {
+        let moves = [
+            (Action::MoveSelectionLeft, [-1.0, 0.0, 0.0]),
+            (Action::MoveSelectionRight, [1.0, 0.0, 0.0]),
+            (Action::MoveSelectionAway, [0.0, 1.0, 0.0]),
+            (Action::MoveSelectionToward, [0.0, -1.0, 0.0]),
+            (Action::MoveSelectionUp, [0.0, 0.0, 1.0]),
+            (Action::MoveSelectionDown, [0.0, 0.0, -1.0]),
+        ]
+        .map(|(action, direction)| (self.input.keyboard.take(action), direction));
+        let add_face = self.input.keyboard.take(Action::AddFace);
+        let delete_face = self.input.keyboard.take(Action::DeleteFace);
+        let undo = self.input.keyboard.take(Action::Undo);
+        let redo = self.input.keyboard.take(Action::Redo);
+        let save = self.input.keyboard.take(Action::SaveModel);
+        let pressed = moves.iter().any(|(pressed, _)| *pressed)
+            || add_face
+            || delete_face
+            || undo
+            || redo
+            || save;
+
+        let Some(editor) = self.editor.as_mut() else {
+            return;
+        };
+        let mut vertices = self.vertices.borrow_mut();
+        let mut line_draw_order = self.line_draw_order.borrow_mut();
+
+        for (_, direction) in moves.into_iter().filter(|(pressed, _)| *pressed) {
+            let direction = direction.map(|d| d * editor.step);
+            let offset =
+                quaternion::rotate(&direction.into(), &camera.rotation.0, &camera.rotation.1);
+            editor.move_selected(&offset, &mut vertices, &mut line_draw_order);
+        }
+
+        let mut failed = None;
+
+        if add_face {
+            failed = editor.add_face(&mut vertices, &mut line_draw_order).err();
+        }
+
+        if delete_face {
+            failed = editor.delete_face(&mut vertices, &mut line_draw_order).err();
+        }
+
+        if undo {
+            editor.undo(&mut vertices, &mut line_draw_order);
+        }
+
+        if redo {
+            editor.redo(&mut vertices, &mut line_draw_order);
+        }
+
+        if save {
+            if let Err(msg) = editor.save(&vertices, &line_draw_order) {
+                self.errors.push(format!("Failed to save model. {msg}"));
+            }
+        }
+
+        if pressed {
+            self.info.edit_error = failed;
+        }
+
+        // Follow the picked vertex as it moves, and the selected face.
+        if let Some((pick, position)) = self.info.selection.as_mut() {
+            *position = vertices[pick.vertex].clone();
+            pick.face = editor.face;
+        }
+    }
+
+    /// Cell clicked since the last call, to be picked from what was drawn there.
+    pub fn take_click(&mut self) -> Option<(u32, u32)> {
+        self.input.mouse.click.take()
+    }
+
+    /// Select what was picked, or clear the selection if nothing was. When editing, picked vertices
+    /// are added to (or removed from) the selection instead.
+    pub fn select(&mut self, pick: Option<Pick>) {
+        if let Some(editor) = self.editor.as_mut() {
+            editor.select(pick.as_ref());
+        }
+
+        self.info.selection = pick.and_then(|pick| {
+            let position = self.vertices.borrow().get(pick.vertex)?.clone();
+            Some((pick, position))
+        });
+    }
+
+    /// What to highlight.
+    pub fn selection(&self) -> Selection {
+        match (self.editor.as_ref(), self.info.selection.as_ref()) {
+            (Some(editor), _) => editor.selection(),
+            (None, Some((pick, _))) => Selection {
+                face: pick.face,
+                vertices: vec![pick.vertex],
+            },
+            (None, None) => Selection::default(),
+        }
+    }
+
     pub fn info(&self) -> &info::State {
         &self.info
     }
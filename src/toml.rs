@@ -0,0 +1,385 @@
+/// Minimal reader for the subset of TOML used by the configuration files.
+/// Supports tables ("[a.b]"), dotted and quoted keys, and string, integer, float,
+/// boolean, and single line array values. Everything else is reported as an error.
+use std::{collections::BTreeMap, fmt::Display, iter::Peekable, str::Chars};
+
+pub type Table = BTreeMap<String, Value>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Integers are accepted as floats as well.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Float(f) => Some(*f),
+            Value::Integer(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_table(&self) -> Option<&Table> {
+        match self {
+            Value::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Error {
+    pub line: usize,
+    pub msg: &'static str,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+struct Cursor<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(line: &'a str) -> Self {
+        Self {
+            chars: line.chars().peekable(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+    }
+
+    /// True if only whitespace and/or a comment remains.
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        matches!(self.chars.peek(), None | Some('#'))
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), &'static str> {
+        self.skip_whitespace();
+
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err("Unexpected character."),
+        }
+    }
+
+    /// Dotted key, e.g. 'a.b."c d"'.
+    fn key(&mut self) -> Result<Vec<String>, &'static str> {
+        let mut path = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+
+            let part = match self.chars.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let mut part = String::new();
+
+                    while let Some(c) = self
+                        .chars
+                        .next_if(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+                    {
+                        part.push(c);
+                    }
+
+                    if part.is_empty() {
+                        return Err("Expected key.");
+                    }
+
+                    part
+                }
+            };
+            path.push(part);
+
+            self.skip_whitespace();
+
+            if self.chars.next_if_eq(&'.').is_none() {
+                return Ok(path);
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, &'static str> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some('"') => Ok(Value::String(self.basic_string()?)),
+            Some('\'') => Ok(Value::String(self.literal_string()?)),
+            Some('[') => self.array(),
+            Some(_) => {
+                let mut word = String::new();
+
+                while let Some(c) = self
+                    .chars
+                    .next_if(|c| c.is_ascii_alphanumeric() || "+-._".contains(*c))
+                {
+                    word.push(c);
+                }
+
+                match word.as_str() {
+                    "true" => Ok(Value::Boolean(true)),
+                    "false" => Ok(Value::Boolean(false)),
+                    _ => {
+                        let number = word.replace('_', "");
+
+                        if let Ok(integer) = number.parse() {
+                            Ok(Value::Integer(integer))
+                        } else if let Ok(float) = number.parse() {
+                            Ok(Value::Float(float))
+                        } else {
+                            Err("Invalid value.")
+                        }
+                    }
+                }
+            }
+            None => Err("Expected value."),
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, &'static str> {
+        self.expect('[')?;
+        let mut array = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+
+            if self.chars.next_if_eq(&']').is_some() {
+                return Ok(Value::Array(array));
+            }
+
+            array.push(self.value()?);
+            self.skip_whitespace();
+
+            match self.chars.next() {
+                Some(',') => (),
+                Some(']') => return Ok(Value::Array(array)),
+                _ => return Err("Expected ',' or ']' in array."),
+            }
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, &'static str> {
+        self.expect('"')?;
+        let mut s = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.chars.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('u') => {
+                        let hex = (0..4)
+                            .filter_map(|_| self.chars.next())
+                            .collect::<String>();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or("Invalid unicode escape.")?;
+                        s.push(c);
+                    }
+                    _ => return Err("Invalid escape sequence."),
+                },
+                Some(c) => s.push(c),
+                None => return Err("Unterminated string."),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, &'static str> {
+        self.expect('\'')?;
+        let mut s = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('\'') => return Ok(s),
+                Some(c) => s.push(c),
+                None => return Err("Unterminated string."),
+            }
+        }
+    }
+}
+
+/// Get (or create) the table at the given path.
+fn table_mut<'a>(root: &'a mut Table, path: &[String]) -> Result<&'a mut Table, &'static str> {
+    let mut table = root;
+
+    for part in path {
+        let value = table
+            .entry(part.clone())
+            .or_insert_with(|| Value::Table(Table::new()));
+
+        table = match value {
+            Value::Table(table) => table,
+            _ => return Err("Key is already defined as a non-table."),
+        };
+    }
+
+    Ok(table)
+}
+
+pub fn parse(s: &str) -> Result<Table, Error> {
+    let mut root = Table::new();
+    let mut current: Vec<String> = Vec::new();
+
+    for (index, line) in s.lines().enumerate() {
+        let error = |msg| Error {
+            line: index + 1,
+            msg,
+        };
+        let mut cursor = Cursor::new(line);
+
+        if cursor.at_end() {
+            continue;
+        }
+
+        if cursor.chars.next_if_eq(&'[').is_some() {
+            current = cursor.key().map_err(error)?;
+            cursor.expect(']').map_err(error)?;
+            table_mut(&mut root, &current).map_err(error)?;
+        } else {
+            let mut key = cursor.key().map_err(error)?;
+            cursor.expect('=').map_err(error)?;
+            let value = cursor.value().map_err(error)?;
+            let name = key.pop().unwrap();
+            let path = [current.as_slice(), key.as_slice()].concat();
+            let table = table_mut(&mut root, &path).map_err(error)?;
+
+            if table.insert(name, value).is_some() {
+                return Err(error("Duplicate key."));
+            }
+        }
+
+        if !cursor.at_end() {
+            return Err(error("Unexpected trailing characters."));
+        }
+    }
+
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        let table = parse(
+            r#"
+            # Comment
+            string = "a \"b\" \u00e5" # Trailing comment
+            literal = 'c:\d'
+            integer = -1_000
+            float = 6.5e1
+            yes = true
+            no = false
+            array = ["+", 'x', 1, [2]]
+            empty = []
+            "#,
+        )
+        .unwrap();
+
+        assert!(table["string"].as_str() == Some("a \"b\" å"));
+        assert!(table["literal"].as_str() == Some("c:\\d"));
+        assert!(table["integer"].as_integer() == Some(-1000));
+        assert!(table["float"].as_float() == Some(65.0));
+        assert!(table["integer"].as_float() == Some(-1000.0));
+        assert!(table["yes"].as_bool() == Some(true));
+        assert!(table["no"].as_bool() == Some(false));
+        assert!(
+            table["array"]
+                == Value::Array(vec![
+                    Value::String("+".into()),
+                    Value::String("x".into()),
+                    Value::Integer(1),
+                    Value::Array(vec![Value::Integer(2)]),
+                ])
+        );
+        assert!(table["empty"] == Value::Array(vec![]));
+    }
+
+    #[test]
+    fn tables() {
+        let table = parse(
+            r#"
+            top = 1
+            [a]
+            b.c = 2
+            [a."d e"]
+            f = 3
+            "#,
+        )
+        .unwrap();
+
+        let a = table["a"].as_table().unwrap();
+        assert!(table["top"].as_integer() == Some(1));
+        assert!(a["b"].as_table().unwrap()["c"].as_integer() == Some(2));
+        assert!(a["d e"].as_table().unwrap()["f"].as_integer() == Some(3));
+    }
+
+    #[test]
+    fn errors() {
+        let tester = |s: &str, line: usize| {
+            let result = parse(s);
+            assert!(
+                matches!(result, Err(Error { line: l, .. }) if l == line),
+                "Input: {s:?}. Actual: {:?}",
+                result
+            );
+        };
+
+        tester("a = ", 1);
+        tester("a = 1\na = 2", 2);
+        tester("\n\na = \"unterminated", 3);
+        tester("a = 1 2", 1);
+        tester("a = [1, 2", 1);
+        tester("[a", 1);
+        tester("a = 1\n[a]", 2);
+        tester("= 1", 1);
+        tester("a = nope", 1);
+    }
+}
@@ -0,0 +1,427 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use linear_algebra::quaternion::Quaternion;
+use renderer::{Camera, ProjectionMode, VectorRow, ViewMode};
+
+use crate::{camera::Orbit, config, toml};
+
+/// A saved viewpoint. The resolution is left out, as it follows the terminal.
+#[derive(Clone)]
+pub struct View {
+    pub position: VectorRow<f64, 3>,
+    pub rotation: Quaternion<f64>,
+    pub view_mode: ViewMode,
+    pub projection_mode: ProjectionMode,
+    /// What the orbit camera looks at. Kept for first person views too, to orbit after switching.
+    pub orbit: Orbit,
+}
+
+impl View {
+    pub fn of(camera: &Camera, orbit: &Orbit) -> Self {
+        Self {
+            position: camera.position.clone(),
+            rotation: camera.rotation.0.clone(),
+            view_mode: camera.view_mode.clone(),
+            projection_mode: camera.projection_mode.clone(),
+            orbit: orbit.clone(),
+        }
+    }
+
+    /// Set up `camera` as the view, and return what to orbit.
+    pub fn apply(&self, camera: &mut Camera) -> Orbit {
+        camera.position = self.position.clone();
+        camera.rotation = (self.rotation.clone(), self.rotation.inverse());
+        camera.view_mode = self.view_mode.clone();
+        camera.projection_mode = self.projection_mode.clone();
+
+        if let ViewMode::Orbital = camera.view_mode {
+            camera.position = self.orbit.position(camera);
+        }
+
+        self.orbit.clone()
+    }
+
+    /// The same view, seen in another [ViewMode]. `camera` gives the resolution.
+    fn in_view_mode(&self, view_mode: &ViewMode, camera: &Camera) -> Self {
+        let mut camera = camera.clone();
+        let orbit = self.apply(&mut camera);
+        let mut view = self.clone();
+
+        match (&self.view_mode, view_mode) {
+            (ViewMode::FirstPerson, ViewMode::Orbital) => view.orbit = Orbit::ahead_of(&camera),
+            (ViewMode::Orbital, ViewMode::FirstPerson) => view.position = orbit.viewpoint(&camera),
+            _ => (),
+        }
+
+        view.view_mode = view_mode.clone();
+        view
+    }
+
+    fn from_toml(table: &toml::Table) -> Result<Self, String> {
+        let get = |key: &str| table.get(key).ok_or_else(|| format!("\"{key}\": Missing."));
+        let floats = |key: &str, len: usize| -> Result<Vec<f64>, String> {
+            get(key)?
+                .as_array()
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(toml::Value::as_float)
+                        .collect::<Vec<_>>()
+                })
+                .filter(|values| values.len() == len)
+                .ok_or_else(|| format!("\"{key}\": Expected {len} numbers."))
+        };
+
+        let position = floats("position", 3)?;
+        let rotation = floats("rotation", 4)?;
+        let target = floats("target", 3)?;
+
+        Ok(Self {
+            position: VectorRow::from([position[0], position[1], position[2]]),
+            rotation: normalized(Quaternion(
+                rotation[0],
+                rotation[1],
+                rotation[2],
+                rotation[3],
+            ))
+            .ok_or("\"rotation\": Expected a non-zero quaternion.")?,
+            view_mode: match get("view_mode")?.as_str() {
+                Some("first_person") => ViewMode::FirstPerson,
+                Some("orbital") => ViewMode::Orbital,
+                _ => return Err("\"view_mode\": Expected \"first_person\" or \"orbital\".".into()),
+            },
+            projection_mode: match table.get("fov") {
+                Some(fov) => ProjectionMode::Perspective {
+                    fov: fov
+                        .as_integer()
+                        .filter(|fov| (1..=170).contains(fov))
+                        .ok_or("\"fov\": Expected integer in the range [1,170].")?
+                        as u64,
+                },
+                None => ProjectionMode::Orthographic,
+            },
+            orbit: Orbit {
+                target: VectorRow::from([target[0], target[1], target[2]]),
+                distance: get("distance")?
+                    .as_float()
+                    .filter(|distance| *distance > 0.0)
+                    .ok_or("\"distance\": Expected positive number.")?,
+            },
+        })
+    }
+
+    fn to_toml(&self) -> String {
+        let vector = |vector: &VectorRow<f64, 3>| {
+            format!("[{:?}, {:?}, {:?}]", vector[0], vector[1], vector[2])
+        };
+        let rotation = &self.rotation;
+        let mut s = format!(
+            "view_mode = \"{}\"\nposition = {}\nrotation = [{:?}, {:?}, {:?}, {:?}]\n",
+            match self.view_mode {
+                ViewMode::FirstPerson => "first_person",
+                ViewMode::Orbital => "orbital",
+            },
+            vector(&self.position),
+            rotation.0,
+            rotation.1,
+            rotation.2,
+            rotation.3,
+        );
+
+        // Orthographic if left out.
+        if let ProjectionMode::Perspective { fov } = self.projection_mode {
+            s += &format!("fov = {fov}\n");
+        }
+
+        s + &format!(
+            "target = {}\ndistance = {:?}\n",
+            vector(&self.orbit.target),
+            self.orbit.distance
+        )
+    }
+}
+
+fn normalized(q: Quaternion<f64>) -> Option<Quaternion<f64>> {
+    let length = (q.0 * q.0 + q.1 * q.1 + q.2 * q.2 + q.3 * q.3).sqrt();
+    (length > f64::EPSILON)
+        .then(|| Quaternion(q.0 / length, q.1 / length, q.2 / length, q.3 / length))
+}
+
+/// Spherical interpolation between rotations, the shorter way around.
+fn slerp(a: &Quaternion<f64>, b: &Quaternion<f64>, t: f64) -> Quaternion<f64> {
+    let dot = a.0 * b.0 + a.1 * b.1 + a.2 * b.2 + a.3 * b.3;
+    // A quaternion and its negation are the same rotation.
+    let (b, dot) = match dot < 0.0 {
+        true => (Quaternion(-b.0, -b.1, -b.2, -b.3), -dot),
+        false => (b.clone(), dot),
+    };
+
+    let (weight_a, weight_b) = if dot > 0.9995 {
+        // Close enough to interpolate linearly, and avoid dividing by a tiny sine.
+        (1.0 - t, t)
+    } else {
+        let angle = dot.acos();
+        (
+            ((1.0 - t) * angle).sin() / angle.sin(),
+            (t * angle).sin() / angle.sin(),
+        )
+    };
+
+    normalized(Quaternion(
+        weight_a * a.0 + weight_b * b.0,
+        weight_a * a.1 + weight_b * b.1,
+        weight_a * a.2 + weight_b * b.2,
+        weight_a * a.3 + weight_b * b.3,
+    ))
+    .unwrap_or_else(|| a.clone())
+}
+
+fn lerp(a: &VectorRow<f64, 3>, b: &VectorRow<f64, 3>, t: f64) -> VectorRow<f64, 3> {
+    VectorRow::from(std::array::from_fn(|axis| {
+        a[axis] + (b[axis] - a[axis]) * t
+    }))
+}
+
+/// Animated move from one view to another, over a number of simulation steps.
+pub struct Transition {
+    from: View,
+    to: View,
+    step: u32,
+    steps: u32,
+}
+
+impl Transition {
+    /// Move from where `camera` is to `to`, in `steps`. `camera` gives the resolution.
+    pub fn new(camera: &Camera, orbit: &Orbit, to: View, steps: u32) -> Self {
+        // Switch view mode at the start, without changing the view.
+        let from = View::of(camera, orbit).in_view_mode(&to.view_mode, camera);
+
+        Self {
+            from,
+            to,
+            step: 0,
+            steps: steps.max(1),
+        }
+    }
+
+    pub fn done(&self) -> bool {
+        self.step >= self.steps
+    }
+
+    /// Advance `steps`, and return the view at that point.
+    pub fn advance(&mut self, steps: u32) -> View {
+        self.step = (self.step + steps).min(self.steps);
+        let t = self.step as f64 / self.steps as f64;
+        let t = t * t * (3.0 - 2.0 * t); // Ease in and out.
+
+        View {
+            position: lerp(&self.from.position, &self.to.position, t),
+            rotation: slerp(&self.from.rotation, &self.to.rotation, t),
+            view_mode: self.to.view_mode.clone(),
+            projection_mode: match (&self.from.projection_mode, &self.to.projection_mode) {
+                (
+                    ProjectionMode::Perspective { fov: from },
+                    ProjectionMode::Perspective { fov: to },
+                ) => ProjectionMode::Perspective {
+                    fov: (*from as f64 + (*to as f64 - *from as f64) * t).round() as u64,
+                },
+                (_, projection_mode) => projection_mode.clone(),
+            },
+            orbit: Orbit {
+                target: lerp(&self.from.orbit.target, &self.to.orbit.target, t),
+                // Geometric, to match the exponential dolly.
+                distance: self.from.orbit.distance
+                    * (self.to.orbit.distance / self.from.orbit.distance).powf(t),
+            },
+        }
+    }
+}
+
+/// Named viewpoints, saved in "views.toml" in the configuration directory, e.g:
+/// ```toml
+/// [1]
+/// view_mode = "orbital"
+/// position = [0.0, -120.5, 40.0]
+/// rotation = [0.96, -0.26, 0.0, 0.0]
+/// fov = 90
+/// target = [0.0, 0.0, 0.0]
+/// distance = 1.5
+/// ```
+/// Keys 1-9 recall (and save) the views named by the digit. Any view can be restored on launch with "--view".
+#[derive(Default)]
+pub struct Views {
+    views: BTreeMap<String, View>,
+}
+
+impl Views {
+    fn path() -> Option<PathBuf> {
+        config::dir().map(|dir| dir.join("views.toml"))
+    }
+
+    /// Load views from file. There are none if there is no file.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(s) => Self::from_toml(&s).map_err(|msg| format!("{}: {msg}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {e}", path.display())),
+        }
+    }
+
+    /// Write all views to file.
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("No configuration directory.")?;
+
+        std::fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| std::fs::write(&path, self.to_toml()))
+            .map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, String> {
+        let mut views = Self::default();
+
+        for (name, value) in toml::parse(s).map_err(|e| e.to_string())? {
+            let table = value
+                .as_table()
+                .ok_or_else(|| format!("\"{name}\": Expected table."))?;
+            let view = View::from_toml(table).map_err(|msg| format!("\"{name}\".{msg}"))?;
+            views.views.insert(name, view);
+        }
+
+        Ok(views)
+    }
+
+    pub fn to_toml(&self) -> String {
+        self.views
+            .iter()
+            .map(|(name, view)| {
+                let bare = name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+                match bare {
+                    true => format!("[{name}]\n{}", view.to_toml()),
+                    false => format!("[{name:?}]\n{}", view.to_toml()),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn get(&self, name: &str) -> Option<&View> {
+        self.views.get(name)
+    }
+
+    pub fn set(&mut self, name: String, view: View) {
+        self.views.insert(name, view);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera;
+
+    fn close(a: &VectorRow<f64, 3>, b: &VectorRow<f64, 3>) -> bool {
+        VectorRow::from(&a.0 - &b.0).length() < 1e-9
+    }
+
+    fn view(view_mode: ViewMode) -> View {
+        View {
+            position: VectorRow::from([1.0, -2.5, 3.0]),
+            rotation: camera::rotation(0.3, -1.2).0,
+            view_mode,
+            projection_mode: ProjectionMode::Perspective { fov: 70 },
+            orbit: Orbit {
+                target: VectorRow::from([0.0, 10.0, 0.0]),
+                distance: 2.0,
+            },
+        }
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let mut views = Views::default();
+        views.set("1".into(), view(ViewMode::FirstPerson));
+        views.set("top down".into(), view(ViewMode::Orbital));
+
+        let s = views.to_toml();
+        assert!(s.contains("[1]\n") && s.contains("[\"top down\"]\n"));
+
+        let parsed = Views::from_toml(&s).unwrap();
+        let (a, b) = (views.get("1").unwrap(), parsed.get("1").unwrap());
+        assert!(close(&a.position, &b.position));
+        assert!(a.rotation == b.rotation);
+        assert!(matches!(b.view_mode, ViewMode::FirstPerson));
+        assert!(matches!(
+            b.projection_mode,
+            ProjectionMode::Perspective { fov: 70 }
+        ));
+        assert!(close(&a.orbit.target, &b.orbit.target));
+        assert!(a.orbit.distance == b.orbit.distance);
+        assert!(matches!(
+            parsed.get("top down").unwrap().view_mode,
+            ViewMode::Orbital
+        ));
+    }
+
+    #[test]
+    fn toml_errors() {
+        let error = |s| Views::from_toml(s).err();
+        assert!(
+            error("[1]\nview_mode = \"orbital\"").as_deref()
+                == Some("\"1\".\"position\": Missing.")
+        );
+        assert!(error("1 = 2").as_deref() == Some("\"1\": Expected table."));
+        assert!(
+            error("[1]\nposition = [0, 0]").as_deref()
+                == Some("\"1\".\"position\": Expected 3 numbers.")
+        );
+    }
+
+    #[test]
+    fn transition() {
+        let camera = Camera {
+            position: VectorRow::from([5.0, 5.0, 5.0]),
+            ..Default::default()
+        };
+        let to = view(ViewMode::FirstPerson);
+        let mut transition = Transition::new(&camera, &Orbit::default(), to.clone(), 10);
+
+        // Starts where the camera is (switched to first person, the viewpoint is behind it).
+        let start = transition.advance(0);
+        assert!(close(&start.position, &Orbit::default().viewpoint(&camera)));
+
+        let middle = transition.advance(5);
+        assert!(!transition.done());
+        assert!(matches!(
+            middle.projection_mode,
+            ProjectionMode::Perspective { fov: 80 }
+        ));
+
+        let end = transition.advance(100);
+        assert!(transition.done());
+        assert!(close(&end.position, &to.position));
+        assert!(slerp(&end.rotation, &to.rotation, 0.0) == to.rotation);
+        assert!((end.orbit.distance - to.orbit.distance).abs() < 1e-9);
+    }
+
+    #[test]
+    fn slerp_halfway() {
+        let a = camera::rotation(0.0, 0.0).0;
+        let b = camera::rotation(0.0, 1.0).0;
+        let half = slerp(&a, &b, 0.5);
+        let (pitch, yaw) = camera::pitch_yaw(&(half.clone(), half.inverse()));
+        assert!(pitch.abs() < 1e-9 && (yaw - 0.5).abs() < 1e-9);
+
+        // The negated quaternion is the same rotation, and is not taken the long way around.
+        let b_negated = Quaternion(-b.0, -b.1, -b.2, -b.3);
+        let (_, yaw) = camera::pitch_yaw(&{
+            let half = slerp(&a, &b_negated, 0.5);
+            (half.clone(), half.inverse())
+        });
+        assert!((yaw - 0.5).abs() < 1e-9);
+    }
+}